@@ -0,0 +1,275 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        cookie::{Cookie, SameSite},
+        HeaderName, HeaderValue, Method, StatusCode,
+    },
+    Error, HttpMessage, HttpResponse, ResponseError,
+};
+use futures::future::{err, ok, Either, Ready};
+use rand::{distributions::Alphanumeric, Rng};
+use serde_json::json;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pub const SESSION_COOKIE: &str = "btp_session";
+pub const CSRF_COOKIE: &str = "btp_csrf";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+pub const INVALID_CSRF_TOKEN: &str = "INVALID_CSRF_TOKEN";
+
+const TOKEN_LENGTH: usize = 32;
+const COOKIE_PATH: &str = "/";
+const MESSAGE: &str = "The request is missing a valid CSRF token";
+
+pub fn token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .collect()
+}
+
+fn state_changing(method: &Method) -> bool {
+    match *method {
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE => false,
+        _ => true,
+    }
+}
+
+fn matches(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len()
+        && expected
+            .bytes()
+            .zip(actual.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+#[derive(Debug)]
+pub struct CsrfError;
+
+impl fmt::Display for CsrfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", MESSAGE)
+    }
+}
+
+impl ResponseError for CsrfError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::Forbidden().json(json!({ "code": INVALID_CSRF_TOKEN, "message": MESSAGE }))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Csrf {
+    session_cookie: String,
+    secure: bool,
+}
+
+impl Csrf {
+    pub fn new(session_cookie: &str, secure: bool) -> Csrf {
+        Csrf {
+            session_cookie: String::from(session_cookie),
+            secure,
+        }
+    }
+
+    fn cookie(&self, token: String) -> Cookie<'static> {
+        Cookie::build(CSRF_COOKIE, token)
+            .path(COOKIE_PATH)
+            .secure(self.secure)
+            .same_site(SameSite::Strict)
+            .finish()
+    }
+
+    fn verified(&self, req: &ServiceRequest) -> bool {
+        if !state_changing(req.method()) || req.cookie(&self.session_cookie).is_none() {
+            return true;
+        }
+        let header = req.headers().get(CSRF_HEADER).and_then(|value| value.to_str().ok());
+        match (req.cookie(CSRF_COOKIE), header) {
+            (Some(cookie), Some(header)) => !header.is_empty() && matches(cookie.value(), header),
+            _ => false,
+        }
+    }
+}
+
+impl<S, B> Transform<S> for Csrf
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CsrfMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CsrfMiddleware {
+            service,
+            csrf: self.clone(),
+        })
+    }
+}
+
+pub struct CsrfMiddleware<S> {
+    service: S,
+    csrf: Csrf,
+}
+
+impl<S, B> Service for CsrfMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Either<
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>,
+        Ready<Result<Self::Response, Self::Error>>,
+    >;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        if !self.csrf.verified(&req) {
+            return Either::Right(err(CsrfError.into()));
+        }
+        let existing = req.cookie(CSRF_COOKIE).map(|cookie| String::from(cookie.value()));
+        let issue = existing.is_none();
+        let token = existing.unwrap_or_else(token);
+        let cookie = self.csrf.cookie(token.clone());
+        let response = self.service.call(req);
+        Either::Left(Box::pin(async move {
+            let mut response = response.await?;
+            if issue {
+                response.response_mut().add_cookie(&cookie)?;
+            }
+            // A UI on another origin cannot read the cookie, so the token is
+            // echoed in a header it can read when the header is exposed.
+            if let Ok(value) = HeaderValue::from_str(&token) {
+                response.headers_mut().insert(HeaderName::from_static(CSRF_HEADER), value);
+            }
+            Ok(response)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::header, test, web, App};
+
+    const SESSION_COOKIE: &str = "session";
+
+    async fn status(req: test::TestRequest) -> StatusCode {
+        let mut server = test::init_service(
+            App::new()
+                .wrap(Csrf::new(SESSION_COOKIE, true))
+                .route("/", web::get().to(|| HttpResponse::Ok()))
+                .route("/", web::post().to(|| HttpResponse::Ok())),
+        )
+        .await;
+        match server.call(req.to_request()).await {
+            Ok(response) => response.status(),
+            Err(error) => error.as_response_error().status_code(),
+        }
+    }
+
+    #[test]
+    fn compares_tokens_exactly() {
+        let token = token();
+        assert!(matches(&token, &token));
+        assert!(!matches(&token, &token[1..]));
+        assert!(!matches(&token, &"a".repeat(TOKEN_LENGTH)));
+    }
+
+    #[actix_rt::test]
+    async fn issues_a_token_cookie() {
+        let mut server = test::init_service(
+            App::new()
+                .wrap(Csrf::new(SESSION_COOKIE, true))
+                .route("/", web::get().to(|| HttpResponse::Ok())),
+        )
+        .await;
+        let response = test::call_service(&mut server, test::TestRequest::get().to_request()).await;
+        let cookies = response.headers().get(header::SET_COOKIE).unwrap().to_str().unwrap();
+        assert!(cookies.starts_with(CSRF_COOKIE));
+    }
+
+    #[actix_rt::test]
+    async fn echoes_the_token_for_cross_origin_clients() {
+        let mut server = test::init_service(
+            App::new()
+                .wrap(Csrf::new(SESSION_COOKIE, true))
+                .route("/", web::get().to(|| HttpResponse::Ok()))
+                .route("/", web::post().to(|| HttpResponse::Ok())),
+        )
+        .await;
+        let response = test::call_service(&mut server, test::TestRequest::get().to_request()).await;
+        let issued = response.headers().get(CSRF_HEADER).unwrap().to_str().unwrap().to_owned();
+        let cookie = response.response().cookies().find(|cookie| cookie.name() == CSRF_COOKIE).unwrap();
+        assert_eq!(cookie.value(), issued);
+        let req = test::TestRequest::get()
+            .cookie(Cookie::new(CSRF_COOKIE, issued.clone()))
+            .to_request();
+        let response = test::call_service(&mut server, req).await;
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+        assert_eq!(response.headers().get(CSRF_HEADER).unwrap(), issued.as_str());
+        let req = test::TestRequest::post()
+            .header(header::ORIGIN, "https://ui.example")
+            .cookie(Cookie::new(SESSION_COOKIE, "jwt"))
+            .cookie(Cookie::new(CSRF_COOKIE, issued.clone()))
+            .header(CSRF_HEADER, issued)
+            .to_request();
+        assert_eq!(test::call_service(&mut server, req).await.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn allows_requests_without_a_session_cookie() {
+        assert_eq!(status(test::TestRequest::post()).await, StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_cookie_authenticated_requests_without_a_token() {
+        let req = test::TestRequest::post().cookie(Cookie::new(SESSION_COOKIE, "jwt"));
+        assert_eq!(status(req).await, StatusCode::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_mismatched_tokens() {
+        let req = test::TestRequest::post()
+            .cookie(Cookie::new(SESSION_COOKIE, "jwt"))
+            .cookie(Cookie::new(CSRF_COOKIE, "expected"))
+            .header(CSRF_HEADER, "forged");
+        assert_eq!(status(req).await, StatusCode::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn accepts_matching_tokens() {
+        let req = test::TestRequest::post()
+            .cookie(Cookie::new(SESSION_COOKIE, "jwt"))
+            .cookie(Cookie::new(CSRF_COOKIE, "expected"))
+            .header(CSRF_HEADER, "expected");
+        assert_eq!(status(req).await, StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn ignores_safe_methods() {
+        let req = test::TestRequest::get().cookie(Cookie::new(SESSION_COOKIE, "jwt"));
+        assert_eq!(status(req).await, StatusCode::OK);
+    }
+}
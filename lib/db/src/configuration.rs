@@ -4,12 +4,13 @@ use bb8_postgres::tokio_postgres;
 pub const POOL_SIZE: u32 = 15;
 const TIME_OUT: u32 = 10;
 
+#[derive(Clone, Debug)]
 pub struct Configuration {
-    pub password: &'static str,
-    pub user: &'static str,
-    pub host: &'static str,
-    pub port: &'static str,
-    pub database: &'static str,
+    pub password: String,
+    pub user: String,
+    pub host: String,
+    pub port: String,
+    pub database: String,
 }
 
 impl Configuration {
@@ -46,11 +47,11 @@ mod configuration_test {
         let password = "secret";
         let database = "auth";
         let config = Configuration {
-            user,
-            host,
-            port,
-            password,
-            database,
+            user: String::from(user),
+            host: String::from(host),
+            port: String::from(port),
+            password: String::from(password),
+            database: String::from(database),
         };
         let built = config.build().unwrap();
         assert_eq!(
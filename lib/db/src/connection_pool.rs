@@ -7,6 +7,13 @@ use std::{
 
 const SQL_EXTENSION: &str = "sql";
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PoolState {
+    pub connections: u32,
+    pub idle_connections: u32,
+    pub max_size: u32,
+}
+
 #[derive(Clone)]
 pub struct ConnectionPool {
     pool: Pool,
@@ -21,7 +28,7 @@ impl ConnectionPool {
             .build(manager)
             .await?;
         if environment::in_development() {
-            println!("Connected to database.");
+            tracing::info!("Connected to database");
         }
         Ok(ConnectionPool { pool })
     }
@@ -38,12 +45,21 @@ impl ConnectionPool {
         }
         Ok(())
     }
+    pub fn state(&self) -> PoolState {
+        let state = self.pool.state();
+        PoolState {
+            connections: state.connections,
+            idle_connections: state.idle_connections,
+            max_size: configuration::POOL_SIZE,
+        }
+    }
 }
 
 #[async_trait]
 pub trait ConnectionPoolTrait: Clone + Send + Sync {
     async fn client(&self) -> Result<client::Client<'_>>;
     async fn migrate(&self, path: &str) -> Result<()>;
+    fn state(&self) -> PoolState;
 }
 
 #[async_trait]
@@ -61,4 +77,7 @@ impl ConnectionPoolTrait for ConnectionPool {
         }
         Ok(())
     }
+    fn state(&self) -> PoolState {
+        ConnectionPool::state(self)
+    }
 }
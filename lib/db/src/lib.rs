@@ -21,6 +21,7 @@ pub use client::ClientTrait as Client;
 pub use configuration::Configuration;
 pub use connection_pool::ConnectionPool as DatabaseConnection;
 pub use connection_pool::ConnectionPoolTrait as Database;
+pub use connection_pool::PoolState;
 pub use std::time::SystemTime as TimeStamp;
 pub use tokio_postgres::Statement;
 pub use transaction::Transaction;
@@ -34,12 +35,22 @@ type ConnectionError = bb8::RunError<tokio_postgres::Error>;
 #[derive(Debug, Clone)]
 pub enum Error {
     Error(String),
+    UniqueViolation(String),
+}
+
+impl Error {
+    pub fn is_unique_violation(&self) -> bool {
+        match self {
+            Error::UniqueViolation(_) => true,
+            _ => false,
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::Error(error) => write!(f, "{}", error),
+            Error::Error(error) | Error::UniqueViolation(error) => write!(f, "{}", error),
         }
     }
 }
@@ -52,7 +63,11 @@ impl From<&str> for Error {
 
 impl From<tokio_postgres::Error> for Error {
     fn from(error: tokio_postgres::Error) -> Self {
-        Error::Error(error.to_string())
+        if error.code() == Some(&tokio_postgres::error::SqlState::UNIQUE_VIOLATION) {
+            Error::UniqueViolation(error.to_string())
+        } else {
+            Error::Error(error.to_string())
+        }
     }
 }
 
@@ -0,0 +1,211 @@
+use serde_json::{Map, Value};
+use std::{
+    env, fmt,
+    io::{self, Write},
+    str::FromStr,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer, Registry,
+};
+
+mod request_id;
+
+pub use request_id::{request_id, RequestId, RequestTracing, REQUEST_ID_HEADER};
+pub use tracing::{debug, error, info, instrument, warn};
+
+const LOG_LEVEL: &str = "LOG_LEVEL";
+const REDACTED: &str = "[redacted]";
+const MESSAGE: &str = "message";
+const SENSITIVE_FIELDS: [&str; 6] = ["password", "hash", "token", "secret", "authorization", "cookie"];
+
+pub fn redacted(field: &str) -> bool {
+    let field = field.to_lowercase();
+    SENSITIVE_FIELDS.iter().any(|sensitive| field.contains(sensitive))
+}
+
+struct Fields(Map<String, Value>);
+
+impl Fields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let name = field.name();
+        let value = if redacted(name) { Value::from(REDACTED) } else { value };
+        self.0.insert(String::from(name), value);
+    }
+}
+
+impl Visit for Fields {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::from(format!("{:?}", value)));
+    }
+}
+
+pub struct JsonLayer<W: Write + Send + 'static> {
+    service: String,
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send + 'static> JsonLayer<W> {
+    pub fn new(service: &str, writer: W) -> JsonLayer<W> {
+        JsonLayer {
+            service: String::from(service),
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: Write + Send + 'static,
+{
+    fn new_span(&self, attributes: &Attributes<'_>, id: &Id, context: Context<'_, S>) {
+        let mut fields = Fields(Map::new());
+        attributes.record(&mut fields);
+        if let Some(span) = context.span(id) {
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, context: Context<'_, S>) {
+        if let Some(span) = context.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+                values.record(fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, context: Context<'_, S>) {
+        let mut spans = vec![];
+        let mut current = context.lookup_current();
+        while let Some(span) = current {
+            current = span.parent();
+            spans.push(span);
+        }
+        let mut fields = Fields(Map::new());
+        let mut names = vec![];
+        for span in spans.iter().rev() {
+            names.push(Value::from(span.name()));
+            if let Some(span_fields) = span.extensions().get::<Fields>() {
+                fields.0.extend(span_fields.0.clone());
+            }
+        }
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        let mut line = Map::new();
+        line.insert(String::from("timestamp"), Value::from(timestamp));
+        line.insert(String::from("level"), Value::from(metadata.level().to_string()));
+        line.insert(String::from("service"), Value::from(self.service.clone()));
+        line.insert(String::from("target"), Value::from(metadata.target()));
+        line.insert(
+            String::from(MESSAGE),
+            fields.0.remove(MESSAGE).unwrap_or(Value::Null),
+        );
+        line.insert(String::from("spans"), Value::from(names));
+        line.extend(fields.0);
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{}", Value::Object(line));
+        }
+    }
+}
+
+pub fn level() -> LevelFilter {
+    env::var(LOG_LEVEL)
+        .ok()
+        .and_then(|level| LevelFilter::from_str(&level).ok())
+        .unwrap_or(LevelFilter::INFO)
+}
+
+pub fn init(service: &str) {
+    let subscriber = Registry::default()
+        .with(level())
+        .with(JsonLayer::new(service, io::stdout()));
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        warn!("A global logger was already initialized");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn logged(log: impl FnOnce()) -> Value {
+        let buffer = Buffer::default();
+        let subscriber = Registry::default().with(JsonLayer::new("test", buffer.clone()));
+        tracing::subscriber::with_default(subscriber, log);
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        serde_json::from_str(output.lines().last().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn writes_events_as_json() {
+        let line = logged(|| info!(attempts = 3, "signed in"));
+        assert_eq!(line["message"], "signed in");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["service"], "test");
+        assert_eq!(line["attempts"], 3);
+    }
+
+    #[test]
+    fn includes_fields_from_enclosing_spans() {
+        let line = logged(|| {
+            let span = tracing::info_span!("request", request_id = "abc");
+            let _entered = span.enter();
+            info!("handled");
+        });
+        assert_eq!(line["request_id"], "abc");
+        assert_eq!(line["spans"][0], "request");
+    }
+
+    #[test]
+    fn redacts_sensitive_fields() {
+        let line = logged(|| {
+            let span = tracing::info_span!("repository", reset_token = "secret-token");
+            let _entered = span.enter();
+            warn!(password = "hunter2", password_hash = "$argon2", name = "user", "saving");
+        });
+        assert_eq!(line["password"], REDACTED);
+        assert_eq!(line["password_hash"], REDACTED);
+        assert_eq!(line["reset_token"], REDACTED);
+        assert_eq!(line["name"], "user");
+    }
+}
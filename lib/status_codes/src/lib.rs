@@ -10,4 +10,6 @@ pub const FORBIDDEN: StatusCode = 403;
 pub const NOT_FOUND: StatusCode = 404;
 pub const CONFLICT: StatusCode = 409;
 pub const UNPROCESSABLE_ENTITY: StatusCode = 422;
+pub const PRECONDITION_REQUIRED: StatusCode = 428;
 pub const INTERNAL_SERVER_ERROR: StatusCode = 500;
+pub const SERVICE_UNAVAILABLE: StatusCode = 503;
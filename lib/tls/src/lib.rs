@@ -0,0 +1,200 @@
+use actix_rt::signal::unix::{signal, SignalKind};
+use rustls::{
+    internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys},
+    sign::{self, CertifiedKey},
+    AllowAnyAuthenticatedClient, Certificate, NoClientAuth, PrivateKey,
+    ResolvesServerCert, RootCertStore, ServerConfig, SignatureScheme,
+};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader},
+    sync::{Arc, RwLock},
+};
+use webpki::DNSNameRef;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Configuration {
+    pub certificate: String,
+    pub key: String,
+    pub client_ca: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Read(String, io::Error),
+    Certificate(String),
+    Key(String),
+    ClientCa(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Read(path, error) => write!(f, "Could not read \"{}\": {}", path, error),
+            Error::Certificate(path) => write!(f, "No valid PEM certificates found in \"{}\"", path),
+            Error::Key(path) => write!(f, "No supported PEM private key found in \"{}\"", path),
+            Error::ClientCa(path) => write!(f, "No valid client CA certificates found in \"{}\"", path),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, error.to_string())
+    }
+}
+
+fn open(path: &str) -> Result<BufReader<File>, Error> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|error| Error::Read(String::from(path), error))
+}
+
+fn certificates(path: &str) -> Result<Vec<Certificate>, Error> {
+    match certs(&mut open(path)?) {
+        Ok(certificates) if !certificates.is_empty() => Ok(certificates),
+        _ => Err(Error::Certificate(String::from(path))),
+    }
+}
+
+fn private_key(path: &str) -> Result<PrivateKey, Error> {
+    let pkcs8 = pkcs8_private_keys(&mut open(path)?).unwrap_or_default();
+    let rsa = rsa_private_keys(&mut open(path)?).unwrap_or_default();
+    pkcs8
+        .into_iter()
+        .chain(rsa)
+        .next()
+        .ok_or_else(|| Error::Key(String::from(path)))
+}
+
+fn client_roots(path: &str) -> Result<RootCertStore, Error> {
+    let mut roots = RootCertStore::empty();
+    match roots.add_pem_file(&mut open(path)?) {
+        Ok((valid, _)) if valid > 0 => Ok(roots),
+        _ => Err(Error::ClientCa(String::from(path))),
+    }
+}
+
+pub fn certified_key(configuration: &Configuration) -> Result<CertifiedKey, Error> {
+    let chain = certificates(&configuration.certificate)?;
+    let key = private_key(&configuration.key)?;
+    let signing_key =
+        sign::any_supported_type(&key).map_err(|_| Error::Key(configuration.key.clone()))?;
+    Ok(CertifiedKey::new(chain, Arc::new(signing_key)))
+}
+
+pub struct ReloadableCertificate {
+    configuration: Configuration,
+    current: RwLock<CertifiedKey>,
+}
+
+impl ReloadableCertificate {
+    pub fn new(configuration: Configuration) -> Result<ReloadableCertificate, Error> {
+        let current = RwLock::new(certified_key(&configuration)?);
+        Ok(ReloadableCertificate { configuration, current })
+    }
+
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    pub fn reload(&self) -> Result<(), Error> {
+        let key = certified_key(&self.configuration)?;
+        if let Ok(mut current) = self.current.write() {
+            *current = key;
+        }
+        Ok(())
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertificate {
+    fn resolve(&self, _: Option<DNSNameRef>, _: &[SignatureScheme]) -> Option<CertifiedKey> {
+        self.current.read().ok().map(|current| current.clone())
+    }
+}
+
+pub fn server_config(certificate: Arc<ReloadableCertificate>) -> Result<ServerConfig, Error> {
+    let mut config = match &certificate.configuration().client_ca {
+        Some(path) => ServerConfig::new(AllowAnyAuthenticatedClient::new(client_roots(path)?)),
+        None => ServerConfig::new(NoClientAuth::new()),
+    };
+    config.cert_resolver = certificate;
+    Ok(config)
+}
+
+pub fn reload_on_hangup(certificate: Arc<ReloadableCertificate>) -> io::Result<()> {
+    let mut hangup = signal(SignalKind::hangup())?;
+    actix_rt::spawn(async move {
+        while hangup.recv().await.is_some() {
+            match certificate.reload() {
+                Ok(()) => tracing::info!(
+                    certificate = %certificate.configuration().certificate,
+                    "Reloaded TLS certificate"
+                ),
+                Err(error) => tracing::error!(error = %error, "Keeping the current TLS certificate"),
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    fn temporary_file(name: &str, contents: &str) -> String {
+        let path = env::temp_dir().join(format!("tls_{}_{}", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        String::from(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn reports_missing_files() {
+        let configuration = Configuration {
+            certificate: String::from("/does/not/exist.pem"),
+            key: String::from("/does/not/exist.key"),
+            client_ca: None,
+        };
+        match certified_key(&configuration) {
+            Err(Error::Read(path, _)) => assert_eq!(path, configuration.certificate),
+            _ => panic!("Expected a read error"),
+        }
+    }
+
+    #[test]
+    fn reports_files_without_certificates() {
+        let path = temporary_file("empty_certificate", "not a certificate");
+        let result = certificates(&path);
+        fs::remove_file(&path).unwrap();
+        match result {
+            Err(Error::Certificate(reported)) => assert_eq!(reported, path),
+            _ => panic!("Expected a certificate error"),
+        }
+    }
+
+    #[test]
+    fn reports_files_without_keys() {
+        let path = temporary_file("empty_key", "not a key");
+        let result = private_key(&path);
+        fs::remove_file(&path).unwrap();
+        match result {
+            Err(Error::Key(reported)) => assert_eq!(reported, path),
+            _ => panic!("Expected a key error"),
+        }
+    }
+
+    #[test]
+    fn reports_files_without_client_ca_certificates() {
+        let path = temporary_file("empty_ca", "not a ca");
+        let result = client_roots(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(match result {
+            Err(Error::ClientCa(reported)) => reported == path,
+            _ => false,
+        });
+    }
+}
@@ -0,0 +1,11 @@
+use crate::connection;
+use actix_web::HttpRequest;
+use reqwest::{Client, Method, RequestBuilder};
+
+pub fn request(client: &Client, method: Method, path: &str, req: &HttpRequest) -> RequestBuilder {
+    let builder = client.request(method, &format!("{}{}", connection::auth(), path));
+    match logging::request_id(req) {
+        Some(id) => builder.header(logging::REQUEST_ID_HEADER, id),
+        None => builder,
+    }
+}
@@ -1,11 +1,12 @@
 use super::Mutation;
 use crate::models::playground;
-use crate::redis;
+use crate::{metrics, redis};
 use juniper::FieldResult;
 
 #[juniper::object]
 impl Mutation {
     fn create_human(new_human: playground::NewHuman) -> FieldResult<playground::Human> {
+        let _timer = metrics::time_resolver("Mutation.createHuman");
         let human = playground::Human {
             id: "1234".to_owned(),
             name: new_human.name.clone(),
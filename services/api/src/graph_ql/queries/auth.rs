@@ -1,12 +1,14 @@
-use crate::models::auth;
+use crate::{metrics, models::auth};
 use juniper::FieldResult;
+use logging::debug;
 
 pub struct Auth;
 
 #[juniper::object]
 impl Auth {
     fn get_credentials(_username: String) -> FieldResult<auth::Credentials> {
-        println!("getting credentials");
+        let _timer = metrics::time_resolver("Auth.getCredentials");
+        debug!("Getting credentials");
         Ok(auth::Credentials {
             username: "testing".to_owned(),
             password: "hashedPassword".to_owned(),
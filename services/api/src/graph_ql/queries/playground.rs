@@ -1,5 +1,5 @@
 use crate::models::playground;
-use crate::redis;
+use crate::{metrics, redis};
 use juniper;
 
 pub struct Playground;
@@ -7,6 +7,7 @@ pub struct Playground;
 #[juniper::object]
 impl Playground {
     fn human(id: String) -> juniper::FieldResult<playground::Human> {
+        let _timer = metrics::time_resolver("Playground.human");
         Ok(redis::get_human_from_cache(id)?)
     }
 }
@@ -1,6 +1,8 @@
 use std::sync::{Arc, Mutex};
 
+pub mod auth;
 pub mod graph_ql;
+pub mod metrics;
 pub mod models;
 pub mod redis;
 pub mod routes;
@@ -15,7 +17,26 @@ pub mod connection {
     const PORT: &str = "PORT";
     const ADDRESS: &str = "IP";
     const DEFAULT_PORT: &str = "3000";
+    const AUTH_URL: &str = "AUTH_URL";
+    const DEFAULT_AUTH_URL: &str = "http://127.0.0.1:8080";
+    const SHUTDOWN_TIMEOUT: &str = "SHUTDOWN_TIMEOUT";
+    const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
     const ALL_ADDRESSES: &str = "0.0.0.0";
+    const TLS_CERTIFICATE: &str = "TLS_CERTIFICATE";
+    const TLS_KEY: &str = "TLS_KEY";
+    const TLS_CLIENT_CA: &str = "TLS_CLIENT_CA";
+    const CORS_ORIGINS: &str = "CORS_ORIGINS";
+    const CORS_CREDENTIALS: &str = "CORS_CREDENTIALS";
+    const CORS_MAX_AGE: &str = "CORS_MAX_AGE";
+    const DEFAULT_CORS_MAX_AGE: usize = 3600;
+    const COOKIE_SECURE: &str = "COOKIE_SECURE";
+    const AUTH_EVENTS_REDIS_URL: &str = "AUTH_EVENTS_REDIS_URL";
+    const AUTH_EVENTS_STREAM: &str = "AUTH_EVENTS_STREAM";
+    const DEFAULT_AUTH_EVENTS_STREAM: &str = "auth:events";
+    const AUTH_EVENTS_GROUP: &str = "AUTH_EVENTS_GROUP";
+    const DEFAULT_AUTH_EVENTS_GROUP: &str = "api";
+    const AUTH_EVENTS_CONSUMER: &str = "AUTH_EVENTS_CONSUMER";
+    const HOSTNAME: &str = "HOSTNAME";
 
     pub const GRAPHQL_ENDPOINT: &str = "graphql";
     pub const REDIS_IP: &str = "127.0.0.2:6379";
@@ -26,6 +47,70 @@ pub mod connection {
         format!("{}:{}", ip, port)
     }
 
+    pub fn shutdown_timeout() -> u64 {
+        environment::env_or_default(SHUTDOWN_TIMEOUT, DEFAULT_SHUTDOWN_TIMEOUT)
+            .parse()
+            .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT)
+    }
+
+    pub fn tls() -> Option<tls::Configuration> {
+        match (std::env::var(TLS_CERTIFICATE), std::env::var(TLS_KEY)) {
+            (Ok(certificate), Ok(key)) => Some(tls::Configuration {
+                certificate,
+                key,
+                client_ca: std::env::var(TLS_CLIENT_CA).ok(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn flag(variable: &str, default: bool) -> bool {
+        std::env::var(variable)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(default)
+    }
+
+    pub fn cors_origins() -> Vec<String> {
+        environment::env_or_default(CORS_ORIGINS, "")
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn cors_credentials() -> bool {
+        flag(CORS_CREDENTIALS, false)
+    }
+
+    pub fn cors_max_age() -> usize {
+        environment::env_or_default(CORS_MAX_AGE, DEFAULT_CORS_MAX_AGE)
+            .parse()
+            .unwrap_or(DEFAULT_CORS_MAX_AGE)
+    }
+
+    pub fn secure_cookies() -> bool {
+        flag(COOKIE_SECURE, true)
+    }
+
+    pub fn auth() -> String {
+        environment::env_or_default(AUTH_URL, DEFAULT_AUTH_URL)
+    }
+
+    pub fn auth_events() -> Option<crate::redis::events::Subscription> {
+        let url = std::env::var(AUTH_EVENTS_REDIS_URL).ok()?;
+        let consumer = std::env::var(AUTH_EVENTS_CONSUMER)
+            .or_else(|_| std::env::var(HOSTNAME))
+            .unwrap_or_else(|_| String::from(DEFAULT_AUTH_EVENTS_GROUP));
+        Some(crate::redis::events::Subscription {
+            url,
+            stream: environment::env_or_default(AUTH_EVENTS_STREAM, DEFAULT_AUTH_EVENTS_STREAM),
+            group: environment::env_or_default(AUTH_EVENTS_GROUP, DEFAULT_AUTH_EVENTS_GROUP),
+            consumer,
+        })
+    }
+
     pub fn graphql() -> String {
         format!("http://{}/{}", uri(), GRAPHQL_ENDPOINT)
     }
@@ -1,40 +1,110 @@
-use actix_web::{web, App, HttpServer};
-use btp_api_server::{connection, graph_ql, routes, AppData};
-use std::sync::Mutex;
+use actix_cors::{Cors, CorsFactory};
+use actix_web::{
+    dev::Service,
+    http::{header, HeaderName},
+    middleware::Condition,
+    web, App, HttpServer,
+};
+use btp_api_server::{connection, graph_ql, metrics, redis::events, routes, AppData};
+use futures::future::FutureExt;
+use logging::info;
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+const SERVICE_NAME: &str = "api";
+const ALL_ORIGINS: &str = "*";
+const ALLOWED_METHODS: [&str; 4] = ["GET", "POST", "PUT", "OPTIONS"];
+
+fn cors(origins: &[String]) -> Condition<CorsFactory> {
+    let request_id = HeaderName::from_static(logging::REQUEST_ID_HEADER);
+    let cors = origins
+        .iter()
+        .filter(|origin| *origin != ALL_ORIGINS)
+        .fold(Cors::new(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(ALLOWED_METHODS.to_vec())
+        .allowed_headers(vec![
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static(csrf::CSRF_HEADER),
+            request_id.clone(),
+        ])
+        .expose_headers(vec![header::AUTHORIZATION, HeaderName::from_static(csrf::CSRF_HEADER), request_id])
+        .max_age(connection::cors_max_age());
+    let cors = if connection::cors_credentials() {
+        cors.supports_credentials()
+    } else {
+        cors
+    };
+    Condition::new(!origins.is_empty(), cors.finish())
+}
 
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
+    logging::init(SERVICE_NAME);
     let uri = connection::uri();
     let data = web::Data::new(AppData {
         schema: graph_ql::graph_schema(),
         app_name: String::from("ByThePeoples"),
         counter: Mutex::new(0),
     });
+    if let Some(subscription) = connection::auth_events() {
+        events::subscribe(subscription, |event| {
+            info!(id = event.id, event_type = %event.event_type, aggregate_id = event.aggregate_id, "Received auth event");
+        });
+    }
+    let origins = connection::cors_origins();
+    let secure_cookies = connection::secure_cookies();
 
     let mut server = HttpServer::new(move || {
         App::new()
+            .wrap(csrf::Csrf::new(csrf::SESSION_COOKIE, secure_cookies))
+            .wrap(cors(&origins))
+            .wrap_fn(|req, srv| {
+                let started = Instant::now();
+                let method = req.method().to_string();
+                srv.call(req).map(move |response| {
+                    if let Ok(response) = &response {
+                        let route = response.request().match_pattern();
+                        metrics::request(&method, route, response.status().as_str(), started);
+                    }
+                    response
+                })
+            })
+            .wrap(logging::RequestTracing)
             .app_data(data.clone())
             .configure(routes::configuration)
-    });
+    })
+    .shutdown_timeout(connection::shutdown_timeout());
 
     if environment::in_production() {
-        println!("in production");
-        server = server.bind(&uri)?;
+        info!("In production");
+        server = match connection::tls() {
+            Some(configuration) => {
+                let certificate = Arc::new(tls::ReloadableCertificate::new(configuration)?);
+                let config = tls::server_config(certificate.clone())?;
+                tls::reload_on_hangup(certificate)?;
+                info!("TLS enabled");
+                server.bind_rustls(&uri, config)?
+            }
+            None => server.bind(&uri)?,
+        };
     } else {
         use listenfd::ListenFd;
         let mut listen = ListenFd::from_env();
 
-        println!("Running in development mode.");
+        info!("Running in development mode");
 
         server = if let Some(listener) = listen.take_tcp_listener(0).unwrap() {
-            println!("Hot reloading enabled.");
+            info!("Hot reloading enabled");
             server.listen(listener)?
         } else {
             server.bind(&uri)?
         };
     };
 
-    println!("Listening at {}", &uri);
+    info!(uri = %uri, "Listening");
 
     server.run().await
 }
@@ -0,0 +1,56 @@
+use once_cell::sync::Lazy;
+use prometheus::{Encoder, HistogramOpts, HistogramTimer, HistogramVec, Registry, TextEncoder};
+use std::time::Instant;
+
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+pub const METRICS_ROUTE: &str = "/metrics";
+
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+struct Metrics {
+    registry: Registry,
+    http_request_duration: HistogramVec,
+    resolver_duration: HistogramVec,
+}
+
+impl Metrics {
+    fn new() -> Metrics {
+        let metrics = Metrics {
+            registry: Registry::new(),
+            http_request_duration: HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "HTTP request duration by route"),
+                &["method", "route", "status"],
+            )
+            .unwrap(),
+            resolver_duration: HistogramVec::new(
+                HistogramOpts::new("graphql_resolver_duration_seconds", "GraphQL resolver duration"),
+                &["resolver"],
+            )
+            .unwrap(),
+        };
+        let registry = &metrics.registry;
+        registry.register(Box::new(metrics.http_request_duration.clone())).unwrap();
+        registry.register(Box::new(metrics.resolver_duration.clone())).unwrap();
+        metrics
+    }
+}
+
+static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+pub fn request(method: &str, route: Option<String>, status: &str, started: Instant) {
+    let route = route.unwrap_or_else(|| String::from(UNMATCHED_ROUTE));
+    METRICS
+        .http_request_duration
+        .with_label_values(&[method, &route, status])
+        .observe(started.elapsed().as_secs_f64());
+}
+
+pub fn time_resolver(resolver: &str) -> HistogramTimer {
+    METRICS.resolver_duration.with_label_values(&[resolver]).start_timer()
+}
+
+pub fn export() -> prometheus::Result<String> {
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&METRICS.registry.gather(), &mut buffer)?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
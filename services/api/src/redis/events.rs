@@ -0,0 +1,180 @@
+use logging::{debug, info, warn};
+use redis::{Client, Connection, RedisResult, Value};
+use std::{
+    collections::{HashSet, VecDeque},
+    thread,
+    time::Duration,
+};
+
+const BATCH_SIZE: usize = 100;
+const BLOCK_MILLISECONDS: usize = 5000;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const REMEMBERED_EVENTS: usize = 10000;
+const PENDING: &str = "0";
+const NEW: &str = ">";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AuthEvent {
+    pub entry: String,
+    pub id: i64,
+    pub event_type: String,
+    pub aggregate_id: i32,
+    pub payload: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Subscription {
+    pub url: String,
+    pub stream: String,
+    pub group: String,
+    pub consumer: String,
+}
+
+/// The relay delivers at least once, so the same outbox id may arrive
+/// under several stream entries.
+struct Seen {
+    ids: HashSet<i64>,
+    order: VecDeque<i64>,
+}
+
+impl Seen {
+    fn new() -> Seen {
+        Seen { ids: HashSet::new(), order: VecDeque::new() }
+    }
+    fn first_time(&mut self, id: i64) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > REMEMBERED_EVENTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::Data(bytes) => String::from_utf8(bytes.clone()).ok(),
+        Value::Status(status) => Some(status.clone()),
+        _ => None,
+    }
+}
+
+fn event(entry: &Value) -> Option<AuthEvent> {
+    let (entry, fields) = match entry {
+        Value::Bulk(items) if items.len() == 2 => (text(&items[0])?, &items[1]),
+        _ => return None,
+    };
+    let fields = match fields {
+        Value::Bulk(fields) => fields,
+        _ => return None,
+    };
+    let mut event = AuthEvent { entry, ..AuthEvent::default() };
+    for pair in fields.chunks(2) {
+        let value = pair.get(1).and_then(text)?;
+        match text(&pair[0])?.as_str() {
+            "id" => event.id = value.parse().ok()?,
+            "type" => event.event_type = value,
+            "aggregate_id" => event.aggregate_id = value.parse().ok()?,
+            "payload" => event.payload = value,
+            _ => {}
+        }
+    }
+    Some(event)
+}
+
+/// Flattens an XREADGROUP reply into its entries. Entries that cannot be
+/// parsed are returned as `None` so they can still be acknowledged.
+fn entries(reply: &Value) -> Vec<(String, Option<AuthEvent>)> {
+    let streams = match reply {
+        Value::Bulk(streams) => streams,
+        _ => return vec![],
+    };
+    streams
+        .iter()
+        .filter_map(|stream| match stream {
+            Value::Bulk(parts) if parts.len() == 2 => match &parts[1] {
+                Value::Bulk(entries) => Some(entries),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|entry| match entry {
+            Value::Bulk(items) if !items.is_empty() => Some((text(&items[0])?, event(entry))),
+            _ => None,
+        })
+        .collect()
+}
+
+fn create_group(connection: &mut Connection, subscription: &Subscription) -> RedisResult<()> {
+    let created: RedisResult<()> = redis::cmd("XGROUP")
+        .arg("CREATE")
+        .arg(&subscription.stream)
+        .arg(&subscription.group)
+        .arg(PENDING)
+        .arg("MKSTREAM")
+        .query(connection);
+    match created {
+        Err(error) if error.code() == Some("BUSYGROUP") => Ok(()),
+        result => result,
+    }
+}
+
+fn read(connection: &mut Connection, subscription: &Subscription, from: &str) -> RedisResult<Value> {
+    redis::cmd("XREADGROUP")
+        .arg("GROUP")
+        .arg(&subscription.group)
+        .arg(&subscription.consumer)
+        .arg("COUNT")
+        .arg(BATCH_SIZE)
+        .arg("BLOCK")
+        .arg(BLOCK_MILLISECONDS)
+        .arg("STREAMS")
+        .arg(&subscription.stream)
+        .arg(from)
+        .query(connection)
+}
+
+fn consume<F: Fn(&AuthEvent)>(subscription: &Subscription, seen: &mut Seen, handle: &F) -> RedisResult<()> {
+    let mut connection = Client::open(subscription.url.as_str())?.get_connection()?;
+    create_group(&mut connection, subscription)?;
+    // Entries delivered before a restart but never acknowledged come first.
+    let mut from = PENDING;
+    loop {
+        let reply = read(&mut connection, subscription, from)?;
+        let entries = entries(&reply);
+        if from == PENDING && entries.is_empty() {
+            from = NEW;
+            continue;
+        }
+        for (entry, event) in entries.iter() {
+            match event {
+                Some(event) if seen.first_time(event.id) => handle(event),
+                Some(event) => debug!(id = event.id, "Skipped duplicate auth event"),
+                None => warn!(entry = %entry, "Skipped malformed auth event"),
+            }
+            redis::cmd("XACK")
+                .arg(&subscription.stream)
+                .arg(&subscription.group)
+                .arg(entry)
+                .query::<i64>(&mut connection)?;
+        }
+    }
+}
+
+pub fn subscribe<F: Fn(&AuthEvent) + Send + 'static>(subscription: Subscription, handle: F) {
+    thread::spawn(move || {
+        let mut seen = Seen::new();
+        info!(stream = %subscription.stream, group = %subscription.group, "Consuming auth events");
+        loop {
+            if let Err(error) = consume(&subscription, &mut seen, &handle) {
+                warn!(error = %error, "Auth event consumer failed; reconnecting");
+            }
+            thread::sleep(RETRY_DELAY);
+        }
+    });
+}
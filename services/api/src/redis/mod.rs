@@ -1,6 +1,9 @@
+pub mod events;
+
 use crate::connection;
 use crate::models::playground;
 use crate::models::playground::Episode;
+use logging::debug;
 use redis::{Client, Commands};
 
 fn get_connection() -> redis::RedisResult<redis::Connection> {
@@ -8,6 +11,11 @@ fn get_connection() -> redis::RedisResult<redis::Connection> {
     Ok(client.get_connection()?)
 }
 
+pub fn ping() -> redis::RedisResult<()> {
+    let mut con = get_connection()?;
+    redis::cmd("PING").query(&mut con)
+}
+
 pub fn cache_human(human: &playground::Human) -> redis::RedisResult<playground::Human> {
     let mut con = get_connection()?;
     let playground::Human {
@@ -37,7 +45,6 @@ pub fn get_human_from_cache(id: String) -> redis::RedisResult<playground::Human>
     let name: String = con.get(format!("{}-name", &id))?;
     let home_planet: String = con.get(format!("{}-home_planet", &id))?;
     let films: String = con.get(format!("{}-appears_in", &id))?;
-    println!("response {}, {}, : {}", name, home_planet, films);
     let appears_in: Vec<Episode> = vec![films]
         .iter()
         .map(|film| Episode::from_string(film))
@@ -48,7 +55,7 @@ pub fn get_human_from_cache(id: String) -> redis::RedisResult<playground::Human>
         .map(|f| f.unwrap())
         .collect();
 
-    println!("response {}, {:?}, {}", name, appears_in, home_planet);
+    debug!(id = %id, "Read human from cache");
 
     Ok(playground::Human {
         id,
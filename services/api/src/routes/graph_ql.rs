@@ -2,6 +2,7 @@ use crate::connection;
 use actix_web::{web, Error, HttpResponse};
 use juniper::http::GraphQLRequest;
 use juniper::http::{graphiql, GraphQLResponse};
+use logging::debug;
 
 pub async fn graph_iql() -> HttpResponse {
     let graphql_endpoint = connection::graphql();
@@ -20,7 +21,7 @@ pub async fn graph_ql(
         Ok::<_, serde_json::error::Error>(serde_json::to_string(&res)?)
     })
     .await?;
-    println!("res: {}", response);
+    debug!(bytes = response.len(), "Executed GraphQL request");
     Ok(HttpResponse::Ok()
         .content_type("application/json")
         .body(response))
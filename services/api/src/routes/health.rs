@@ -0,0 +1,22 @@
+use crate::redis;
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+const UP: &str = "up";
+const DOWN: &str = "down";
+
+pub async fn live() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "status": UP }))
+}
+
+pub async fn ready() -> HttpResponse {
+    match web::block(redis::ping).await {
+        Ok(_) => HttpResponse::Ok().json(json!({ "status": UP, "redis": UP })),
+        Err(_) => HttpResponse::ServiceUnavailable().json(json!({ "status": DOWN, "redis": DOWN })),
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/live").route(web::get().to(live)))
+        .service(web::resource("/ready").route(web::get().to(ready)));
+}
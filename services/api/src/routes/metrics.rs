@@ -0,0 +1,9 @@
+use crate::metrics;
+use actix_web::HttpResponse;
+
+pub async fn export() -> HttpResponse {
+    match metrics::export() {
+        Ok(body) => HttpResponse::Ok().content_type(metrics::CONTENT_TYPE).body(body),
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
+}
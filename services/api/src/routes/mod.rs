@@ -1,6 +1,8 @@
 use actix_web::{web, HttpResponse, Responder};
 
 mod graph_ql;
+mod health;
+mod metrics;
 mod user;
 
 async fn hello_world(data: web::Data<super::AppData>) -> impl Responder {
@@ -14,6 +16,8 @@ async fn hello_world(data: web::Data<super::AppData>) -> impl Responder {
 
 pub fn configuration(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("/").route(web::get().to(hello_world)))
+        .route(crate::metrics::METRICS_ROUTE, web::get().to(metrics::export))
+        .service(web::scope("/health").configure(health::config))
         .service(web::scope("").configure(graph_ql::configuration))
         .service(web::scope("/model").configure(user::config));
 }
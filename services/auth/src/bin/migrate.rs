@@ -0,0 +1,28 @@
+use btp_auth_server::{
+    configuration::Settings,
+    migration, model,
+};
+use logging::error;
+
+const SERVICE_NAME: &str = "auth-migrate";
+const DATABASE_INITIALIZATION_FAILURE: &str = "Failed to initialize database";
+const MIGRATION_FAILURE: &str = "Failed to migrate the database";
+const INVALID_SETTINGS_EXIT_CODE: i32 = 1;
+
+/// Migrates the database ahead of a production deploy, where the server
+/// itself does not.
+#[actix_rt::main]
+async fn main() {
+    logging::init(SERVICE_NAME);
+    let settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(error) => {
+            error!(problems = ?error.problems, "Invalid settings");
+            std::process::exit(INVALID_SETTINGS_EXIT_CODE);
+        }
+    };
+    let db = model::DatabaseConnection::new(settings.database.configuration())
+        .await
+        .expect(DATABASE_INITIALIZATION_FAILURE);
+    migration::run(&db).await.expect(MIGRATION_FAILURE);
+}
@@ -0,0 +1,16 @@
+use crate::{model, Result};
+use async_trait::async_trait;
+use std::marker::{Send, Sync};
+
+mod proof_of_work;
+
+pub use proof_of_work::*;
+
+/// Issues and checks the challenges required after repeated failures. The
+/// proof of work verifier is self-hosted; a third-party CAPTCHA would
+/// implement the same trait.
+#[async_trait]
+pub trait Verifier: Clone + Send + Sync {
+    async fn issue(&self, failures: i64) -> Result<model::Challenge>;
+    async fn verify(&self, response: &model::ChallengeResponse, failures: i64) -> Result<bool>;
+}
@@ -0,0 +1,202 @@
+use crate::{
+    configuration::settings::ChallengeSettings,
+    model, repository,
+    utilities::hash,
+    Result,
+};
+use async_trait::async_trait;
+use ring::{constant_time, digest, hmac};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SEPARATOR: char = '.';
+const MAXIMUM_SOLUTION_LENGTH: usize = 64;
+
+pub type AppChallengeVerifier = ProofOfWork<repository::AppChallenges>;
+
+fn sign(secret: &str, payload: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::sign(&key, payload.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn leading_zero_bits(bytes: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in bytes {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Whether SHA-256 of `challenge:solution` starts with `difficulty` zero bits.
+pub fn solves(challenge: &str, solution: &str, difficulty: u32) -> bool {
+    let attempt = format!("{}:{}", challenge, solution);
+    leading_zero_bits(digest::digest(&digest::SHA256, attempt.as_bytes()).as_ref()) >= difficulty
+}
+
+struct Issued {
+    expires_at: u64,
+    difficulty: u32,
+}
+
+fn parse(secret: &str, challenge: &str) -> Option<Issued> {
+    let parts: Vec<&str> = challenge.split(SEPARATOR).collect();
+    if parts.len() != 4 {
+        return None;
+    }
+    let payload = parts[..3].join(&SEPARATOR.to_string());
+    constant_time::verify_slices_are_equal(sign(secret, &payload).as_bytes(), parts[3].as_bytes()).ok()?;
+    Some(Issued {
+        expires_at: parts[0].parse().ok()?,
+        difficulty: parts[1].parse().ok()?,
+    })
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+#[derive(Clone, Debug)]
+pub struct ProofOfWork<R: repository::Challenges> {
+    store: R,
+    settings: ChallengeSettings,
+}
+
+impl<R: repository::Challenges> ProofOfWork<R> {
+    pub fn new(store: R, settings: &ChallengeSettings) -> ProofOfWork<R> {
+        ProofOfWork {
+            store,
+            settings: settings.clone(),
+        }
+    }
+    /// Each further `threshold` failures adds a bit of difficulty.
+    pub fn difficulty(&self, failures: i64) -> u32 {
+        let extra = (failures - self.settings.threshold).max(0) / self.settings.threshold.max(1);
+        (self.settings.difficulty as i64 + extra).min(self.settings.maximum_difficulty as i64) as u32
+    }
+}
+
+#[async_trait]
+impl<R: repository::Challenges> super::Verifier for ProofOfWork<R> {
+    async fn issue(&self, failures: i64) -> Result<model::Challenge> {
+        let expires_at = seconds(SystemTime::now()) + self.settings.lifetime;
+        let difficulty = self.difficulty(failures);
+        let payload = format!("{}{}{}{}{}", expires_at, SEPARATOR, difficulty, SEPARATOR, hash::token());
+        Ok(model::Challenge {
+            algorithm: String::from(model::PROOF_OF_WORK),
+            challenge: format!("{}{}{}", payload, SEPARATOR, sign(&self.settings.secret, &payload)),
+            difficulty,
+            expires_at,
+        })
+    }
+    async fn verify(&self, response: &model::ChallengeResponse, failures: i64) -> Result<bool> {
+        let issued = match parse(&self.settings.secret, &response.challenge) {
+            Some(issued) => issued,
+            None => return Ok(false),
+        };
+        if issued.expires_at <= seconds(SystemTime::now())
+            || issued.difficulty < self.difficulty(failures)
+            || response.solution.len() > MAXIMUM_SOLUTION_LENGTH
+            || !solves(&response.challenge, &response.solution, issued.difficulty)
+        {
+            return Ok(false);
+        }
+        self.store
+            .redeem(
+                &hash::digest(&response.challenge),
+                UNIX_EPOCH + Duration::from_secs(issued.expires_at),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{challenge::Verifier, utilities::test::{fake, mock::MockChallenges}};
+    use actix_rt;
+
+    const EASY: u32 = 4;
+
+    fn verifier() -> ProofOfWork<MockChallenges<crate::model::DatabaseConnection>> {
+        let mut settings = fake::settings().challenge.clone();
+        settings.difficulty = EASY;
+        settings.maximum_difficulty = EASY + 2;
+        settings.threshold = 5;
+        settings.secret = String::from("challenge secret");
+        ProofOfWork::new(MockChallenges::new(), &settings)
+    }
+
+    fn solve(challenge: &model::Challenge) -> model::ChallengeResponse {
+        let solution = (0u64..)
+            .map(|nonce| nonce.to_string())
+            .find(|nonce| solves(&challenge.challenge, nonce, challenge.difficulty))
+            .unwrap();
+        model::ChallengeResponse {
+            challenge: challenge.challenge.clone(),
+            solution,
+        }
+    }
+
+    #[test]
+    fn counts_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0, 0x0f, 0xff]), 12);
+        assert_eq!(leading_zero_bits(&[0x80]), 0);
+    }
+
+    #[test]
+    fn raises_the_difficulty_with_failures_up_to_the_maximum() {
+        let verifier = verifier();
+        assert_eq!(verifier.difficulty(0), EASY);
+        assert_eq!(verifier.difficulty(10), EASY + 1);
+        assert_eq!(verifier.difficulty(1000), EASY + 2);
+    }
+
+    #[actix_rt::test]
+    async fn accepts_a_solved_challenge() {
+        let mut verifier = verifier();
+        verifier.store.redeem.returns(true);
+        let challenge = verifier.issue(5).await.unwrap();
+        assert!(verifier.verify(&solve(&challenge), 5).await.unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn rejects_a_replayed_challenge() {
+        let mut verifier = verifier();
+        verifier.store.redeem.returns(false);
+        let challenge = verifier.issue(5).await.unwrap();
+        assert!(!verifier.verify(&solve(&challenge), 5).await.unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn rejects_a_tampered_challenge() {
+        let verifier = verifier();
+        let mut challenge = verifier.issue(5).await.unwrap();
+        challenge.challenge = challenge.challenge.replacen(&format!(".{}.", EASY), ".0.", 1);
+        challenge.difficulty = 0;
+        assert!(!verifier.verify(&solve(&challenge), 5).await.unwrap());
+        assert_eq!(verifier.store.redeem.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_a_challenge_easier_than_the_failures_require() {
+        let verifier = verifier();
+        let challenge = verifier.issue(0).await.unwrap();
+        assert!(!verifier.verify(&solve(&challenge), 1000).await.unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn rejects_a_challenge_signed_with_another_secret() {
+        let verifier = verifier();
+        let mut other = verifier.settings.clone();
+        other.secret = String::from("another secret");
+        let challenge = ProofOfWork::new(MockChallenges::new(), &other).issue(5).await.unwrap();
+        assert!(!verifier.verify(&solve(&challenge), 5).await.unwrap());
+        assert_eq!(verifier.store.redeem.times_called(), 0);
+    }
+}
@@ -1,7 +0,0 @@
-pub const TEST_DATABASE_CONFIG: database::Configuration = database::Configuration {
-    database: "postgres",
-    password: "password",
-    user: "postgres",
-    host: "127.0.0.1",
-    port: "5435",
-};
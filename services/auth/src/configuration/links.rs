@@ -0,0 +1,31 @@
+use super::settings::LinkSettings;
+
+const MAGIC_LINK_PATH: &str = "/magic-link";
+const EMAIL_CHANGE_PATH: &str = "/email-change";
+const EMAIL_REVERT_PATH: &str = "/email-change/revert";
+const REGISTRATION_PATH: &str = "/register";
+const DEVICE_ALERT_PATH: &str = "/not-me";
+
+pub fn ui(settings: &LinkSettings) -> String {
+    settings.ui.clone()
+}
+
+pub fn magic_link(settings: &LinkSettings, id: &str, token: &str) -> String {
+    format!("{}{}?id={}&token={}", ui(settings), MAGIC_LINK_PATH, id, token)
+}
+
+pub fn email_change(settings: &LinkSettings, id: &str, token: &str) -> String {
+    format!("{}{}?id={}&token={}", ui(settings), EMAIL_CHANGE_PATH, id, token)
+}
+
+pub fn invitation(settings: &LinkSettings, token: &str) -> String {
+    format!("{}{}?invitation={}", ui(settings), REGISTRATION_PATH, token)
+}
+
+pub fn email_revert(settings: &LinkSettings, id: &str, token: &str) -> String {
+    format!("{}{}?id={}&token={}", ui(settings), EMAIL_REVERT_PATH, id, token)
+}
+
+pub fn device_alert(settings: &LinkSettings, id: &str, token: &str) -> String {
+    format!("{}{}?id={}&token={}", ui(settings), DEVICE_ALERT_PATH, id, token)
+}
@@ -1,12 +1,18 @@
-use crate::constants::ONE_DAY;
+use crate::constants::{ONE_DAY, SECONDS_IN_A_MINUTE};
 
 pub use environment;
 
 pub mod connection;
-pub mod database;
-pub mod hash;
-pub mod jwt;
+pub mod links;
+pub mod security;
+pub mod settings;
+
+pub use settings::Settings;
 
 pub const ACCOUNT_LOCK_DURATION_IN_SECONDS: u64 = ONE_DAY;
 pub const PASSWORD_RESET_TIME_PERIOD: u64 = ONE_DAY;
 pub const ALLOWED_FAILED_LOGIN_ATTEMPTS: i16 = 50;
+pub const MAGIC_LINK_TIME_PERIOD: u64 = SECONDS_IN_A_MINUTE * 15;
+pub const EMAIL_CHANGE_TIME_PERIOD: u64 = ONE_DAY;
+pub const EMAIL_CHANGE_REVERT_TIME_PERIOD: u64 = ONE_DAY * 30;
+pub const DEVICE_ALERT_TIME_PERIOD: u64 = ONE_DAY * 30;
@@ -0,0 +1,161 @@
+use super::settings::{Settings, DEFAULT_DATABASE_PASSWORD, DEFAULT_HASH_SECRET};
+
+pub const MINIMUM_SECRET_LENGTH: usize = 32;
+
+pub const REDACTED: &str = "<redacted>";
+
+fn check_secret(name: &str, secret: &str, default: Option<&str>, problems: &mut Vec<String>) {
+    if secret.is_empty() {
+        problems.push(format!("{} must be set", name));
+    } else if default.map_or(false, |default| secret == default) {
+        problems.push(format!("{} must not use the default value", name));
+    } else if secret.chars().count() < MINIMUM_SECRET_LENGTH {
+        problems.push(format!(
+            "{} must be at least {} characters long",
+            name, MINIMUM_SECRET_LENGTH
+        ));
+    }
+}
+
+pub fn problems(settings: &Settings) -> Vec<String> {
+    let mut problems = vec![];
+    check_secret("HASH_SECRET", &settings.hash.secret, Some(DEFAULT_HASH_SECRET), &mut problems);
+    check_secret("JWT_SECRET", &settings.jwt.secret, None, &mut problems);
+    if settings.hash.secret == settings.jwt.secret {
+        problems.push(String::from("HASH_SECRET and JWT_SECRET must be different"));
+    }
+    if settings.challenge.enabled {
+        check_secret("CHALLENGE_SECRET", &settings.challenge.secret, None, &mut problems);
+        if settings.challenge.secret == settings.hash.secret || settings.challenge.secret == settings.jwt.secret {
+            problems.push(String::from("CHALLENGE_SECRET must differ from HASH_SECRET and JWT_SECRET"));
+        }
+    }
+    if settings.database.password.is_empty() || settings.database.password == DEFAULT_DATABASE_PASSWORD {
+        problems.push(String::from("DATABASE_PASSWORD must be set to a non default value"));
+    }
+    problems
+}
+
+fn describe_secret(secret: &str, default: Option<&str>) -> String {
+    if secret.is_empty() {
+        String::from("unset")
+    } else if default.map_or(false, |default| secret == default) {
+        String::from("default")
+    } else {
+        format!("{} ({} characters)", REDACTED, secret.chars().count())
+    }
+}
+
+pub fn summary(settings: &Settings) -> String {
+    let Settings {
+        challenge,
+        database,
+        hash,
+        jwt,
+        mail,
+        ..
+    } = settings;
+    [
+        format!(
+            "hash secret: {}, argon lanes: {}, argon time cost: {}, argon memory: {}",
+            describe_secret(&hash.secret, Some(DEFAULT_HASH_SECRET)),
+            hash.lanes,
+            hash.time_cost,
+            hash.memory
+        ),
+        format!(
+            "jwt secret: {}, jwt expiration: {}, jwt issuer: {}, jwt audience: {}",
+            describe_secret(&jwt.secret, None),
+            jwt.expiration,
+            jwt.issuer,
+            jwt.audience
+        ),
+        format!(
+            "challenges: {}, challenge secret: {}",
+            if challenge.enabled { "enabled" } else { "disabled" },
+            describe_secret(&challenge.secret, None)
+        ),
+        format!(
+            "database: {}@{}:{}/{}, password: {}",
+            database.user,
+            database.host,
+            database.port,
+            database.name,
+            describe_secret(&database.password, Some(DEFAULT_DATABASE_PASSWORD))
+        ),
+        format!(
+            "smtp: {}, password: {}",
+            mail.smtp_host.as_ref().map_or("disabled", String::as_str),
+            describe_secret(&mail.smtp_password, None)
+        ),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_variables(_: &str) -> Option<String> {
+        None
+    }
+
+    fn secure_settings() -> Settings {
+        let mut settings = Settings::parse("", &no_variables).unwrap();
+        settings.hash.secret = "h".repeat(MINIMUM_SECRET_LENGTH);
+        settings.jwt.secret = "j".repeat(MINIMUM_SECRET_LENGTH);
+        settings.database.password = String::from("a database password");
+        settings.challenge.enabled = true;
+        settings.challenge.secret = "c".repeat(MINIMUM_SECRET_LENGTH);
+        settings
+    }
+
+    #[test]
+    fn accepts_long_unique_secrets() {
+        assert!(problems(&secure_settings()).is_empty());
+    }
+
+    #[test]
+    fn rejects_default_and_empty_secrets() {
+        let settings = Settings::parse("", &no_variables).unwrap();
+        let problems = problems(&settings);
+        assert!(problems.iter().any(|problem| problem.starts_with("HASH_SECRET must not use")));
+        assert!(problems.iter().any(|problem| problem.starts_with("JWT_SECRET must be set")));
+        assert!(problems.iter().any(|problem| problem.starts_with("DATABASE_PASSWORD")));
+    }
+
+    #[test]
+    fn rejects_short_secrets() {
+        let mut settings = secure_settings();
+        settings.jwt.secret = String::from("short");
+        assert_eq!(problems(&settings).len(), 1);
+    }
+
+    #[test]
+    fn rejects_a_shared_secret() {
+        let mut settings = secure_settings();
+        settings.jwt.secret = settings.hash.secret.clone();
+        assert_eq!(problems(&settings).len(), 1);
+    }
+
+    #[test]
+    fn requires_a_distinct_challenge_secret_when_challenges_are_enabled() {
+        let mut settings = secure_settings();
+        settings.challenge.secret = settings.hash.secret.clone();
+        assert_eq!(problems(&settings).len(), 1);
+        settings.challenge.secret = String::new();
+        assert!(problems(&settings).iter().any(|problem| problem.starts_with("CHALLENGE_SECRET must be set")));
+        settings.challenge.enabled = false;
+        assert!(problems(&settings).is_empty());
+    }
+
+    #[test]
+    fn summary_does_not_contain_secrets() {
+        let settings = secure_settings();
+        let summary = summary(&settings);
+        assert!(!summary.contains(&settings.hash.secret));
+        assert!(!summary.contains(&settings.jwt.secret));
+        assert!(!summary.contains(&settings.database.password));
+        assert!(!summary.contains(&settings.challenge.secret));
+    }
+}
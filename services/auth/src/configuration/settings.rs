@@ -0,0 +1,956 @@
+use super::security::REDACTED;
+use crate::constants::ONE_DAY;
+use actix_web::http::cookie::SameSite;
+use std::{env, fmt, fs, str::FromStr};
+use toml::{value::Table, Value};
+
+const SETTINGS_FILE: &str = "SETTINGS_FILE";
+const DEFAULT_SETTINGS_FILE: &str = "/settings.toml";
+const LIST_SEPARATOR: char = ',';
+const FILE_SUFFIX: &str = "_FILE";
+const FILE_KEY_SUFFIX: &str = "_file";
+const MINIMUM_MEMORY_PER_LANE: u32 = 8;
+
+const DEFAULT_ADDRESS: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 8080;
+const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+const DEFAULT_CORS_MAX_AGE: usize = 3600;
+const SESSION_TRANSPORTS: [(&str, SessionTransport); 3] = [
+    ("header", SessionTransport::Header),
+    ("cookie", SessionTransport::Cookie),
+    ("both", SessionTransport::Both),
+];
+const SAME_SITE_POLICIES: [(&str, SameSite); 3] = [
+    ("strict", SameSite::Strict),
+    ("lax", SameSite::Lax),
+    ("none", SameSite::None),
+];
+const REGISTRATION_MODES: [(&str, RegistrationMode); 2] = [
+    ("open", RegistrationMode::Open),
+    ("invite_only", RegistrationMode::InviteOnly),
+];
+const DEFAULT_IMPERSONATION_LIFETIME: usize = 900;
+const DEFAULT_INVITATION_LIFETIME: u64 = ONE_DAY * 7;
+const DEFAULT_SERVICE_TOKEN_LIFETIME: usize = 3600;
+const DEFAULT_SERVICE_SECRET_GRACE: u64 = ONE_DAY;
+const DEFAULT_CHALLENGE_THRESHOLD: i64 = 5;
+const DEFAULT_CHALLENGE_WINDOW: u64 = 3600;
+const DEFAULT_CHALLENGE_DIFFICULTY: u32 = 18;
+const DEFAULT_CHALLENGE_MAXIMUM_DIFFICULTY: u32 = 24;
+const DEFAULT_CHALLENGE_LIFETIME: u64 = 300;
+const MAXIMUM_CHALLENGE_DIFFICULTY: u32 = 32;
+const DEFAULT_OUTBOX_STREAM: &str = "auth:events";
+const DEFAULT_OUTBOX_POLL_INTERVAL: u64 = 1000;
+const DEFAULT_OUTBOX_BATCH_SIZE: i64 = 100;
+const DEFAULT_OUTBOX_MAX_LENGTH: usize = 100000;
+const DEFAULT_DATABASE_HOST: &str = "127.0.0.1";
+const DEFAULT_DATABASE_PORT: u16 = 5435;
+const DEFAULT_DATABASE_USER: &str = "postgres";
+pub const DEFAULT_DATABASE_PASSWORD: &str = "password";
+const DEFAULT_DATABASE_NAME: &str = "postgres";
+pub const DEFAULT_HASH_SECRET: &str = "secret";
+const DEFAULT_ARGON_LANES: u32 = 8;
+const DEFAULT_ARGON_TIME_COST: u32 = 10;
+const DEFAULT_ARGON_MEMORY: u32 = 2048;
+const DEFAULT_JWT_EXPIRATION: usize = 500000;
+const DEFAULT_JWT_ISSUER: &str = "https://auth.bythepeoples.org";
+const DEFAULT_JWT_AUDIENCE: &str = "https://bythepeoples.org";
+const DEFAULT_JWT_LEEWAY: u64 = 60;
+const DEFAULT_MAIL_SENDER: &str = "no-reply@bythepeoples.org";
+const DEFAULT_UI_URL: &str = "http://localhost:8000";
+const DEFAULT_RESERVED_NAMES: &str = "admin,administrator,root,system,support,moderator,official,staff,bythepeoples,mayor,deputy mayor,governor,lieutenant governor,senator,representative,congressman,congresswoman,councilmember,councilman,councilwoman,alderman,commissioner,president,vice president,secretary,clerk,sheriff,judge";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerSettings {
+    pub address: String,
+    pub port: u16,
+    pub shutdown_timeout: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TlsSettings {
+    pub certificate: Option<String>,
+    pub key: Option<String>,
+    pub client_ca: Option<String>,
+}
+
+impl TlsSettings {
+    pub fn configuration(&self) -> Option<tls::Configuration> {
+        match (&self.certificate, &self.key) {
+            (Some(certificate), Some(key)) => Some(tls::Configuration {
+                certificate: certificate.clone(),
+                key: key.clone(),
+                client_ca: self.client_ca.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CorsSettings {
+    pub origins: Vec<String>,
+    pub credentials: bool,
+    pub max_age: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SessionTransport {
+    Header,
+    Cookie,
+    Both,
+}
+
+impl SessionTransport {
+    pub fn header(self) -> bool {
+        self != SessionTransport::Cookie
+    }
+    pub fn cookie(self) -> bool {
+        self != SessionTransport::Header
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CookieSettings {
+    pub secure: bool,
+    pub session: SessionTransport,
+    pub same_site: SameSite,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminSettings {
+    pub ids: Vec<i32>,
+    pub impersonation_lifetime: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServiceClientSettings {
+    pub token_lifetime: usize,
+    pub secret_grace: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RegistrationMode {
+    Open,
+    InviteOnly,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistrationSettings {
+    pub mode: RegistrationMode,
+    pub invitation_lifetime: u64,
+    pub private: bool,
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct ChallengeSettings {
+    pub enabled: bool,
+    pub secret: String,
+    pub threshold: i64,
+    pub window: u64,
+    pub difficulty: u32,
+    pub maximum_difficulty: u32,
+    pub lifetime: u64,
+    pub trust_forwarded_for: bool,
+}
+
+impl fmt::Debug for ChallengeSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChallengeSettings")
+            .field("enabled", &self.enabled)
+            .field("secret", &REDACTED)
+            .field("threshold", &self.threshold)
+            .field("window", &self.window)
+            .field("difficulty", &self.difficulty)
+            .field("maximum_difficulty", &self.maximum_difficulty)
+            .field("lifetime", &self.lifetime)
+            .field("trust_forwarded_for", &self.trust_forwarded_for)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeviceSettings {
+    pub notify: bool,
+    pub geoip_database: Option<String>,
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct DatabaseSettings {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub name: String,
+}
+
+impl fmt::Debug for DatabaseSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatabaseSettings")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("user", &self.user)
+            .field("password", &REDACTED)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl DatabaseSettings {
+    pub fn configuration(&self) -> database::Configuration {
+        database::Configuration {
+            host: self.host.clone(),
+            port: self.port.to_string(),
+            user: self.user.clone(),
+            password: self.password.clone(),
+            database: self.name.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutboxSettings {
+    pub redis_url: Option<String>,
+    pub stream: String,
+    pub poll_interval: u64,
+    pub batch_size: i64,
+    pub max_length: usize,
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct HashSettings {
+    pub secret: String,
+    pub lanes: u32,
+    pub time_cost: u32,
+    pub memory: u32,
+}
+
+impl fmt::Debug for HashSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashSettings")
+            .field("secret", &REDACTED)
+            .field("lanes", &self.lanes)
+            .field("time_cost", &self.time_cost)
+            .field("memory", &self.memory)
+            .finish()
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct JwtSettings {
+    pub secret: String,
+    pub expiration: usize,
+    pub issuer: String,
+    pub audience: String,
+    pub leeway: u64,
+}
+
+impl fmt::Debug for JwtSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtSettings")
+            .field("secret", &REDACTED)
+            .field("expiration", &self.expiration)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("leeway", &self.leeway)
+            .finish()
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct MailSettings {
+    pub sender: String,
+    pub smtp_host: Option<String>,
+    pub smtp_user: String,
+    pub smtp_password: String,
+}
+
+impl fmt::Debug for MailSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailSettings")
+            .field("sender", &self.sender)
+            .field("smtp_host", &self.smtp_host)
+            .field("smtp_user", &self.smtp_user)
+            .field("smtp_password", &REDACTED)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinkSettings {
+    pub ui: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NameSettings {
+    pub reserved: Vec<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Settings {
+    pub server: ServerSettings,
+    pub tls: TlsSettings,
+    pub cors: CorsSettings,
+    pub cookies: CookieSettings,
+    pub admin: AdminSettings,
+    pub service_clients: ServiceClientSettings,
+    pub registration: RegistrationSettings,
+    pub challenge: ChallengeSettings,
+    pub devices: DeviceSettings,
+    pub database: DatabaseSettings,
+    pub outbox: OutboxSettings,
+    pub hash: HashSettings,
+    pub jwt: JwtSettings,
+    pub mail: MailSettings,
+    pub links: LinkSettings,
+    pub names: NameSettings,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettingsError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid settings:\n  {}", self.problems.join("\n  "))
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+struct Source<'a> {
+    file: &'a Table,
+    variables: &'a dyn Fn(&str) -> Option<String>,
+    problems: Vec<String>,
+}
+
+impl<'a> Source<'a> {
+    fn file_value(&self, section: &str, key: &str) -> Option<String> {
+        self.file
+            .get(section)
+            .and_then(|section| section.get(key))
+            .map(|value| match value {
+                Value::String(value) => value.clone(),
+                Value::Array(values) => values
+                    .iter()
+                    .map(|value| match value {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(&LIST_SEPARATOR.to_string()),
+                value => value.to_string(),
+            })
+    }
+    fn optional(&self, section: &str, key: &str, variable: &str) -> Option<String> {
+        (self.variables)(variable)
+            .or_else(|| self.file_value(section, key))
+            .filter(|value| !value.trim().is_empty())
+    }
+    fn string(&self, section: &str, key: &str, variable: &str, default: &str) -> String {
+        self.optional(section, key, variable)
+            .unwrap_or_else(|| String::from(default))
+    }
+    fn secret(&mut self, section: &str, key: &str, variable: &str, default: &str) -> String {
+        let file_variable = format!("{}{}", variable, FILE_SUFFIX);
+        let file_key = format!("{}{}", key, FILE_KEY_SUFFIX);
+        let path = match (self.variables)(variable) {
+            Some(_) => None,
+            None => (self.variables)(&file_variable)
+                .or_else(|| self.file_value(section, &file_key))
+                .filter(|path| !path.trim().is_empty()),
+        };
+        match path {
+            Some(path) => match fs::read_to_string(path.trim()) {
+                Ok(secret) => String::from(secret.trim()),
+                Err(error) => {
+                    self.problem(
+                        section,
+                        &file_key,
+                        &file_variable,
+                        &format!("could not be read from \"{}\": {}", path, error),
+                    );
+                    String::from(default)
+                }
+            },
+            None => self.string(section, key, variable, default),
+        }
+    }
+    fn list(&self, section: &str, key: &str, variable: &str, default: &str) -> Vec<String> {
+        self.string(section, key, variable, default)
+            .split(LIST_SEPARATOR)
+            .map(|value| String::from(value.trim()))
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+    fn identifiers(&mut self, section: &str, key: &str, variable: &str) -> Vec<i32> {
+        let values = self.list(section, key, variable, "");
+        values
+            .iter()
+            .filter_map(|value| match value.parse::<i32>() {
+                Ok(id) if id > 0 => Some(id),
+                _ => {
+                    self.problem(section, key, variable, &format!("must be a list of credential ids, found \"{}\"", value));
+                    None
+                }
+            })
+            .collect()
+    }
+    fn boolean(&mut self, section: &str, key: &str, variable: &str, default: bool) -> bool {
+        match self.optional(section, key, variable) {
+            Some(value) => match value.trim().to_lowercase().as_str() {
+                "true" | "yes" | "1" => true,
+                "false" | "no" | "0" => false,
+                _ => {
+                    self.problem(section, key, variable, &format!("must be true or false, found \"{}\"", value));
+                    default
+                }
+            },
+            None => default,
+        }
+    }
+    fn choice<T: Copy>(&mut self, section: &str, key: &str, variable: &str, options: &[(&str, T)], default: T) -> T {
+        match self.optional(section, key, variable) {
+            Some(value) => match options.iter().find(|(name, _)| value.trim().eq_ignore_ascii_case(name)) {
+                Some((_, option)) => *option,
+                None => {
+                    let names: Vec<&str> = options.iter().map(|(name, _)| *name).collect();
+                    self.problem(section, key, variable, &format!("must be one of {}, found \"{}\"", names.join(", "), value));
+                    default
+                }
+            },
+            None => default,
+        }
+    }
+    fn number<T: FromStr + Copy>(&mut self, section: &str, key: &str, variable: &str, default: T) -> T {
+        match self.optional(section, key, variable) {
+            Some(value) => value.trim().parse::<T>().unwrap_or_else(|_| {
+                self.problem(section, key, variable, &format!("must be a positive whole number, found \"{}\"", value));
+                default
+            }),
+            None => default,
+        }
+    }
+    fn problem(&mut self, section: &str, key: &str, variable: &str, message: &str) {
+        self.problems
+            .push(format!("{}.{} ({}) {}", section, key, variable, message));
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Settings, SettingsError> {
+        let (path, required) = match env::var(SETTINGS_FILE) {
+            Ok(path) => (path, true),
+            Err(_) => (environment::path(DEFAULT_SETTINGS_FILE), false),
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if required => {
+                return Err(SettingsError {
+                    problems: vec![format!("{} ({}) could not be read: {}", path, SETTINGS_FILE, error)],
+                })
+            }
+            Err(_) => String::new(),
+        };
+        Settings::parse(&contents, &|variable: &str| env::var(variable).ok())
+    }
+
+    pub fn parse(
+        contents: &str,
+        variables: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Settings, SettingsError> {
+        let file = contents.parse::<Value>().map_err(|error| SettingsError {
+            problems: vec![format!("Settings file is not valid TOML: {}", error)],
+        })?;
+        let empty = Table::new();
+        let mut source = Source {
+            file: file.as_table().unwrap_or(&empty),
+            variables,
+            problems: vec![],
+        };
+        let settings = Settings {
+            server: ServerSettings {
+                address: source.string("server", "address", "IP", DEFAULT_ADDRESS),
+                port: source.number("server", "port", "PORT", DEFAULT_PORT),
+                shutdown_timeout: source.number("server", "shutdown_timeout", "SHUTDOWN_TIMEOUT", DEFAULT_SHUTDOWN_TIMEOUT),
+            },
+            tls: TlsSettings {
+                certificate: source.optional("tls", "certificate", "TLS_CERTIFICATE"),
+                key: source.optional("tls", "key", "TLS_KEY"),
+                client_ca: source.optional("tls", "client_ca", "TLS_CLIENT_CA"),
+            },
+            cors: CorsSettings {
+                origins: source.list("cors", "origins", "CORS_ORIGINS", ""),
+                credentials: source.boolean("cors", "credentials", "CORS_CREDENTIALS", false),
+                max_age: source.number("cors", "max_age", "CORS_MAX_AGE", DEFAULT_CORS_MAX_AGE),
+            },
+            cookies: CookieSettings {
+                secure: source.boolean("cookies", "secure", "COOKIE_SECURE", true),
+                session: source.choice("cookies", "session", "COOKIE_SESSION", &SESSION_TRANSPORTS, SessionTransport::Header),
+                same_site: source.choice("cookies", "same_site", "COOKIE_SAME_SITE", &SAME_SITE_POLICIES, SameSite::Strict),
+            },
+            admin: AdminSettings {
+                ids: source.identifiers("admin", "ids", "ADMIN_IDS"),
+                impersonation_lifetime: source.number("admin", "impersonation_lifetime", "IMPERSONATION_LIFETIME", DEFAULT_IMPERSONATION_LIFETIME),
+            },
+            service_clients: ServiceClientSettings {
+                token_lifetime: source.number("service_clients", "token_lifetime", "SERVICE_TOKEN_LIFETIME", DEFAULT_SERVICE_TOKEN_LIFETIME),
+                secret_grace: source.number("service_clients", "secret_grace", "SERVICE_SECRET_GRACE", DEFAULT_SERVICE_SECRET_GRACE),
+            },
+            registration: RegistrationSettings {
+                mode: source.choice("registration", "mode", "REGISTRATION_MODE", &REGISTRATION_MODES, RegistrationMode::Open),
+                invitation_lifetime: source.number("registration", "invitation_lifetime", "INVITATION_LIFETIME", DEFAULT_INVITATION_LIFETIME),
+                private: source.boolean("registration", "private", "REGISTRATION_PRIVATE", false),
+            },
+            challenge: ChallengeSettings {
+                enabled: source.boolean("challenge", "enabled", "CHALLENGE_ENABLED", false),
+                secret: source.secret("challenge", "secret", "CHALLENGE_SECRET", ""),
+                threshold: source.number("challenge", "threshold", "CHALLENGE_THRESHOLD", DEFAULT_CHALLENGE_THRESHOLD),
+                window: source.number("challenge", "window", "CHALLENGE_WINDOW", DEFAULT_CHALLENGE_WINDOW),
+                difficulty: source.number("challenge", "difficulty", "CHALLENGE_DIFFICULTY", DEFAULT_CHALLENGE_DIFFICULTY),
+                maximum_difficulty: source.number("challenge", "maximum_difficulty", "CHALLENGE_MAXIMUM_DIFFICULTY", DEFAULT_CHALLENGE_MAXIMUM_DIFFICULTY),
+                lifetime: source.number("challenge", "lifetime", "CHALLENGE_LIFETIME", DEFAULT_CHALLENGE_LIFETIME),
+                trust_forwarded_for: source.boolean("challenge", "trust_forwarded_for", "CHALLENGE_TRUST_FORWARDED_FOR", false),
+            },
+            devices: DeviceSettings {
+                notify: source.boolean("devices", "notify", "DEVICE_NOTIFICATIONS", true),
+                geoip_database: source.optional("devices", "geoip_database", "GEOIP_DATABASE"),
+            },
+            database: DatabaseSettings {
+                host: source.string("database", "host", "DATABASE_HOST", DEFAULT_DATABASE_HOST),
+                port: source.number("database", "port", "DATABASE_PORT", DEFAULT_DATABASE_PORT),
+                user: source.string("database", "user", "DATABASE_USER", DEFAULT_DATABASE_USER),
+                password: source.secret("database", "password", "DATABASE_PASSWORD", DEFAULT_DATABASE_PASSWORD),
+                name: source.string("database", "name", "DATABASE_NAME", DEFAULT_DATABASE_NAME),
+            },
+            outbox: OutboxSettings {
+                redis_url: source.optional("outbox", "redis_url", "OUTBOX_REDIS_URL"),
+                stream: source.string("outbox", "stream", "OUTBOX_STREAM", DEFAULT_OUTBOX_STREAM),
+                poll_interval: source.number("outbox", "poll_interval", "OUTBOX_POLL_INTERVAL", DEFAULT_OUTBOX_POLL_INTERVAL),
+                batch_size: source.number("outbox", "batch_size", "OUTBOX_BATCH_SIZE", DEFAULT_OUTBOX_BATCH_SIZE),
+                max_length: source.number("outbox", "max_length", "OUTBOX_MAX_LENGTH", DEFAULT_OUTBOX_MAX_LENGTH),
+            },
+            hash: HashSettings {
+                secret: source.secret("hash", "secret", "HASH_SECRET", DEFAULT_HASH_SECRET),
+                lanes: source.number("hash", "lanes", "ARGON_LANES", DEFAULT_ARGON_LANES),
+                time_cost: source.number("hash", "time_cost", "ARGON_TIME_COST", DEFAULT_ARGON_TIME_COST),
+                memory: source.number("hash", "memory", "ARGON_MEMORY", DEFAULT_ARGON_MEMORY),
+            },
+            jwt: JwtSettings {
+                secret: source.secret("jwt", "secret", "JWT_SECRET", ""),
+                expiration: source.number("jwt", "expiration", "JWT_EXPIRATION", DEFAULT_JWT_EXPIRATION),
+                issuer: source.string("jwt", "issuer", "JWT_ISSUER", DEFAULT_JWT_ISSUER),
+                audience: source.string("jwt", "audience", "JWT_AUDIENCE", DEFAULT_JWT_AUDIENCE),
+                leeway: source.number("jwt", "leeway", "JWT_LEEWAY", DEFAULT_JWT_LEEWAY),
+            },
+            mail: MailSettings {
+                sender: source.string("mail", "sender", "MAIL_SENDER", DEFAULT_MAIL_SENDER),
+                smtp_host: source.optional("mail", "smtp_host", "SMTP_HOST"),
+                smtp_user: source.string("mail", "smtp_user", "SMTP_USER", ""),
+                smtp_password: source.secret("mail", "smtp_password", "SMTP_PASSWORD", ""),
+            },
+            links: LinkSettings {
+                ui: source.string("links", "ui", "UI_URL", DEFAULT_UI_URL),
+            },
+            names: NameSettings {
+                reserved: source.list("names", "reserved", "RESERVED_NAMES", DEFAULT_RESERVED_NAMES),
+            },
+        };
+        settings.validate(&mut source);
+        if source.problems.is_empty() {
+            Ok(settings)
+        } else {
+            Err(SettingsError { problems: source.problems })
+        }
+    }
+
+    fn validate(&self, source: &mut Source) {
+        if self.server.port == 0 {
+            source.problem("server", "port", "PORT", "must be greater than zero");
+        }
+        if self.tls.certificate.is_some() != self.tls.key.is_some() {
+            source.problem("tls", "certificate", "TLS_CERTIFICATE", "and tls.key (TLS_KEY) must be set together");
+        }
+        if self.tls.client_ca.is_some() && self.tls.certificate.is_none() {
+            source.problem("tls", "client_ca", "TLS_CLIENT_CA", "requires a certificate and key");
+        }
+        if self.cors.origins.iter().any(|origin| origin == "*") && self.cors.credentials {
+            source.problem("cors", "origins", "CORS_ORIGINS", "must list explicit origins when credentials are allowed");
+        }
+        if let Some(origin) = self
+            .cors
+            .origins
+            .iter()
+            .find(|origin| *origin != "*" && !origin.starts_with("http://") && !origin.starts_with("https://"))
+        {
+            source.problem("cors", "origins", "CORS_ORIGINS", &format!("must be http or https origins, found \"{}\"", origin));
+        }
+        if self.cookies.same_site == SameSite::None && !self.cookies.secure {
+            source.problem("cookies", "same_site", "COOKIE_SAME_SITE", "can only be none for secure cookies");
+        }
+        if self.database.port == 0 {
+            source.problem("database", "port", "DATABASE_PORT", "must be greater than zero");
+        }
+        if let Some(url) = &self.outbox.redis_url {
+            if !url.starts_with("redis://") && !url.starts_with("rediss://") {
+                source.problem("outbox", "redis_url", "OUTBOX_REDIS_URL", "must be a redis:// or rediss:// url");
+            }
+        }
+        if self.admin.impersonation_lifetime == 0 || self.admin.impersonation_lifetime > self.jwt.expiration {
+            source.problem("admin", "impersonation_lifetime", "IMPERSONATION_LIFETIME", "must be greater than zero and no longer than jwt.expiration");
+        }
+        if self.service_clients.token_lifetime == 0 || self.service_clients.token_lifetime > self.jwt.expiration {
+            source.problem("service_clients", "token_lifetime", "SERVICE_TOKEN_LIFETIME", "must be greater than zero and no longer than jwt.expiration");
+        }
+        if self.registration.invitation_lifetime == 0 {
+            source.problem("registration", "invitation_lifetime", "INVITATION_LIFETIME", "must be greater than zero");
+        }
+        if self.challenge.difficulty == 0 || self.challenge.maximum_difficulty > MAXIMUM_CHALLENGE_DIFFICULTY {
+            source.problem("challenge", "difficulty", "CHALLENGE_DIFFICULTY", &format!("must be between 1 and {} bits", MAXIMUM_CHALLENGE_DIFFICULTY));
+        }
+        if self.challenge.maximum_difficulty < self.challenge.difficulty {
+            source.problem("challenge", "maximum_difficulty", "CHALLENGE_MAXIMUM_DIFFICULTY", "must not be less than challenge.difficulty");
+        }
+        if self.challenge.threshold < 0 || self.challenge.window == 0 || self.challenge.lifetime == 0 {
+            source.problem("challenge", "threshold", "CHALLENGE_THRESHOLD", "and the challenge window and lifetime must be positive");
+        }
+        if self.outbox.poll_interval == 0 {
+            source.problem("outbox", "poll_interval", "OUTBOX_POLL_INTERVAL", "must be greater than zero");
+        }
+        if self.outbox.batch_size <= 0 {
+            source.problem("outbox", "batch_size", "OUTBOX_BATCH_SIZE", "must be greater than zero");
+        }
+        if self.hash.lanes == 0 {
+            source.problem("hash", "lanes", "ARGON_LANES", "must be greater than zero");
+        }
+        if self.hash.time_cost == 0 {
+            source.problem("hash", "time_cost", "ARGON_TIME_COST", "must be greater than zero");
+        }
+        match self.hash.lanes.checked_mul(MINIMUM_MEMORY_PER_LANE) {
+            None => source.problem("hash", "lanes", "ARGON_LANES", "is too large"),
+            Some(minimum) if self.hash.memory < minimum => source.problem(
+                "hash",
+                "memory",
+                "ARGON_MEMORY",
+                &format!("must be at least {} times the number of lanes", MINIMUM_MEMORY_PER_LANE),
+            ),
+            Some(_) => (),
+        }
+        if self.jwt.expiration == 0 {
+            source.problem("jwt", "expiration", "JWT_EXPIRATION", "must be greater than zero");
+        }
+        if self.jwt.leeway as usize >= self.jwt.expiration {
+            source.problem("jwt", "leeway", "JWT_LEEWAY", "must be shorter than jwt.expiration");
+        }
+        if !self.mail.sender.contains('@') {
+            source.problem("mail", "sender", "MAIL_SENDER", "must be an email address");
+        }
+        if !self.links.ui.starts_with("http://") && !self.links.ui.starts_with("https://") {
+            source.problem("links", "ui", "UI_URL", "must be an http or https url");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_variables(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn uses_defaults_without_a_file_or_variables() {
+        let settings = Settings::parse("", &no_variables).unwrap();
+        assert_eq!(settings.server.port, DEFAULT_PORT);
+        assert_eq!(settings.server.shutdown_timeout, DEFAULT_SHUTDOWN_TIMEOUT);
+        assert_eq!(settings.database.port, DEFAULT_DATABASE_PORT);
+        assert_eq!(settings.hash.lanes, DEFAULT_ARGON_LANES);
+        assert_eq!(settings.mail.smtp_host, None);
+    }
+
+    #[test]
+    fn reads_values_from_the_file() {
+        let file = "[server]\nport = 9000\n\n[names]\nreserved = [\"admin\", \"root\"]\n";
+        let settings = Settings::parse(file, &no_variables).unwrap();
+        assert_eq!(settings.server.port, 9000);
+        assert_eq!(settings.names.reserved, vec![String::from("admin"), String::from("root")]);
+    }
+
+    #[test]
+    fn environment_variables_override_the_file() {
+        let file = "[server]\nport = 9000\n";
+        let variables = |variable: &str| match variable {
+            "PORT" => Some(String::from("9001")),
+            _ => None,
+        };
+        let settings = Settings::parse(file, &variables).unwrap();
+        assert_eq!(settings.server.port, 9001);
+    }
+
+    #[test]
+    fn reports_every_invalid_field() {
+        let variables = |variable: &str| match variable {
+            "ARGON_LANES" => Some(String::from("many")),
+            "JWT_EXPIRATION" => Some(String::from("0")),
+            "UI_URL" => Some(String::from("localhost")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert_eq!(error.problems.len(), 3);
+        assert!(error.problems[0].contains("ARGON_LANES"));
+    }
+
+    #[test]
+    fn reads_secrets_from_mounted_files() {
+        let path = std::env::temp_dir().join(format!("jwt_secret_{}", std::process::id()));
+        fs::write(&path, "mounted-secret\n").unwrap();
+        let file = format!("[jwt]\nsecret_file = \"{}\"\n", path.display());
+        let settings = Settings::parse(&file, &no_variables).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(settings.jwt.secret, "mounted-secret");
+    }
+
+    #[test]
+    fn reports_unreadable_secret_files() {
+        let variables = |variable: &str| match variable {
+            "HASH_SECRET_FILE" => Some(String::from("/does/not/exist")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("HASH_SECRET_FILE"));
+    }
+
+    #[test]
+    fn enables_tls_when_a_certificate_and_key_are_set() {
+        let file = "[tls]\ncertificate = \"/certs/auth.pem\"\nkey = \"/certs/auth.key\"\n";
+        let settings = Settings::parse(file, &no_variables).unwrap();
+        let configuration = settings.tls.configuration().unwrap();
+        assert_eq!(configuration.certificate, "/certs/auth.pem");
+        assert_eq!(configuration.client_ca, None);
+        assert_eq!(Settings::parse("", &no_variables).unwrap().tls.configuration(), None);
+    }
+
+    #[test]
+    fn rejects_incomplete_tls_settings() {
+        let variables = |variable: &str| match variable {
+            "TLS_CERTIFICATE" => Some(String::from("/certs/auth.pem")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("TLS_KEY"));
+        let variables = |variable: &str| match variable {
+            "TLS_CLIENT_CA" => Some(String::from("/certs/ca.pem")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("TLS_CLIENT_CA"));
+    }
+
+    #[test]
+    fn reads_cors_settings() {
+        let file = "[cors]\norigins = [\"http://localhost:8000\"]\ncredentials = true\n";
+        let settings = Settings::parse(file, &no_variables).unwrap();
+        assert_eq!(settings.cors.origins, vec![String::from("http://localhost:8000")]);
+        assert!(settings.cors.credentials);
+        assert!(Settings::parse("", &no_variables).unwrap().cors.origins.is_empty());
+    }
+
+    #[test]
+    fn rejects_wildcard_origins_with_credentials() {
+        let variables = |variable: &str| match variable {
+            "CORS_ORIGINS" => Some(String::from("*")),
+            "CORS_CREDENTIALS" => Some(String::from("true")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("CORS_ORIGINS"));
+    }
+
+    #[test]
+    fn rejects_invalid_booleans() {
+        let variables = |variable: &str| match variable {
+            "COOKIE_SECURE" => Some(String::from("sometimes")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("COOKIE_SECURE"));
+    }
+
+    #[test]
+    fn reads_cookie_session_settings() {
+        let file = "[cookies]\nsession = \"both\"\nsame_site = \"lax\"\n";
+        let settings = Settings::parse(file, &no_variables).unwrap();
+        assert_eq!(settings.cookies.session, SessionTransport::Both);
+        assert_eq!(settings.cookies.same_site, SameSite::Lax);
+        let defaults = Settings::parse("", &no_variables).unwrap();
+        assert_eq!(defaults.cookies.session, SessionTransport::Header);
+        assert!(defaults.cookies.session.header() && !defaults.cookies.session.cookie());
+    }
+
+    #[test]
+    fn rejects_unknown_cookie_options() {
+        let variables = |variable: &str| match variable {
+            "COOKIE_SESSION" => Some(String::from("local-storage")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("COOKIE_SESSION"));
+    }
+
+    #[test]
+    fn rejects_cross_site_cookies_without_secure() {
+        let variables = |variable: &str| match variable {
+            "COOKIE_SAME_SITE" => Some(String::from("none")),
+            "COOKIE_SECURE" => Some(String::from("false")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("COOKIE_SAME_SITE"));
+    }
+
+    #[test]
+    fn reads_admin_ids() {
+        let file = "[admin]\nids = [1, 42]\n";
+        assert_eq!(Settings::parse(file, &no_variables).unwrap().admin.ids, vec![1, 42]);
+        let variables = |variable: &str| match variable {
+            "ADMIN_IDS" => Some(String::from("1,admin")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("ADMIN_IDS"));
+    }
+
+    #[test]
+    fn reads_the_token_issuer_audience_and_leeway() {
+        let settings = Settings::parse("", &no_variables).unwrap();
+        assert_eq!(settings.jwt.issuer, DEFAULT_JWT_ISSUER);
+        assert_eq!(settings.jwt.audience, DEFAULT_JWT_AUDIENCE);
+        assert_eq!(settings.jwt.leeway, DEFAULT_JWT_LEEWAY);
+        let variables = |variable: &str| match variable {
+            "JWT_ISSUER" => Some(String::from("https://auth.example")),
+            "JWT_LEEWAY" => Some(String::from("5")),
+            _ => None,
+        };
+        let settings = Settings::parse("[jwt]\naudience = \"https://api.example\"", &variables).unwrap();
+        assert_eq!(settings.jwt.issuer, "https://auth.example");
+        assert_eq!(settings.jwt.audience, "https://api.example");
+        assert_eq!(settings.jwt.leeway, 5);
+        let error = Settings::parse("[jwt]\nleeway = 500000", &no_variables).err().unwrap();
+        assert!(error.problems[0].contains("JWT_LEEWAY"));
+    }
+
+    #[test]
+    fn reads_the_registration_mode() {
+        assert_eq!(Settings::parse("", &no_variables).unwrap().registration.mode, RegistrationMode::Open);
+        let settings = Settings::parse("[registration]\nmode = \"invite_only\"", &no_variables).unwrap();
+        assert_eq!(settings.registration.mode, RegistrationMode::InviteOnly);
+        assert!(!settings.registration.private);
+        let settings = Settings::parse("[registration]\nprivate = true", &no_variables).unwrap();
+        assert!(settings.registration.private);
+    }
+
+    #[test]
+    fn limits_impersonation_to_the_session_lifetime() {
+        assert_eq!(Settings::parse("", &no_variables).unwrap().admin.impersonation_lifetime, 900);
+        let variables = |variable: &str| match variable {
+            "IMPERSONATION_LIFETIME" => Some(String::from("1000")),
+            "JWT_EXPIRATION" => Some(String::from("600")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("IMPERSONATION_LIFETIME"));
+    }
+
+    #[test]
+    fn reads_the_service_client_settings() {
+        let settings = Settings::parse("", &no_variables).unwrap();
+        assert_eq!(settings.service_clients.token_lifetime, 3600);
+        assert_eq!(settings.service_clients.secret_grace, 86400);
+        let settings = Settings::parse("[service_clients]\nsecret_grace = 0\n", &no_variables).unwrap();
+        assert_eq!(settings.service_clients.secret_grace, 0);
+        let variables = |variable: &str| match variable {
+            "SERVICE_TOKEN_LIFETIME" => Some(String::from("0")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("SERVICE_TOKEN_LIFETIME"));
+    }
+
+    #[test]
+    fn reads_the_device_settings() {
+        let settings = Settings::parse("", &no_variables).unwrap();
+        assert!(settings.devices.notify);
+        assert_eq!(settings.devices.geoip_database, None);
+        let variables = |variable: &str| match variable {
+            "DEVICE_NOTIFICATIONS" => Some(String::from("false")),
+            "GEOIP_DATABASE" => Some(String::from("/GeoLite2-Country.mmdb")),
+            _ => None,
+        };
+        let settings = Settings::parse("", &variables).unwrap();
+        assert!(!settings.devices.notify);
+        assert_eq!(settings.devices.geoip_database, Some(String::from("/GeoLite2-Country.mmdb")));
+    }
+
+    #[test]
+    fn rejects_a_maximum_difficulty_below_the_difficulty() {
+        let variables = |variable: &str| match variable {
+            "CHALLENGE_DIFFICULTY" => Some(String::from("20")),
+            "CHALLENGE_MAXIMUM_DIFFICULTY" => Some(String::from("10")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("CHALLENGE_MAXIMUM_DIFFICULTY"));
+    }
+
+    #[test]
+    fn relays_the_outbox_only_when_redis_is_configured() {
+        assert_eq!(Settings::parse("", &no_variables).unwrap().outbox.redis_url, None);
+        let variables = |variable: &str| match variable {
+            "OUTBOX_REDIS_URL" => Some(String::from("http://redis:6379")),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("OUTBOX_REDIS_URL"));
+    }
+
+    #[test]
+    fn rejects_lanes_too_large_to_size_memory_for() {
+        let variables = |variable: &str| match variable {
+            "ARGON_LANES" => Some(u32::MAX.to_string()),
+            _ => None,
+        };
+        let error = Settings::parse("", &variables).err().unwrap();
+        assert!(error.problems[0].contains("ARGON_LANES"));
+    }
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let variables = |variable: &str| match variable {
+            "HASH_SECRET" => Some(String::from("hash-secret-value")),
+            "JWT_SECRET" => Some(String::from("jwt-secret-value")),
+            "DATABASE_PASSWORD" => Some(String::from("database-password-value")),
+            "SMTP_PASSWORD" => Some(String::from("smtp-password-value")),
+            "CHALLENGE_SECRET" => Some(String::from("challenge-secret-value")),
+            _ => None,
+        };
+        let output = format!("{:?}", Settings::parse("", &variables).unwrap());
+        let secrets = [
+            "hash-secret-value",
+            "jwt-secret-value",
+            "database-password-value",
+            "smtp-password-value",
+            "challenge-secret-value",
+        ];
+        for secret in secrets.iter() {
+            assert!(!output.contains(secret));
+        }
+        assert!(output.contains(REDACTED));
+    }
+
+    #[test]
+    fn rejects_invalid_toml() {
+        assert!(Settings::parse("[server", &no_variables).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{model, repository, Result};
+use crate::{configuration::settings::HashSettings, model, repository, utilities::hash, Result};
 
 #[derive(Eq, PartialEq, Debug)]
 pub enum Results {
@@ -12,16 +12,21 @@ pub async fn authorize<
     L: repository::LoginHistory,
     C: repository::Credentials,
 >(
-    user_credentials: &model::NameRequest,
+    user_credentials: &model::LoginRequest,
     auth_credentials: &C,
     login_history: &L,
+    settings: &HashSettings,
 ) -> Result<Results> {
-    if let Some(auth_record) = auth_credentials.by_name(&user_credentials.name).await? {
+    let stored_credentials = match user_credentials.identifier() {
+        model::Identifier::Name(name) => auth_credentials.by_name(&name).await?,
+        model::Identifier::Email(email) => auth_credentials.by_email(&email).await?,
+    };
+    if let Some(auth_record) = stored_credentials {
         let user_id = &auth_record.id;
         if auth_record.suspended()? {
             Ok(Results::Suspended)
         } else {
-            if auth_record.password_matches(&user_credentials.password)? {
+            if auth_record.password_matches(settings, &user_credentials.password)? {
                 Ok(Results::Valid(auth_record))
             } else {
                 login_history.suspend(user_id).await?;
@@ -29,6 +34,7 @@ pub async fn authorize<
             }
         }
     } else {
+        hash::dummy_authenticate(settings, &user_credentials.password)?;
         Ok(Results::None)
     }
 }
@@ -45,11 +51,11 @@ mod authorization_test {
     #[actix_rt::test]
     async fn returns_suspended_if_the_auth_record_has_been_suspended() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         let mut credentials = fake::credentials();
         credentials.locked_at = Some(SystemTime::now());
         state.credentials.by_name.returns(Some(credentials));
-        let result = authorize(&request, &state.credentials, &state.login_history)
+        let result = authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
             .await
             .expect("error occurred in authorize");
         assert_eq!(result, Results::Suspended);
@@ -58,9 +64,9 @@ mod authorization_test {
     #[actix_rt::test]
     async fn returns_none_if_no_record_is_found() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         state.credentials.by_name.returns(None);
-        let result = authorize(&request, &state.credentials, &state.login_history)
+        let result = authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
             .await
             .expect("error occurred in authorize");
         assert_eq!(result, Results::None);
@@ -69,11 +75,11 @@ mod authorization_test {
     #[actix_rt::test]
     async fn returns_invalid_if_credentials_dont_match() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         let record = fake::credentials();
         state.login_history.suspend.returns(());
         state.credentials.by_name.returns(Some(record));
-        let result = authorize(&request, &state.credentials, &state.login_history)
+        let result = authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
             .await
             .expect("error occurred in authorize");
         assert_eq!(result, Results::Invalid);
@@ -82,11 +88,11 @@ mod authorization_test {
     #[actix_rt::test]
     async fn calls_suspend_on_a_user_if_their_credentials_are_invalid() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         let record = fake::credentials();
         state.login_history.suspend.returns(());
         state.credentials.by_name.returns(Some(record));
-        authorize(&request, &state.credentials, &state.login_history)
+        authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
             .await
             .expect("error occurred in authorize");
         assert_eq!(state.login_history.suspend.times_called(), 1);
@@ -95,13 +101,66 @@ mod authorization_test {
     #[actix_rt::test]
     async fn returns_valid_if_credentials_match() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         let mut record = fake::credentials();
-        record.hash = hash::generate(&request.password).unwrap();
+        record.hash = hash::generate(&state.settings.hash, &request.password).unwrap();
         state.credentials.by_name.returns(Some(record.clone()));
-        let result = authorize(&request, &state.credentials, &state.login_history)
+        let result = authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
+            .await
+            .expect("error occurred in authorize");
+        assert_eq!(result, Results::Valid(record.clone()));
+    }
+
+    #[actix_rt::test]
+    async fn looks_up_credentials_by_email_if_the_identifier_is_an_email() {
+        let mut state = fake::service_state();
+        let request = fake::email_login_request();
+        let mut record = fake::credentials();
+        record.hash = hash::generate(&state.settings.hash, &request.password).unwrap();
+        state.credentials.by_email.returns(Some(record.clone()));
+        let result = authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
             .await
             .expect("error occurred in authorize");
         assert_eq!(result, Results::Valid(record.clone()));
+        assert_eq!(state.credentials.by_name.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn calls_suspend_if_credentials_found_by_email_dont_match() {
+        let mut state = fake::service_state();
+        let request = fake::email_login_request();
+        let record = fake::credentials();
+        state.login_history.suspend.returns(());
+        state.credentials.by_email.returns(Some(record));
+        let result = authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
+            .await
+            .expect("error occurred in authorize");
+        assert_eq!(result, Results::Invalid);
+        assert_eq!(state.login_history.suspend.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn verifies_against_a_dummy_hash_for_unknown_accounts() {
+        let mut state = fake::service_state();
+        let request = fake::login_request();
+        state.credentials.by_name.returns(None);
+        let before = hash::dummy_authentications();
+        authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
+            .await
+            .expect("error occurred in authorize");
+        assert_eq!(hash::dummy_authentications(), before + 1);
+    }
+
+    #[actix_rt::test]
+    async fn verifies_against_the_stored_hash_for_known_accounts() {
+        let mut state = fake::service_state();
+        let request = fake::login_request();
+        state.login_history.suspend.returns(());
+        state.credentials.by_name.returns(Some(fake::credentials()));
+        let before = hash::dummy_authentications();
+        authorize(&request, &state.credentials, &state.login_history, &state.settings.hash)
+            .await
+            .expect("error occurred in authorize");
+        assert_eq!(hash::dummy_authentications(), before);
     }
 }
@@ -0,0 +1,170 @@
+use crate::{challenge, configuration::settings::ChallengeSettings, model, repository, Result};
+use std::time::Duration;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Passed,
+    Required(model::Challenge),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attempt {
+    keys: Vec<String>,
+    account: String,
+}
+
+impl Attempt {
+    pub fn new(address: Option<&str>, account: &str) -> Attempt {
+        let account = model::account_key(account);
+        let mut keys = vec![account.clone()];
+        keys.extend(address.map(model::address_key));
+        Attempt { keys, account }
+    }
+}
+
+fn window(settings: &ChallengeSettings) -> Duration {
+    Duration::from_secs(settings.window)
+}
+
+pub async fn check<C: repository::Challenges, V: challenge::Verifier>(
+    challenges: &C,
+    verifier: &V,
+    settings: &ChallengeSettings,
+    attempt: &Attempt,
+    response: Option<&model::ChallengeResponse>,
+) -> Result<Outcome> {
+    if !settings.enabled {
+        return Ok(Outcome::Passed);
+    }
+    let failures = challenges.failures(&attempt.keys, window(settings)).await?;
+    if failures < settings.threshold {
+        return Ok(Outcome::Passed);
+    }
+    if let Some(response) = response {
+        if verifier.verify(response, failures).await? {
+            return Ok(Outcome::Passed);
+        }
+    }
+    Ok(Outcome::Required(verifier.issue(failures).await?))
+}
+
+pub async fn failed<C: repository::Challenges>(
+    challenges: &C,
+    settings: &ChallengeSettings,
+    attempt: &Attempt,
+) -> Result<()> {
+    if settings.enabled {
+        challenges.record_failure(&attempt.keys, window(settings)).await?;
+    }
+    Ok(())
+}
+
+/// Success only clears the account, so an address cycling through
+/// accounts keeps its count.
+pub async fn succeeded<C: repository::Challenges>(
+    challenges: &C,
+    settings: &ChallengeSettings,
+    attempt: &Attempt,
+) -> Result<()> {
+    if settings.enabled {
+        challenges.clear(&[attempt.account.clone()]).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use actix_rt;
+
+    fn enabled() -> ChallengeSettings {
+        ChallengeSettings {
+            enabled: true,
+            ..fake::settings().challenge.clone()
+        }
+    }
+
+    fn challenge() -> model::Challenge {
+        model::Challenge {
+            algorithm: String::from(model::PROOF_OF_WORK),
+            challenge: String::from("challenge"),
+            difficulty: 1,
+            expires_at: 0,
+        }
+    }
+
+    fn response() -> model::ChallengeResponse {
+        model::ChallengeResponse {
+            challenge: String::from("challenge"),
+            solution: String::from("1"),
+        }
+    }
+
+    #[test]
+    fn attempts_track_the_account_and_the_address() {
+        assert_eq!(Attempt::new(Some("10.0.0.1"), "someone").keys.len(), 2);
+        assert_eq!(Attempt::new(None, "someone").keys.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn passes_without_looking_up_failures_when_disabled() {
+        let state = fake::service_state();
+        let settings = ChallengeSettings { enabled: false, ..enabled() };
+        let result = check(&state.challenges, &state.challenge_verifier, &settings, &Attempt::new(None, "someone"), None)
+            .await
+            .unwrap();
+        assert_eq!(result, Outcome::Passed);
+        assert_eq!(state.challenges.failures.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn passes_below_the_threshold() {
+        let mut state = fake::service_state();
+        let settings = enabled();
+        state.challenges.failures.returns(settings.threshold - 1);
+        let result = check(&state.challenges, &state.challenge_verifier, &settings, &Attempt::new(None, "someone"), None)
+            .await
+            .unwrap();
+        assert_eq!(result, Outcome::Passed);
+    }
+
+    #[actix_rt::test]
+    async fn requires_a_challenge_once_the_threshold_is_reached() {
+        let mut state = fake::service_state();
+        let settings = enabled();
+        state.challenges.failures.returns(settings.threshold);
+        state.challenge_verifier.issue.returns(challenge());
+        let result = check(&state.challenges, &state.challenge_verifier, &settings, &Attempt::new(None, "someone"), None)
+            .await
+            .unwrap();
+        assert_eq!(result, Outcome::Required(challenge()));
+    }
+
+    #[actix_rt::test]
+    async fn passes_with_a_verified_response() {
+        let mut state = fake::service_state();
+        let settings = enabled();
+        state.challenges.failures.returns(settings.threshold);
+        state.challenge_verifier.verify.returns(true);
+        let response = response();
+        let result = check(&state.challenges, &state.challenge_verifier, &settings, &Attempt::new(None, "someone"), Some(&response))
+            .await
+            .unwrap();
+        assert_eq!(result, Outcome::Passed);
+    }
+
+    #[actix_rt::test]
+    async fn issues_a_new_challenge_when_the_response_fails() {
+        let mut state = fake::service_state();
+        let settings = enabled();
+        state.challenges.failures.returns(settings.threshold);
+        state.challenge_verifier.verify.returns(false);
+        state.challenge_verifier.issue.returns(challenge());
+        let response = response();
+        let result = check(&state.challenges, &state.challenge_verifier, &settings, &Attempt::new(None, "someone"), Some(&response))
+            .await
+            .unwrap();
+        assert_eq!(result, Outcome::Required(challenge()));
+    }
+}
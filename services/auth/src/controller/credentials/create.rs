@@ -1,59 +1,191 @@
-use crate::{utilities::{password, hash}, model, repository, Result};
+use crate::{
+    configuration::{settings::RegistrationMode, Settings},
+    mail, mail::templates,
+    utilities::{name as user_name, password, hash},
+    model,
+    repository,
+    Result,
+};
+use logging::warn;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SaveResults {
     WeakPassword(password::PasswordIssues),
+    InvalidName(user_name::NameIssues),
+    InvitationRequired,
+    InvitationExpired,
     Success(model::Credentials),
     Conflict,
+    EmailTaken,
 }
 
-pub async fn create<C: repository::Credentials>(
+enum InvitationCheck {
+    NotRequired,
+    Valid(model::Invitation),
+    Invalid(SaveResults),
+}
+
+async fn check_invitation<I: repository::Invitations>(
+    invitations: &I,
+    request: &model::FullRequest,
+    mode: RegistrationMode,
+) -> Result<InvitationCheck> {
+    if mode == RegistrationMode::Open {
+        return Ok(InvitationCheck::NotRequired);
+    }
+    let invitation = match &request.invitation {
+        Some(token) => invitations.by_token(token).await?,
+        None => None,
+    };
+    Ok(match invitation {
+        Some(invitation) if invitation.used() || !invitation.for_email(&request.email) => {
+            InvitationCheck::Invalid(SaveResults::InvitationRequired)
+        }
+        Some(invitation) if invitation.expired() => InvitationCheck::Invalid(SaveResults::InvitationExpired),
+        Some(invitation) => InvitationCheck::Valid(invitation),
+        None => InvitationCheck::Invalid(SaveResults::InvitationRequired),
+    })
+}
+
+/// Sent off the request path, so neither the time it takes to deliver nor a
+/// failure to deliver reveals that the email is registered.
+fn notify_registration_attempt<M: mail::Mailer + 'static>(mailer: &M, message: mail::Message) {
+    let mailer = mailer.clone();
+    actix_rt::spawn(async move {
+        if let Err(error) = mailer.send(&message).await {
+            warn!(error = %error, "Failed to send a registration attempt notice");
+        }
+    });
+}
+
+/// With private registration an email that is already registered results in
+/// `EmailTaken` rather than `Conflict`, costs as much as a successful sign up,
+/// and notifies the owner of the address instead.
+pub async fn create<C: repository::Credentials, I: repository::Invitations, M: mail::Mailer + 'static>(
     credentials: &C,
+    invitations: &I,
+    mailer: &M,
     request: &model::FullRequest,
+    settings: &Settings,
 ) -> Result<SaveResults> {
     let model::FullRequest {
         name,
         email,
         password,
+        ..
     }: &model::FullRequest = request;
-    if let password::Strength::Weak(problems) = password::strength(name, email, password)? {
-        Ok(SaveResults::WeakPassword(problems))
-    } else {
-        match credentials.get_status(name, email).await? {
-            repository::CredentialStatus::None => Ok(SaveResults::Success(
-                credentials
-                    .save_credentials(&model::FullRequest {
-                        name: String::from(name),
-                        email: String::from(email),
-                        password: hash::generate(&password)?,
-                    })
-                    .await?,
-            )),
-            _ => Ok(SaveResults::Conflict),
+    let name = user_name::normalize(name);
+    if let user_name::Validity::Invalid(problems) = user_name::validate(&name, &settings.names.reserved) {
+        return Ok(SaveResults::InvalidName(problems));
+    }
+    if let password::Strength::Weak(problems) = password::strength(&name, email, password)? {
+        return Ok(SaveResults::WeakPassword(problems));
+    }
+    let invitation = match check_invitation(invitations, request, settings.registration.mode).await? {
+        InvitationCheck::Invalid(result) => return Ok(result),
+        InvitationCheck::Valid(invitation) => Some(invitation),
+        InvitationCheck::NotRequired => None,
+    };
+    if settings.registration.private {
+        if let Some(owner) = credentials.by_email(email).await? {
+            hash::dummy_authenticate(&settings.hash, password)?;
+            if owner.deleted_at.is_none() {
+                notify_registration_attempt(mailer, templates::registration_attempt(&settings.links, &owner.email));
+            }
+            return Ok(SaveResults::EmailTaken);
         }
     }
+    match credentials.get_status(&name, email).await? {
+        repository::CredentialStatus::None => {}
+        _ => return Ok(SaveResults::Conflict),
+    }
+    let hashed = model::FullRequest {
+        name,
+        email: String::from(email),
+        password: hash::generate(&settings.hash, &password)?,
+        invitation: None,
+    };
+    let saved = match &invitation {
+        Some(invitation) => match credentials.save_invited_credentials(&hashed, invitation).await? {
+            Some(saved) => saved,
+            None => return Ok(SaveResults::InvitationRequired),
+        },
+        None => credentials.save_credentials(&hashed).await?,
+    };
+    Ok(SaveResults::Success(saved))
 }
 
 #[cfg(test)]
 mod credentials_create_test {
     use super::*;
-    use crate::utilities::test::fake;
+    use crate::{
+        configuration::settings::RegistrationSettings,
+        error::Error,
+        utilities::{test, test::fake},
+    };
     use actix_rt;
 
     const WEAK_PASSWORD: &str = "password";
 
+    fn registering(registration: RegistrationSettings) -> Settings {
+        Settings {
+            registration,
+            ..fake::settings().clone()
+        }
+    }
+
+    fn open() -> Settings {
+        registering(RegistrationSettings {
+            mode: RegistrationMode::Open,
+            invitation_lifetime: 60,
+            private: false,
+        })
+    }
+
+    fn invite_only() -> Settings {
+        registering(RegistrationSettings { mode: RegistrationMode::InviteOnly, ..open().registration })
+    }
+
+    fn private() -> Settings {
+        registering(RegistrationSettings { private: true, ..open().registration })
+    }
+
     #[actix_rt::test]
     async fn returns_weak_password_if_the_password_is_too_weak() {
         let mut request = fake::full_request();
         let state = fake::service_state();
         request.password = WEAK_PASSWORD.to_string();
-        let result = create(&state.credentials, &request).await.unwrap();
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &open()).await.unwrap();
         match result {
             SaveResults::WeakPassword(_) => assert!(true),
             _ => assert!(false),
         }
     }
 
+    #[actix_rt::test]
+    async fn returns_invalid_name_if_the_name_is_reserved() {
+        let mut request = fake::full_request();
+        let state = fake::service_state();
+        request.name = String::from("Admin");
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &open()).await.unwrap();
+        match result {
+            SaveResults::InvalidName(_) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn returns_invalid_name_if_the_name_contains_an_email_separator() {
+        let mut request = fake::full_request();
+        let state = fake::service_state();
+        request.name = fake::email_address();
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &open()).await.unwrap();
+        match result {
+            SaveResults::InvalidName(_) => assert!(true),
+            _ => assert!(false),
+        }
+    }
+
     #[actix_rt::test]
     async fn returns_conflict_if_credentials_already_exist() {
         let request = fake::full_request();
@@ -62,7 +194,7 @@ mod credentials_create_test {
             .credentials
             .get_status
             .returns(repository::CredentialStatus::Exists);
-        let result = create(&state.credentials, &request).await.unwrap();
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &open()).await.unwrap();
         assert_eq!(result, SaveResults::Conflict);
     }
 
@@ -74,7 +206,7 @@ mod credentials_create_test {
             .credentials
             .get_status
             .returns(repository::CredentialStatus::Deleted);
-        let result = create(&state.credentials, &request).await.unwrap();
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &open()).await.unwrap();
         assert_eq!(result, SaveResults::Conflict);
     }
 
@@ -91,7 +223,124 @@ mod credentials_create_test {
             .credentials
             .save_credentials
             .returns(credentials.clone());
-        let result = create(&state.credentials, &request).await.unwrap();
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &open()).await.unwrap();
         assert_eq!(result, SaveResults::Success(credentials.clone()));
     }
+
+    fn invited(request: &model::FullRequest) -> (model::FullRequest, model::Invitation) {
+        let mut invitation = fake::invitation();
+        invitation.email = request.email.clone();
+        let request = model::FullRequest {
+            invitation: Some(hash::token()),
+            ..request.clone()
+        };
+        (request, invitation)
+    }
+
+    #[actix_rt::test]
+    async fn requires_an_invitation_when_invite_only() {
+        let request = fake::full_request();
+        let state = fake::service_state();
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &invite_only()).await.unwrap();
+        assert_eq!(result, SaveResults::InvitationRequired);
+        assert_eq!(state.credentials.get_status.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_an_invitation_for_another_email() {
+        let (request, mut invitation) = invited(&fake::full_request());
+        invitation.email = fake::email_address();
+        let mut state = fake::service_state();
+        state.invitations.by_token.returns(Some(invitation));
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &invite_only()).await.unwrap();
+        assert_eq!(result, SaveResults::InvitationRequired);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_an_expired_invitation() {
+        let (request, mut invitation) = invited(&fake::full_request());
+        invitation.expires_at = std::time::SystemTime::now();
+        let mut state = fake::service_state();
+        state.invitations.by_token.returns(Some(invitation));
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &invite_only()).await.unwrap();
+        assert_eq!(result, SaveResults::InvitationExpired);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_an_invitation_claimed_concurrently() {
+        let (request, invitation) = invited(&fake::full_request());
+        let mut state = fake::service_state();
+        state.invitations.by_token.returns(Some(invitation));
+        state.credentials.get_status.returns(repository::CredentialStatus::None);
+        state.credentials.save_invited_credentials.returns(None);
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &invite_only()).await.unwrap();
+        assert_eq!(result, SaveResults::InvitationRequired);
+        assert_eq!(state.credentials.save_credentials.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn consumes_a_valid_invitation() {
+        let (request, invitation) = invited(&fake::full_request());
+        let credentials = fake::credentials();
+        let mut state = fake::service_state();
+        state.invitations.by_token.returns(Some(invitation));
+        state.credentials.get_status.returns(repository::CredentialStatus::None);
+        state.credentials.save_invited_credentials.returns(Some(credentials.clone()));
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &invite_only()).await.unwrap();
+        assert_eq!(result, SaveResults::Success(credentials));
+        assert_eq!(state.credentials.save_invited_credentials.times_called(), 1);
+        assert_eq!(state.credentials.save_credentials.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn notifies_the_owner_when_registering_privately_with_a_taken_email() {
+        let request = fake::full_request();
+        let mut state = fake::service_state();
+        state.credentials.by_email.returns(Some(fake::credentials()));
+        state.mailer.send.returns(());
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &private()).await.unwrap();
+        assert_eq!(result, SaveResults::EmailTaken);
+        test::settle().await;
+        assert_eq!(state.mailer.send.times_called(), 1);
+        assert_eq!(state.credentials.save_credentials.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn reports_a_taken_email_when_the_notice_cannot_be_sent() {
+        let request = fake::full_request();
+        let mut state = fake::service_state();
+        state.credentials.by_email.returns(Some(fake::credentials()));
+        state.mailer.send.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &private()).await.unwrap();
+        assert_eq!(result, SaveResults::EmailTaken);
+        test::settle().await;
+        assert_eq!(state.mailer.send.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn does_not_notify_deleted_owners_when_registering_privately() {
+        let request = fake::full_request();
+        let mut state = fake::service_state();
+        let deleted = model::Credentials {
+            deleted_at: Some(std::time::SystemTime::now()),
+            ..fake::credentials()
+        };
+        state.credentials.by_email.returns(Some(deleted));
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &private()).await.unwrap();
+        assert_eq!(result, SaveResults::EmailTaken);
+        test::settle().await;
+        assert_eq!(state.mailer.send.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn registers_privately_when_the_email_is_free() {
+        let request = fake::full_request();
+        let mut state = fake::service_state();
+        let credentials = fake::credentials();
+        state.credentials.by_email.returns(None);
+        state.credentials.get_status.returns(repository::CredentialStatus::None);
+        state.credentials.save_credentials.returns(credentials.clone());
+        let result = create(&state.credentials, &state.invitations, &state.mailer, &request, &private()).await.unwrap();
+        assert_eq!(result, SaveResults::Success(credentials));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{model, repository, Result};
+use crate::{configuration::settings::HashSettings, model, repository, utilities::hash, Result};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum DeleteResults {
@@ -15,13 +15,14 @@ pub async fn delete<
     credentials: &C,
     login_history: &L,
     request: &model::EmailRequest,
+    settings: &HashSettings,
 ) -> Result<DeleteResults> {
     let model::EmailRequest { password, email }: &model::EmailRequest = request;
     if let Some(stored_credentials) = credentials.by_email(&email).await? {
         if stored_credentials.suspended()? {
             Ok(DeleteResults::Suspended)
         } else {
-            if stored_credentials.password_matches(&password)? {
+            if stored_credentials.password_matches(settings, &password)? {
                 credentials.mark_as_deleted_by_email(&email).await?;
                 Ok(DeleteResults::Success)
             } else {
@@ -30,6 +31,7 @@ pub async fn delete<
             }
         }
     } else {
+        hash::dummy_authenticate(settings, &password)?;
         Ok(DeleteResults::NotFound)
     }
 }
@@ -49,7 +51,7 @@ mod credentials_delete_test {
         let request = fake::email_request();
         let mut state = fake::service_state();
         state.credentials.by_email.returns(None);
-        let result = delete(&state.credentials, &state.login_history, &request)
+        let result = delete(&state.credentials, &state.login_history, &request, &state.settings.hash)
             .await
             .unwrap();
         assert_eq!(result, DeleteResults::NotFound);
@@ -62,7 +64,7 @@ mod credentials_delete_test {
         let mut state = fake::service_state();
         credentials.locked_at = Some(SystemTime::now());
         state.credentials.by_email.returns(Some(credentials));
-        let result = delete(&state.credentials, &state.login_history, &request)
+        let result = delete(&state.credentials, &state.login_history, &request, &state.settings.hash)
             .await
             .unwrap();
         assert_eq!(result, DeleteResults::Suspended);
@@ -75,7 +77,7 @@ mod credentials_delete_test {
         let mut state = fake::service_state();
         state.credentials.by_email.returns(Some(credentials));
         state.login_history.suspend.returns(());
-        let result = delete(&state.credentials, &state.login_history, &request)
+        let result = delete(&state.credentials, &state.login_history, &request, &state.settings.hash)
             .await
             .unwrap();
         assert_eq!(result, DeleteResults::Unauthorized);
@@ -88,7 +90,7 @@ mod credentials_delete_test {
         let mut state = fake::service_state();
         state.credentials.by_email.returns(Some(credentials));
         state.login_history.suspend.returns(());
-        delete(&state.credentials, &state.login_history, &request)
+        delete(&state.credentials, &state.login_history, &request, &state.settings.hash)
             .await
             .unwrap();
         assert_eq!(state.login_history.suspend.times_called(), 1);
@@ -99,10 +101,10 @@ mod credentials_delete_test {
         let request = fake::email_request();
         let mut credentials = fake::credentials();
         let mut state = fake::service_state();
-        credentials.hash = hash::generate(&request.password).unwrap();
+        credentials.hash = hash::generate(&state.settings.hash, &request.password).unwrap();
         state.credentials.by_email.returns(Some(credentials));
         state.credentials.mark_as_deleted_by_email.returns(1);
-        let result = delete(&state.credentials, &state.login_history, &request)
+        let result = delete(&state.credentials, &state.login_history, &request, &state.settings.hash)
             .await
             .unwrap();
         assert_eq!(result, DeleteResults::Success);
@@ -113,10 +115,10 @@ mod credentials_delete_test {
         let request = fake::email_request();
         let mut credentials = fake::credentials();
         let mut state = fake::service_state();
-        credentials.hash = hash::generate(&request.password).unwrap();
+        credentials.hash = hash::generate(&state.settings.hash, &request.password).unwrap();
         state.credentials.by_email.returns(Some(credentials));
         state.credentials.mark_as_deleted_by_email.returns(1);
-        delete(&state.credentials, &state.login_history, &request)
+        delete(&state.credentials, &state.login_history, &request, &state.settings.hash)
             .await
             .unwrap();
         assert_eq!(state.credentials.mark_as_deleted_by_email.times_called(), 1);
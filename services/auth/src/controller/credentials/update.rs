@@ -1,53 +1,106 @@
-use crate::{utilities::hash, model, repository, Result};
+use crate::{
+    configuration::{settings::LinkSettings, Settings},
+    mail, mail::templates, model, repository,
+    utilities::{hash, name as user_name},
+    Result,
+};
+use logging::warn;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum UpdateResults {
     Success(model::Credentials),
+    Pending(model::Credentials),
+    InvalidName(user_name::NameIssues),
+    Conflict,
     NotFound,
     Suspended,
     Unauthorized,
 }
 
+/// The change is already committed, so failing to deliver either message is
+/// logged rather than reported; the user can request the change again.
+async fn notify_email_change<M: mail::Mailer>(mailer: &M, links: &LinkSettings, change: &model::EmailChange) {
+    let messages = [
+        templates::email_change(links, &change.new_email, &change.id, &change.token),
+        templates::email_change_notice(links, &change.old_email, &change.new_email, &change.id, &change.revert_token),
+    ];
+    for message in messages.iter() {
+        if let Err(error) = mailer.send(message).await {
+            warn!(error = %error, subject = %message.subject, "Failed to send an email change message");
+        }
+    }
+}
+
 pub async fn update<
     L: repository::LoginHistory,
     C: repository::Credentials,
+    M: mail::Mailer,
 >(
     credentials: &C,
     login_history: &L,
+    mailer: &M,
     auth_details: &model::EmailRequest,
     request: &model::CredentialsRequest,
+    settings: &Settings,
 ) -> Result<UpdateResults> {
     let model::CredentialsRequest {
         name,
         email,
         password,
     }: &model::CredentialsRequest = request;
+    let name = name.as_ref().map(|name| user_name::normalize(name));
+    if let Some(name) = &name {
+        if let user_name::Validity::Invalid(problems) =
+            user_name::validate(name, &settings.names.reserved)
+        {
+            return Ok(UpdateResults::InvalidName(problems));
+        }
+    }
     if let Some(stored_credentials) = credentials.by_email(&auth_details.email).await? {
         if stored_credentials.suspended()? {
             Ok(UpdateResults::Suspended)
         } else {
-            if stored_credentials.password_matches(&auth_details.password)? {
-                Ok(UpdateResults::Success(
-                    credentials
-                        .update_credentials(&model::Credentials {
-                            name: name.as_ref().map_or(stored_credentials.name, String::from),
-                            email: email
-                                .as_ref()
-                                .map_or(stored_credentials.email, String::from),
-                            hash: match &password {
-                                Some(p) => hash::generate(p)?,
-                                None => stored_credentials.hash,
-                            },
-                            ..stored_credentials
-                        })
-                        .await?,
-                ))
+            if stored_credentials.password_matches(&settings.hash, &auth_details.password)? {
+                if let Some(name) = &name {
+                    if let Some(owner) = credentials.by_name(name).await? {
+                        if owner.id != stored_credentials.id {
+                            return Ok(UpdateResults::Conflict);
+                        }
+                    }
+                }
+                let new_email = email
+                    .as_ref()
+                    .filter(|email| **email != stored_credentials.email)
+                    .cloned();
+                let updated = model::Credentials {
+                    name: name.unwrap_or(stored_credentials.name),
+                    hash: match &password {
+                        Some(p) => hash::generate(&settings.hash, p)?,
+                        None => stored_credentials.hash,
+                    },
+                    ..stored_credentials
+                };
+                let result = match new_email {
+                    Some(new_email) => match credentials.update_with_email_change(&updated, &new_email).await {
+                        Ok((updated_credentials, change)) => {
+                            notify_email_change(mailer, &settings.links, &change).await;
+                            Ok(UpdateResults::Pending(updated_credentials))
+                        }
+                        Err(error) => Err(error),
+                    },
+                    None => credentials.update_credentials(&updated).await.map(UpdateResults::Success),
+                };
+                match result {
+                    Err(error) if error.is_unique_violation() => Ok(UpdateResults::Conflict),
+                    result => result,
+                }
             } else {
                 login_history.suspend(&stored_credentials.id).await?;
                 Ok(UpdateResults::Unauthorized)
             }
         }
     } else {
+        hash::dummy_authenticate(&settings.hash, &auth_details.password)?;
         Ok(UpdateResults::NotFound)
     }
 }
@@ -55,6 +108,7 @@ pub async fn update<
 #[cfg(test)]
 mod credentials_update_test {
     use super::*;
+    use crate::error::Error;
     use crate::utilities::{
         test::fake,
         hash,
@@ -71,14 +125,38 @@ mod credentials_update_test {
         let result = update(
             &state.credentials,
             &state.login_history,
+            &state.mailer,
             &auth_request,
             &update_request,
+            state.settings,
         )
         .await
         .unwrap();
         assert_eq!(result, UpdateResults::NotFound);
     }
 
+    #[actix_rt::test]
+    async fn returns_invalid_name_if_the_new_name_is_reserved() {
+        let mut update_request = fake::credentials_request();
+        let auth_request = fake::email_request();
+        let state = fake::service_state();
+        update_request.name = Some(String::from("administrator"));
+        let result = update(
+            &state.credentials,
+            &state.login_history,
+            &state.mailer,
+            &auth_request,
+            &update_request,
+            state.settings,
+        )
+        .await
+        .unwrap();
+        match result {
+            UpdateResults::InvalidName(_) => assert!(true),
+            _ => assert!(false),
+        };
+    }
+
     #[actix_rt::test]
     async fn returns_suspended_if_the_user_has_been_suspended() {
         let mut credentials = fake::credentials();
@@ -90,8 +168,10 @@ mod credentials_update_test {
         let result = update(
             &state.credentials,
             &state.login_history,
+            &state.mailer,
             &auth_request,
             &update_request,
+            state.settings,
         )
         .await
         .unwrap();
@@ -109,8 +189,10 @@ mod credentials_update_test {
         let result = update(
             &state.credentials,
             &state.login_history,
+            &state.mailer,
             &auth_request,
             &update_request,
+            state.settings,
         )
         .await
         .unwrap();
@@ -128,8 +210,10 @@ mod credentials_update_test {
         let result = update(
             &state.credentials,
             &state.login_history,
+            &state.mailer,
             &auth_request,
             &update_request,
+            state.settings,
         )
         .await
         .unwrap();
@@ -139,14 +223,16 @@ mod credentials_update_test {
     #[actix_rt::test]
     async fn returns_success_if_credentials_match() {
         let mut credentials = fake::credentials();
-        let update_request = fake::credentials_request();
+        let mut update_request = fake::credentials_request();
         let auth_request = fake::email_request();
         let mut state = fake::service_state();
-        credentials.hash = hash::generate(&auth_request.password).unwrap();
+        credentials.hash = hash::generate(&state.settings.hash, &auth_request.password).unwrap();
+        update_request.email = None;
         state
             .credentials
             .by_email
             .returns(Some(credentials.clone()));
+        state.credentials.by_name.returns(None);
         state
             .credentials
             .update_credentials
@@ -154,8 +240,10 @@ mod credentials_update_test {
         let result = update(
             &state.credentials,
             &state.login_history,
+            &state.mailer,
             &auth_request,
             &update_request,
+            state.settings,
         )
         .await
         .unwrap();
@@ -168,14 +256,16 @@ mod credentials_update_test {
     #[actix_rt::test]
     async fn calls_update_credentials_if_credentials_match() {
         let mut credentials = fake::credentials();
-        let update_request = fake::credentials_request();
+        let mut update_request = fake::credentials_request();
         let auth_request = fake::email_request();
         let mut state = fake::service_state();
-        credentials.hash = hash::generate(&auth_request.password).unwrap();
+        credentials.hash = hash::generate(&state.settings.hash, &auth_request.password).unwrap();
+        update_request.email = None;
         state
             .credentials
             .by_email
             .returns(Some(credentials.clone()));
+        state.credentials.by_name.returns(None);
         state
             .credentials
             .update_credentials
@@ -183,11 +273,160 @@ mod credentials_update_test {
         update(
             &state.credentials,
             &state.login_history,
+            &state.mailer,
             &auth_request,
             &update_request,
+            state.settings,
         )
         .await
         .unwrap();
         assert_eq!(state.credentials.update_credentials.times_called(), 1);
     }
+
+    #[actix_rt::test]
+    async fn holds_a_new_email_as_pending_and_notifies_both_addresses() {
+        let mut credentials = fake::credentials();
+        let update_request = fake::credentials_request();
+        let auth_request = fake::email_request();
+        let mut state = fake::service_state();
+        credentials.hash = hash::generate(&state.settings.hash, &auth_request.password).unwrap();
+        state
+            .credentials
+            .by_email
+            .returns(Some(credentials.clone()));
+        state.credentials.by_name.returns(None);
+        state
+            .credentials
+            .update_with_email_change
+            .returns((credentials.clone(), fake::email_change()));
+        state.mailer.send.returns(()).returns(());
+        let result = update(
+            &state.credentials,
+            &state.login_history,
+            &state.mailer,
+            &auth_request,
+            &update_request,
+            state.settings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, UpdateResults::Pending(credentials));
+        assert_eq!(state.credentials.update_with_email_change.times_called(), 1);
+        assert_eq!(state.credentials.update_credentials.times_called(), 0);
+        assert_eq!(state.mailer.send.times_called(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn keeps_a_pending_email_change_when_mail_delivery_fails() {
+        let mut credentials = fake::credentials();
+        let update_request = fake::credentials_request();
+        let auth_request = fake::email_request();
+        let mut state = fake::service_state();
+        credentials.hash = hash::generate(&state.settings.hash, &auth_request.password).unwrap();
+        state.credentials.by_email.returns(Some(credentials.clone()));
+        state.credentials.by_name.returns(None);
+        state
+            .credentials
+            .update_with_email_change
+            .returns((credentials.clone(), fake::email_change()));
+        state
+            .mailer
+            .send
+            .throws_error(Error::InternalServerError(String::from("testing")))
+            .returns(());
+        let result = update(
+            &state.credentials,
+            &state.login_history,
+            &state.mailer,
+            &auth_request,
+            &update_request,
+            state.settings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, UpdateResults::Pending(credentials));
+        assert_eq!(state.mailer.send.times_called(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn does_not_request_an_email_change_if_the_email_is_unchanged() {
+        let mut credentials = fake::credentials();
+        let mut update_request = fake::credentials_request();
+        let auth_request = fake::email_request();
+        let mut state = fake::service_state();
+        credentials.hash = hash::generate(&state.settings.hash, &auth_request.password).unwrap();
+        update_request.email = Some(credentials.email.clone());
+        state
+            .credentials
+            .by_email
+            .returns(Some(credentials.clone()));
+        state.credentials.by_name.returns(None);
+        state
+            .credentials
+            .update_credentials
+            .returns(credentials.clone());
+        let result = update(
+            &state.credentials,
+            &state.login_history,
+            &state.mailer,
+            &auth_request,
+            &update_request,
+            state.settings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, UpdateResults::Success(credentials));
+        assert_eq!(state.credentials.update_with_email_change.times_called(), 0);
+        assert_eq!(state.mailer.send.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn returns_conflict_if_another_account_has_a_confusable_name() {
+        let mut credentials = fake::credentials();
+        let update_request = fake::credentials_request();
+        let auth_request = fake::email_request();
+        let mut state = fake::service_state();
+        credentials.hash = hash::generate(&state.settings.hash, &auth_request.password).unwrap();
+        state.credentials.by_email.returns(Some(credentials.clone()));
+        state.credentials.by_name.returns(Some(model::Credentials { id: credentials.id + 1, ..fake::credentials() }));
+        let result = update(
+            &state.credentials,
+            &state.login_history,
+            &state.mailer,
+            &auth_request,
+            &update_request,
+            state.settings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, UpdateResults::Conflict);
+        assert_eq!(state.credentials.update_with_email_change.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn returns_conflict_if_the_name_is_taken_while_updating() {
+        let mut credentials = fake::credentials();
+        let mut update_request = fake::credentials_request();
+        let auth_request = fake::email_request();
+        let mut state = fake::service_state();
+        credentials.hash = hash::generate(&state.settings.hash, &auth_request.password).unwrap();
+        update_request.email = None;
+        state.credentials.by_email.returns(Some(credentials.clone()));
+        state.credentials.by_name.returns(None);
+        state
+            .credentials
+            .update_credentials
+            .throws_error(Error::DatabaseError(database::Error::UniqueViolation(String::from("testing"))));
+        let result = update(
+            &state.credentials,
+            &state.login_history,
+            &state.mailer,
+            &auth_request,
+            &update_request,
+            state.settings,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, UpdateResults::Conflict);
+    }
 }
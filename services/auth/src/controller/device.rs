@@ -0,0 +1,161 @@
+use crate::{
+    configuration::settings::{HashSettings, LinkSettings},
+    geoip, mail, mail::templates, model, repository, Result,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisownResults {
+    Disowned,
+    Invalid,
+    Expired,
+    NotFound,
+}
+
+/// Records a successful sign in and emails the account owner when it came
+/// from a device, network or country not seen before.
+pub async fn observe<D: repository::Devices, L: geoip::Locator, M: mail::Mailer>(
+    devices: &D,
+    locator: &L,
+    mailer: &M,
+    credentials: &model::Credentials,
+    sighting: model::Sighting,
+    links: &LinkSettings,
+) -> Result<Vec<model::Novelty>> {
+    let country = sighting.address.and_then(|address| locator.country(address));
+    let sighting = sighting.located(country);
+    let novelties = devices.observe(credentials.id, &sighting).await?;
+    if !novelties.is_empty() {
+        let alert = devices.alert(credentials.id, &sighting).await?;
+        mailer
+            .send(&templates::new_device(links, &credentials.email, &sighting, &alert.id, &alert.token))
+            .await?;
+    }
+    Ok(novelties)
+}
+
+pub async fn disown<D: repository::Devices>(
+    devices: &D,
+    confirmation: &model::DeviceAlertConfirmation,
+    settings: &HashSettings,
+) -> Result<DisownResults> {
+    Ok(if let Some(alert) = devices.alert_by_id(&confirmation.id).await? {
+        if alert.used() || alert.expired()? {
+            DisownResults::Expired
+        } else if !alert.matches_token(settings, &confirmation.token)? {
+            DisownResults::Invalid
+        } else if devices.disown(&alert).await? {
+            DisownResults::Disowned
+        } else {
+            DisownResults::Expired
+        }
+    } else {
+        DisownResults::NotFound
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, utilities::test::fake};
+    use actix_rt;
+    use std::time::SystemTime;
+
+    #[actix_rt::test]
+    async fn does_not_send_mail_for_a_familiar_device() {
+        let mut state = fake::service_state();
+        state.locator.country.returns(None);
+        state.devices.observe.returns(vec![]);
+        let novelties = observe(
+            &state.devices,
+            &state.locator,
+            &state.mailer,
+            &fake::credentials(),
+            fake::sighting(),
+            &state.settings.links,
+        )
+        .await
+        .unwrap();
+        assert!(novelties.is_empty());
+        assert_eq!(state.devices.alert.times_called(), 0);
+        assert_eq!(state.mailer.send.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn sends_an_alert_for_a_new_device() {
+        let mut state = fake::service_state();
+        state.locator.country.returns(Some(String::from("NZ")));
+        state.devices.observe.returns(vec![model::Novelty::Device, model::Novelty::Country]);
+        state.devices.alert.returns(fake::device_alert());
+        state.mailer.send.returns(());
+        let novelties = observe(
+            &state.devices,
+            &state.locator,
+            &state.mailer,
+            &fake::credentials(),
+            fake::sighting(),
+            &state.settings.links,
+        )
+        .await
+        .unwrap();
+        assert_eq!(novelties, vec![model::Novelty::Device, model::Novelty::Country]);
+        assert_eq!(state.mailer.send.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn returns_an_error_if_the_alert_cannot_be_sent() {
+        let mut state = fake::service_state();
+        state.locator.country.returns(None);
+        state.devices.observe.returns(vec![model::Novelty::Network]);
+        state.devices.alert.returns(fake::device_alert());
+        state.mailer.send.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = observe(
+            &state.devices,
+            &state.locator,
+            &state.mailer,
+            &fake::credentials(),
+            fake::sighting(),
+            &state.settings.links,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn returns_not_found_when_no_alert_matches_the_id() {
+        let mut state = fake::service_state();
+        state.devices.alert_by_id.returns(None);
+        let result = disown(&state.devices, &fake::device_alert_confirmation(), &state.settings.hash).await.unwrap();
+        assert_eq!(result, DisownResults::NotFound);
+    }
+
+    #[actix_rt::test]
+    async fn returns_invalid_when_the_token_does_not_match() {
+        let mut state = fake::service_state();
+        state.devices.alert_by_id.returns(Some(fake::device_alert()));
+        let result = disown(&state.devices, &fake::device_alert_confirmation(), &state.settings.hash).await.unwrap();
+        assert_eq!(result, DisownResults::Invalid);
+        assert_eq!(state.devices.disown.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_alert_was_already_used() {
+        let confirmation = fake::device_alert_confirmation();
+        let mut alert = fake::disownable_device_alert(&confirmation);
+        let mut state = fake::service_state();
+        alert.used_at = Some(SystemTime::now());
+        state.devices.alert_by_id.returns(Some(alert));
+        let result = disown(&state.devices, &confirmation, &state.settings.hash).await.unwrap();
+        assert_eq!(result, DisownResults::Expired);
+    }
+
+    #[actix_rt::test]
+    async fn revokes_sessions_when_the_token_matches() {
+        let confirmation = fake::device_alert_confirmation();
+        let mut state = fake::service_state();
+        state.devices.alert_by_id.returns(Some(fake::disownable_device_alert(&confirmation)));
+        state.devices.disown.returns(true);
+        let result = disown(&state.devices, &confirmation, &state.settings.hash).await.unwrap();
+        assert_eq!(result, DisownResults::Disowned);
+        assert_eq!(state.devices.disown.times_called(), 1);
+    }
+}
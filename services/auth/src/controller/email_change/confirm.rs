@@ -0,0 +1,159 @@
+use crate::{configuration::settings::HashSettings, model, repository, Result};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConfirmResults {
+    Confirmed(model::Credentials),
+    Conflict,
+    Invalid,
+    Expired,
+    NotFound,
+}
+
+pub async fn confirm<E: repository::EmailChanges, C: repository::Credentials>(
+    email_changes: &E,
+    credentials: &C,
+    confirmation: &model::EmailChangeConfirmation,
+    settings: &HashSettings,
+) -> Result<ConfirmResults> {
+    Ok(if let Some(change) = email_changes.by_id(&confirmation.id).await? {
+        if change.confirmed() || change.reverted() || change.expired()? {
+            ConfirmResults::Expired
+        } else if !change.matches_token(settings, &confirmation.token)? {
+            ConfirmResults::Invalid
+        } else {
+            match credentials.by_id(change.user_id).await? {
+                Some(stored_credentials) if stored_credentials.deleted_at.is_none() => {
+                    if credentials.by_email(&change.new_email).await?.is_some() {
+                        ConfirmResults::Conflict
+                    } else {
+                        let updated = model::Credentials {
+                            email: change.new_email,
+                            ..stored_credentials
+                        };
+                        match credentials.update_with_confirmed_email_change(&updated, &change.id).await {
+                            Ok(Some(updated)) => ConfirmResults::Confirmed(updated),
+                            Ok(None) => ConfirmResults::Expired,
+                            Err(error) if error.is_unique_violation() => ConfirmResults::Conflict,
+                            Err(error) => return Err(error),
+                        }
+                    }
+                }
+                _ => ConfirmResults::NotFound,
+            }
+        }
+    } else {
+        ConfirmResults::NotFound
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{configuration::EMAIL_CHANGE_TIME_PERIOD, error::Error, utilities::test::fake};
+    use std::{ops::Sub, time::{Duration, SystemTime}};
+    use actix_rt;
+
+    #[actix_rt::test]
+    async fn returns_not_found_when_no_change_matches_the_id() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(None);
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::NotFound);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_change_has_expired() {
+        let confirmation = fake::email_change_confirmation();
+        let mut change = fake::pending_email_change(&confirmation);
+        let mut state = fake::service_state();
+        change.created_at = SystemTime::now().sub(Duration::from_secs(EMAIL_CHANGE_TIME_PERIOD + 1));
+        state.email_changes.by_id.returns(Some(change));
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::Expired);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_change_has_been_reverted() {
+        let confirmation = fake::email_change_confirmation();
+        let mut change = fake::pending_email_change(&confirmation);
+        let mut state = fake::service_state();
+        change.reverted_at = Some(SystemTime::now());
+        state.email_changes.by_id.returns(Some(change));
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::Expired);
+    }
+
+    #[actix_rt::test]
+    async fn returns_invalid_when_the_token_does_not_match() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::email_change()));
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::Invalid);
+    }
+
+    #[actix_rt::test]
+    async fn returns_conflict_when_the_new_email_has_since_been_taken() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::pending_email_change(&confirmation)));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.credentials.by_email.returns(Some(fake::credentials()));
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::Conflict);
+        assert_eq!(state.credentials.update_with_confirmed_email_change.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn updates_the_email_when_the_token_matches() {
+        let confirmation = fake::email_change_confirmation();
+        let change = fake::pending_email_change(&confirmation);
+        let credentials = fake::credentials();
+        let updated = model::Credentials {
+            email: change.new_email.clone(),
+            ..credentials.clone()
+        };
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(change));
+        state.credentials.by_id.returns(Some(credentials));
+        state.credentials.by_email.returns(None);
+        state.credentials.update_with_confirmed_email_change.returns(Some(updated.clone()));
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::Confirmed(updated));
+        assert_eq!(state.credentials.update_with_confirmed_email_change.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_change_is_confirmed_concurrently() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::pending_email_change(&confirmation)));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.credentials.by_email.returns(None);
+        state.credentials.update_with_confirmed_email_change.returns(None);
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::Expired);
+    }
+
+    #[actix_rt::test]
+    async fn returns_conflict_when_the_new_email_is_taken_while_confirming() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::pending_email_change(&confirmation)));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.credentials.by_email.returns(None);
+        state.credentials.update_with_confirmed_email_change
+            .throws_error(Error::DatabaseError(database::Error::UniqueViolation(String::from("testing"))));
+        let result = confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, ConfirmResults::Conflict);
+    }
+}
@@ -0,0 +1,5 @@
+mod confirm;
+mod revert;
+
+pub use confirm::*;
+pub use revert::*;
@@ -0,0 +1,114 @@
+use crate::{configuration::settings::HashSettings, model, repository, Result};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RevertResults {
+    Reverted,
+    Invalid,
+    Expired,
+    NotFound,
+}
+
+pub async fn revert<E: repository::EmailChanges, C: repository::Credentials>(
+    email_changes: &E,
+    credentials: &C,
+    confirmation: &model::EmailChangeConfirmation,
+    settings: &HashSettings,
+) -> Result<RevertResults> {
+    Ok(if let Some(change) = email_changes.by_id(&confirmation.id).await? {
+        if change.reverted() || change.revert_expired()? {
+            RevertResults::Expired
+        } else if !change.matches_revert_token(settings, &confirmation.token)? {
+            RevertResults::Invalid
+        } else if email_changes.revert(&change.id).await? {
+            if change.confirmed() {
+                if let Some(stored_credentials) = credentials
+                    .by_id(change.user_id)
+                    .await?
+                    .filter(|stored| stored.email == change.new_email)
+                {
+                    credentials
+                        .update_credentials(&model::Credentials {
+                            email: change.old_email,
+                            ..stored_credentials
+                        })
+                        .await?;
+                }
+            }
+            RevertResults::Reverted
+        } else {
+            RevertResults::Expired
+        }
+    } else {
+        RevertResults::NotFound
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{configuration::EMAIL_CHANGE_REVERT_TIME_PERIOD, utilities::test::fake};
+    use std::{ops::Sub, time::{Duration, SystemTime}};
+    use actix_rt;
+
+    #[actix_rt::test]
+    async fn returns_not_found_when_no_change_matches_the_id() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(None);
+        let result = revert(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, RevertResults::NotFound);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_revert_period_has_passed() {
+        let confirmation = fake::email_change_confirmation();
+        let mut change = fake::revertible_email_change(&confirmation);
+        let mut state = fake::service_state();
+        change.created_at = SystemTime::now().sub(Duration::from_secs(EMAIL_CHANGE_REVERT_TIME_PERIOD + 1));
+        state.email_changes.by_id.returns(Some(change));
+        let result = revert(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, RevertResults::Expired);
+    }
+
+    #[actix_rt::test]
+    async fn returns_invalid_when_the_token_does_not_match() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::email_change()));
+        let result = revert(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, RevertResults::Invalid);
+    }
+
+    #[actix_rt::test]
+    async fn cancels_a_pending_change_without_touching_credentials() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::revertible_email_change(&confirmation)));
+        state.email_changes.revert.returns(true);
+        let result = revert(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, RevertResults::Reverted);
+        assert_eq!(state.credentials.update_credentials.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn restores_the_old_email_when_the_change_was_confirmed() {
+        let confirmation = fake::email_change_confirmation();
+        let mut change = fake::revertible_email_change(&confirmation);
+        let mut credentials = fake::credentials();
+        let mut state = fake::service_state();
+        change.confirmed_at = Some(SystemTime::now());
+        credentials.email = change.new_email.clone();
+        state.email_changes.by_id.returns(Some(change));
+        state.email_changes.revert.returns(true);
+        state.credentials.by_id.returns(Some(credentials.clone()));
+        state.credentials.update_credentials.returns(credentials);
+        let result = revert(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash)
+            .await.unwrap();
+        assert_eq!(result, RevertResults::Reverted);
+        assert_eq!(state.credentials.update_credentials.times_called(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{configuration::settings::JwtSettings, model, repository, utilities::jwt, Result};
+use serde_json::json;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ImpersonationResult {
+    NotFound,
+    Issued(model::ImpersonationResponse),
+}
+
+/// Issues a short lived token for the target user naming the admin as its
+/// actor. The issuance is recorded before the token is handed out.
+pub async fn impersonate<C: repository::Credentials, A: repository::AuditLog>(
+    credentials: &C,
+    audit_log: &A,
+    request: &model::ImpersonationRequest,
+    actor: model::CredentialId,
+    lifetime: usize,
+    settings: &JwtSettings,
+) -> Result<ImpersonationResult> {
+    let target = match credentials.by_id(request.user_id).await? {
+        Some(target) if target.deleted_at.is_none() => target,
+        _ => return Ok(ImpersonationResult::NotFound),
+    };
+    let token = jwt::generate_impersonation_token(settings, target, actor, lifetime)?;
+    let session = jwt::verify_token(settings, &token)?;
+    audit_log
+        .record(
+            model::AuditEventType::ImpersonationIssued,
+            Some(session.id),
+            json!({ "actor": actor, "expires_at": session.expires_at }),
+        )
+        .await?;
+    Ok(ImpersonationResult::Issued(model::ImpersonationResponse {
+        user_id: session.id,
+        actor,
+        expires_at: session.expires_at,
+        token,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, utilities::test::fake};
+    use actix_rt;
+    use std::time::SystemTime;
+
+    const ADMIN_ID: model::CredentialId = 42;
+    const LIFETIME: usize = 60;
+
+    fn request() -> model::ImpersonationRequest {
+        model::ImpersonationRequest { user_id: fake::numeric_id() }
+    }
+
+    #[actix_rt::test]
+    async fn issues_a_token_carrying_the_actor() {
+        let mut state = fake::service_state();
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.audit_log.record.returns(());
+        let result = impersonate(
+            &state.credentials,
+            &state.audit_log,
+            &request(),
+            ADMIN_ID,
+            LIFETIME,
+            &state.settings.jwt,
+        )
+        .await
+        .unwrap();
+        match result {
+            ImpersonationResult::Issued(response) => {
+                let session = jwt::verify_token(&fake::settings().jwt, &response.token).unwrap();
+                assert_eq!(session.id, fake::numeric_id());
+                assert_eq!(session.actor, Some(ADMIN_ID));
+                assert_eq!(response.expires_at, session.expires_at);
+            }
+            _ => panic!("expected a token to be issued"),
+        }
+        assert_eq!(state.audit_log.record.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn does_not_issue_tokens_for_missing_or_deleted_users() {
+        let mut deleted = fake::credentials();
+        deleted.deleted_at = Some(SystemTime::now());
+        let mut state = fake::service_state();
+        state.credentials.by_id.returns(None).returns(Some(deleted));
+        for _ in 0..2 {
+            let result = impersonate(
+                &state.credentials,
+                &state.audit_log,
+                &request(),
+                ADMIN_ID,
+                LIFETIME,
+                &state.settings.jwt,
+            )
+            .await
+            .unwrap();
+            assert_eq!(result, ImpersonationResult::NotFound);
+        }
+        assert_eq!(state.audit_log.record.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn does_not_issue_a_token_that_was_not_recorded() {
+        let mut state = fake::service_state();
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.audit_log.record.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = impersonate(
+            &state.credentials,
+            &state.audit_log,
+            &request(),
+            ADMIN_ID,
+            LIFETIME,
+            &state.settings.jwt,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    configuration::settings::LinkSettings,
+    constants::ONE_DAY,
+    mail,
+    mail::templates,
+    model,
+    repository,
+    Result,
+};
+use std::time::Duration;
+
+const MAXIMUM_INVITATION_LIFETIME: u64 = ONE_DAY * 90;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InviteResult {
+    InvalidEmail,
+    InvalidRole,
+    Created(model::Invitation),
+}
+
+pub async fn invite<I: repository::Invitations, M: mail::Mailer>(
+    invitations: &I,
+    mailer: &M,
+    request: &model::InvitationRequest,
+    created_by: model::CredentialId,
+    default_lifetime: u64,
+    links: &LinkSettings,
+) -> Result<InviteResult> {
+    if !request.email.contains('@') {
+        return Ok(InviteResult::InvalidEmail);
+    }
+    if let Some(role) = &request.role {
+        if !model::valid_role(role) {
+            return Ok(InviteResult::InvalidRole);
+        }
+    }
+    let lifetime = request
+        .expires_in
+        .unwrap_or(default_lifetime)
+        .max(1)
+        .min(MAXIMUM_INVITATION_LIFETIME);
+    let invitation = invitations
+        .create(request, created_by, Duration::from_secs(lifetime))
+        .await?;
+    mailer
+        .send(&templates::invitation(links, &invitation.email, &invitation.token))
+        .await?;
+    Ok(InviteResult::Created(invitation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use actix_rt;
+
+    fn request() -> model::InvitationRequest {
+        model::InvitationRequest {
+            email: fake::email_address(),
+            role: Some(String::from("council_member")),
+            expires_in: None,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn creates_and_sends_an_invitation() {
+        let mut state = fake::service_state();
+        let invitation = fake::invitation();
+        state.invitations.create.returns(invitation.clone());
+        state.mailer.send.returns(());
+        let result = invite(&state.invitations, &state.mailer, &request(), 1, 60, &state.settings.links).await.unwrap();
+        assert_eq!(result, InviteResult::Created(invitation));
+        assert_eq!(state.mailer.send.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_an_invalid_role() {
+        let state = fake::service_state();
+        let mut request = request();
+        request.role = Some(String::from("Mayor of Everything"));
+        let result = invite(&state.invitations, &state.mailer, &request, 1, 60, &state.settings.links).await.unwrap();
+        assert_eq!(result, InviteResult::InvalidRole);
+        assert_eq!(state.invitations.create.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_an_invalid_email() {
+        let state = fake::service_state();
+        let mut request = request();
+        request.email = fake::user_name();
+        let result = invite(&state.invitations, &state.mailer, &request, 1, 60, &state.settings.links).await.unwrap();
+        assert_eq!(result, InviteResult::InvalidEmail);
+    }
+}
@@ -0,0 +1,5 @@
+mod request;
+mod sign_in;
+
+pub use request::*;
+pub use sign_in::*;
@@ -0,0 +1,56 @@
+use crate::{configuration::settings::LinkSettings, mail, mail::templates, repository, Result};
+use logging::warn;
+
+/// A failed delivery is logged rather than returned, so callers answer the
+/// same way whether or not the email belongs to an account.
+pub async fn request_magic_link<K: repository::MagicLinks, M: mail::Mailer>(
+    magic_links: &K,
+    mailer: &M,
+    email: &str,
+    links: &LinkSettings,
+) -> Result<()> {
+    if let Some(link) = magic_links.generate(email).await? {
+        if let Err(error) = mailer.send(&templates::magic_link(links, &link.email, &link.id, &link.token)).await {
+            warn!(error = %error, "Failed to send a magic link");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utilities::test::fake, error::Error};
+    use actix_rt;
+
+    #[actix_rt::test]
+    async fn sends_a_magic_link_when_a_link_is_generated() {
+        let mut state = fake::service_state();
+        let email = fake::email_address();
+        state.magic_links.generate.returns(Some(fake::magic_link()));
+        state.mailer.send.returns(());
+        request_magic_link(&state.magic_links, &state.mailer, &email, &state.settings.links)
+            .await.unwrap();
+        assert_eq!(state.mailer.send.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn does_not_send_mail_if_no_matching_user_was_found() {
+        let mut state = fake::service_state();
+        let email = fake::email_address();
+        state.magic_links.generate.returns(None);
+        request_magic_link(&state.magic_links, &state.mailer, &email, &state.settings.links)
+            .await.unwrap();
+        assert_eq!(state.mailer.send.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn succeeds_even_if_mail_cannot_be_sent() {
+        let mut state = fake::service_state();
+        let email = fake::email_address();
+        state.magic_links.generate.returns(Some(fake::magic_link()));
+        state.mailer.send.throws_error(Error::InternalServerError(String::from("testing123")));
+        assert!(request_magic_link(&state.magic_links, &state.mailer, &email, &state.settings.links).await.is_ok());
+        assert_eq!(state.mailer.send.times_called(), 1);
+    }
+}
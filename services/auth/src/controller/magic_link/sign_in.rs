@@ -0,0 +1,189 @@
+use crate::{configuration::settings::HashSettings, model, repository, Result};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SignInResults {
+    Valid(model::Credentials),
+    Suspended,
+    Invalid,
+    Expired,
+    NotFound,
+}
+
+pub async fn sign_in<
+    K: repository::MagicLinks,
+    C: repository::Credentials,
+    L: repository::LoginHistory,
+>(
+    magic_links: &K,
+    credentials: &C,
+    login_history: &L,
+    confirmation: &model::MagicLinkConfirmation,
+    settings: &HashSettings,
+) -> Result<SignInResults> {
+    Ok(if let Some(link) = magic_links.by_id(&confirmation.id).await? {
+        if link.used() || link.expired()? {
+            SignInResults::Expired
+        } else {
+            match credentials.by_id(link.user_id).await? {
+                Some(stored_credentials) if stored_credentials.deleted_at.is_none() => {
+                    if stored_credentials.suspended()? {
+                        SignInResults::Suspended
+                    } else if !link.matches_token(settings, &confirmation.token)? {
+                        login_history.suspend(&stored_credentials.id).await?;
+                        SignInResults::Invalid
+                    } else if magic_links.consume(&link.id).await? {
+                        SignInResults::Valid(stored_credentials)
+                    } else {
+                        SignInResults::Expired
+                    }
+                }
+                _ => SignInResults::NotFound,
+            }
+        }
+    } else {
+        SignInResults::NotFound
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{configuration::MAGIC_LINK_TIME_PERIOD, utilities::test::fake};
+    use std::{ops::Sub, time::{Duration, SystemTime}};
+    use actix_rt;
+
+    #[actix_rt::test]
+    async fn returns_not_found_when_no_link_matches_the_id() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut state = fake::service_state();
+        state.magic_links.by_id.returns(None);
+        let result = sign_in(
+            &state.magic_links,
+            &state.credentials,
+            &state.login_history,
+            &confirmation,
+            &state.settings.hash,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, SignInResults::NotFound);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_link_has_expired() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut link = fake::valid_magic_link(&confirmation);
+        let mut state = fake::service_state();
+        link.created_at = SystemTime::now().sub(Duration::from_secs(MAGIC_LINK_TIME_PERIOD + 1));
+        state.magic_links.by_id.returns(Some(link));
+        let result = sign_in(
+            &state.magic_links,
+            &state.credentials,
+            &state.login_history,
+            &confirmation,
+            &state.settings.hash,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, SignInResults::Expired);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_link_has_already_been_used() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut link = fake::valid_magic_link(&confirmation);
+        let mut state = fake::service_state();
+        link.used_at = Some(SystemTime::now());
+        state.magic_links.by_id.returns(Some(link));
+        let result = sign_in(
+            &state.magic_links,
+            &state.credentials,
+            &state.login_history,
+            &confirmation,
+            &state.settings.hash,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, SignInResults::Expired);
+    }
+
+    #[actix_rt::test]
+    async fn returns_suspended_when_the_account_is_suspended() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut credentials = fake::credentials();
+        let mut state = fake::service_state();
+        credentials.locked_at = Some(SystemTime::now());
+        state.magic_links.by_id.returns(Some(fake::valid_magic_link(&confirmation)));
+        state.credentials.by_id.returns(Some(credentials));
+        let result = sign_in(
+            &state.magic_links,
+            &state.credentials,
+            &state.login_history,
+            &confirmation,
+            &state.settings.hash,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, SignInResults::Suspended);
+    }
+
+    #[actix_rt::test]
+    async fn returns_invalid_and_logs_a_failure_when_the_token_does_not_match() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut state = fake::service_state();
+        state.magic_links.by_id.returns(Some(fake::magic_link()));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.login_history.suspend.returns(());
+        let result = sign_in(
+            &state.magic_links,
+            &state.credentials,
+            &state.login_history,
+            &confirmation,
+            &state.settings.hash,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, SignInResults::Invalid);
+        assert_eq!(state.login_history.suspend.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn returns_valid_credentials_and_consumes_the_link_when_the_token_matches() {
+        let confirmation = fake::magic_link_confirmation();
+        let credentials = fake::credentials();
+        let mut state = fake::service_state();
+        state.magic_links.by_id.returns(Some(fake::valid_magic_link(&confirmation)));
+        state.credentials.by_id.returns(Some(credentials.clone()));
+        state.magic_links.consume.returns(true);
+        let result = sign_in(
+            &state.magic_links,
+            &state.credentials,
+            &state.login_history,
+            &confirmation,
+            &state.settings.hash,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, SignInResults::Valid(credentials));
+        assert_eq!(state.magic_links.consume.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn returns_expired_when_the_link_was_consumed_concurrently() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut state = fake::service_state();
+        state.magic_links.by_id.returns(Some(fake::valid_magic_link(&confirmation)));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.magic_links.consume.returns(false);
+        let result = sign_in(
+            &state.magic_links,
+            &state.credentials,
+            &state.login_history,
+            &confirmation,
+            &state.settings.hash,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, SignInResults::Expired);
+    }
+}
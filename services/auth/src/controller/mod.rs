@@ -1,3 +1,11 @@
 pub mod authorization;
+pub mod challenge;
 pub mod credentials;
+pub mod device;
+pub mod email_change;
+pub mod impersonation;
+pub mod invitation;
+pub mod magic_link;
 pub mod password_reset;
+pub mod personal_access_token;
+pub mod service_client;
@@ -1,11 +1,12 @@
-use crate::{repository, Result, model, utilities::hash};
+use crate::{configuration::settings::HashSettings, repository, Result, model, utilities::hash};
 
 pub async fn request_password_reset<R: repository::PasswordResetRequest>(
     reset_request: &R,
     email: &str,
+    settings: &HashSettings,
 ) -> Result<model::ResetToken> {
     reset_request.generate(email).await?
-        .map_or_else(|| hash::generate(hash::token().as_ref())
+        .map_or_else(|| hash::generate(settings, hash::token().as_ref())
         .map_or_else(| error | Err(error), | hashed | Ok(model::ResetToken::new(
             hash::token().as_ref(),
             &hashed,
@@ -29,7 +30,7 @@ mod tests {
         let reset_request = fake::password_reset_request();
         let reset_token = model::ResetToken::new(&reset_request.id, &reset_request.reset_token);
         state.reset_request.generate.returns(Some(reset_request));
-        let result = request_password_reset(&state.reset_request, email)
+        let result = request_password_reset(&state.reset_request, email, &state.settings.hash)
             .await.unwrap();
         assert_eq!(result, reset_token);
     }
@@ -41,7 +42,7 @@ mod tests {
         let email = "test@testing.com";
         let reset_token = model::ResetToken::new(&request.id, &request.reset_token);
         state.reset_request.generate.returns(None);
-        let result = request_password_reset(&state.reset_request, email)
+        let result = request_password_reset(&state.reset_request, email, &state.settings.hash)
             .await.unwrap();
         assert_eq!(reset_token.type_id(), result.type_id());
     }
@@ -52,7 +53,7 @@ mod tests {
         let email = "test@testing.com";
         let reset_error = error::Error::InternalServerError(String::from("testing123"));
         state.reset_request.generate.throws_error(reset_error.clone());
-        let result = request_password_reset(&state.reset_request, email)
+        let result = request_password_reset(&state.reset_request, email, &state.settings.hash)
             .await.err().unwrap();
         assert_eq!(result.to_string(), reset_error.clone().to_string());
     }
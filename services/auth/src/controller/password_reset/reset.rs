@@ -1,4 +1,4 @@
-use crate::{repository, Result, utilities::{hash, password}, model};
+use crate::{configuration::settings::HashSettings, repository, Result, utilities::{hash, password}, model};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ResetResult {
@@ -13,6 +13,7 @@ pub async fn reset_password<R: repository::PasswordResetRequest, C: repository::
     reset_request: &R,
     credentials: &C,
     data: &model::ResetConfirmation,
+    settings: &HashSettings,
 ) -> Result<ResetResult> {
     Ok(if let Some(request) = reset_request.by_id(&data.id).await? {
         if request.expired()? {
@@ -21,8 +22,8 @@ pub async fn reset_password<R: repository::PasswordResetRequest, C: repository::
             if let password::Strength::Weak(problems) = password::strength(&request.name, &request.email, &data.password)? {
                 ResetResult::WeakPassword(problems)
             } else {
-                if hash::authenticate(&data.reset_token, &request.reset_token)? {
-                    let hashed_password = hash::generate(&data.password)?;
+                if hash::authenticate(settings, &data.reset_token, &request.reset_token)? {
+                    let hashed_password = hash::generate(settings, &data.password)?;
                     ResetResult::Success(credentials.update_password_hash(&request.user_id, &hashed_password).await?)
                 } else {
                     ResetResult::InvalidToken
@@ -46,7 +47,7 @@ mod tests {
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
         state.reset_request.by_id.returns(Some(reset_record));
-        let result = reset_password(&state.reset_request, &state.credentials, &request)
+        let result = reset_password(&state.reset_request, &state.credentials, &request, &state.settings.hash)
             .await.unwrap();
         assert_eq!(result, ResetResult::InvalidToken);
     }
@@ -56,7 +57,7 @@ mod tests {
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
         state.reset_request.by_id.returns(None);
-        let result = reset_password(&state.reset_request, &state.credentials, &request)
+        let result = reset_password(&state.reset_request, &state.credentials, &request, &state.settings.hash)
             .await.unwrap();
         assert_eq!(result, ResetResult::NotFound);
     }
@@ -67,10 +68,10 @@ mod tests {
         let mut reset_record = fake::password_reset_request();
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
-        reset_record.reset_token = hash::generate(&request.reset_token).unwrap();
+        reset_record.reset_token = hash::generate(&fake::settings().hash, &request.reset_token).unwrap();
         state.reset_request.by_id.returns(Some(reset_record));
         state.credentials.update_password_hash.returns(credentials.clone());
-        let result = reset_password(&state.reset_request, &state.credentials, &request)
+        let result = reset_password(&state.reset_request, &state.credentials, &request, &state.settings.hash)
             .await.unwrap();
         assert_eq!(result, ResetResult::Success(credentials.clone()));
     }
@@ -81,10 +82,10 @@ mod tests {
         let mut reset_record = fake::password_reset_request();
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
-        reset_record.reset_token = hash::generate(&request.reset_token).unwrap();
+        reset_record.reset_token = hash::generate(&fake::settings().hash, &request.reset_token).unwrap();
         state.reset_request.by_id.returns(Some(reset_record));
         state.credentials.update_password_hash.returns(credentials.clone());
-        let result = reset_password(&state.reset_request, &state.credentials, &request)
+        let result = reset_password(&state.reset_request, &state.credentials, &request, &state.settings.hash)
             .await.unwrap();
         if let ResetResult::Success(reset_record) = result.clone() {
             assert_eq!(reset_record, credentials.clone());
@@ -99,7 +100,7 @@ mod tests {
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
         state.reset_request.by_id.throws_error(error.clone());
-        let result = reset_password(&state.reset_request, &state.credentials, &request)
+        let result = reset_password(&state.reset_request, &state.credentials, &request, &state.settings.hash)
             .await.err().unwrap();
         assert_eq!(result.to_string(), error.clone().to_string());
     }
@@ -111,10 +112,10 @@ mod tests {
         let mut request = fake::password_reset_data();
         let mut state = fake::service_state();
         request.password = fake::weak_password();
-        reset_record.reset_token = hash::generate(&request.reset_token).unwrap();
+        reset_record.reset_token = hash::generate(&fake::settings().hash, &request.reset_token).unwrap();
         state.reset_request.by_id.returns(Some(reset_record));
         state.credentials.update_password_hash.returns(credentials.clone());
-        let result = reset_password(&state.reset_request, &state.credentials, &request)
+        let result = reset_password(&state.reset_request, &state.credentials, &request, &state.settings.hash)
             .await.unwrap();
         match result {
             ResetResult::WeakPassword(_) => assert!(true),
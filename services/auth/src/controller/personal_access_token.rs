@@ -0,0 +1,192 @@
+use crate::{
+    configuration::settings::HashSettings,
+    model,
+    model::{
+        personal_access_token::{self, MAXIMUM_TOKEN_LIFETIME, MAXIMUM_TOKEN_NAME_LENGTH},
+        service_client::valid_scope,
+    },
+    repository, Result,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CreationResult {
+    InvalidName,
+    InvalidScope(String),
+    InvalidExpiry,
+    Created(model::PersonalAccessToken),
+}
+
+fn seconds(time: SystemTime) -> usize {
+    time.duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs() as usize)
+}
+
+pub async fn create<P: repository::PersonalAccessTokens>(
+    tokens: &P,
+    user_id: model::CredentialId,
+    request: &model::PersonalAccessTokenRequest,
+) -> Result<CreationResult> {
+    let name = request.name.trim();
+    if name.is_empty() || name.len() > MAXIMUM_TOKEN_NAME_LENGTH {
+        return Ok(CreationResult::InvalidName);
+    }
+    if let Some(scope) = request.scopes.iter().find(|scope| !valid_scope(scope)) {
+        return Ok(CreationResult::InvalidScope(scope.clone()));
+    }
+    let expires_at = match request.expires_in {
+        None => None,
+        Some(expires_in) if expires_in == 0 || expires_in > MAXIMUM_TOKEN_LIFETIME => {
+            return Ok(CreationResult::InvalidExpiry)
+        }
+        Some(expires_in) => match SystemTime::now().checked_add(Duration::from_secs(expires_in)) {
+            Some(expires_at) => Some(expires_at),
+            None => return Ok(CreationResult::InvalidExpiry),
+        },
+    };
+    let request = model::PersonalAccessTokenRequest {
+        name: String::from(name),
+        ..request.clone()
+    };
+    Ok(CreationResult::Created(tokens.create(user_id, &request, expires_at).await?))
+}
+
+/// The session a personal access token stands for. Tokens without an expiry
+/// report one session lifetime from now, so callers caching the result
+/// verify it again.
+pub async fn verify<P: repository::PersonalAccessTokens, C: repository::Credentials, D: repository::Devices>(
+    tokens: &P,
+    credentials: &C,
+    devices: &D,
+    token: &str,
+    settings: &HashSettings,
+    lifetime: usize,
+) -> Result<Option<model::Session>> {
+    let (id, secret) = match personal_access_token::parse(token) {
+        Some(parts) => parts,
+        None => return Ok(None),
+    };
+    let stored = match tokens.by_id(id).await? {
+        Some(stored) if !stored.revoked() && !stored.expired() && stored.matches_secret(settings, secret)? => stored,
+        _ => return Ok(None),
+    };
+    let user = match credentials.by_id(stored.user_id).await? {
+        Some(user) if user.deleted_at.is_none() && !user.suspended()? => user,
+        _ => return Ok(None),
+    };
+    let issued_at = seconds(stored.created_at);
+    if let Some(revocation) = devices.revocation(user.id).await? {
+        if revocation.revokes(issued_at) {
+            return Ok(None);
+        }
+    }
+    tokens.used(&stored.id).await?;
+    let session_expiry = seconds(SystemTime::now()) + lifetime;
+    Ok(Some(model::Session {
+        id: user.id,
+        name: user.name,
+        email: user.email,
+        issued_at,
+        expires_at: stored.expires_at.map_or(session_expiry, |expires_at| seconds(expires_at).min(session_expiry)),
+        actor: None,
+        scopes: Some(stored.scopes),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::{hash, test::fake};
+    use actix_rt;
+    use std::ops::Sub;
+
+    const LIFETIME: usize = 60;
+    const SECRET: &str = "personal_access_token_secret_32c";
+
+    fn stored() -> model::PersonalAccessToken {
+        model::PersonalAccessToken {
+            token: hash::generate(&fake::settings().hash, SECRET).unwrap(),
+            ..fake::personal_access_token()
+        }
+    }
+
+    fn presented(stored: &model::PersonalAccessToken) -> String {
+        personal_access_token::encode(&stored.id, SECRET)
+    }
+
+    async fn verifying(state: &fake::MockServiceState, token: &str) -> Option<model::Session> {
+        let hash = &state.settings.hash;
+        verify(&state.personal_access_tokens, &state.credentials, &state.devices, token, hash, LIFETIME)
+            .await
+            .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn validates_the_name_scopes_and_expiry() {
+        let state = fake::service_state();
+        let request = fake::personal_access_token_request();
+        let invalid_name = model::PersonalAccessTokenRequest { name: String::new(), ..request.clone() };
+        assert_eq!(
+            create(&state.personal_access_tokens, 1, &invalid_name).await.unwrap(),
+            CreationResult::InvalidName
+        );
+        let invalid_scope = model::PersonalAccessTokenRequest { scopes: vec![String::from("All")], ..request.clone() };
+        assert_eq!(
+            create(&state.personal_access_tokens, 1, &invalid_scope).await.unwrap(),
+            CreationResult::InvalidScope(String::from("All"))
+        );
+        for expires_in in [0, MAXIMUM_TOKEN_LIFETIME + 1, u64::MAX].iter() {
+            let invalid_expiry = model::PersonalAccessTokenRequest { expires_in: Some(*expires_in), ..request.clone() };
+            assert_eq!(
+                create(&state.personal_access_tokens, 1, &invalid_expiry).await.unwrap(),
+                CreationResult::InvalidExpiry
+            );
+        }
+        assert_eq!(state.personal_access_tokens.create.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn verifies_tokens_as_a_scoped_session() {
+        let mut state = fake::service_state();
+        let stored = stored();
+        state.personal_access_tokens.by_id.returns(Some(stored.clone()));
+        state.personal_access_tokens.used.returns(());
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.devices.revocation.returns(None);
+        let session = verifying(&state, &presented(&stored)).await.unwrap();
+        assert_eq!(session.id, fake::credentials().id);
+        assert_eq!(session.scopes, Some(stored.scopes));
+        assert!(session.expires_at <= seconds(SystemTime::now()) + LIFETIME);
+        assert_eq!(state.personal_access_tokens.used.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_revoked_expired_and_mismatched_tokens() {
+        let mut state = fake::service_state();
+        let stored = stored();
+        let revoked = model::PersonalAccessToken { revoked_at: Some(SystemTime::now()), ..stored.clone() };
+        let expired = model::PersonalAccessToken {
+            expires_at: Some(SystemTime::now().sub(Duration::from_secs(1))),
+            ..stored.clone()
+        };
+        state.personal_access_tokens.by_id.returns(Some(revoked)).returns(Some(expired)).returns(Some(stored.clone()));
+        for token in [presented(&stored), presented(&stored), personal_access_token::encode(&stored.id, "wrong")].iter() {
+            let session = verifying(&state, token).await;
+            assert_eq!(session, None);
+        }
+        let session = verifying(&state, "not a token").await;
+        assert_eq!(session, None);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_tokens_created_before_sessions_were_revoked() {
+        let mut state = fake::service_state();
+        let stored = stored();
+        state.personal_access_tokens.by_id.returns(Some(stored.clone()));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.devices.revocation.returns(Some(model::SessionRevocation {
+            revoked_at: SystemTime::now() + Duration::from_secs(1),
+        }));
+        let session = verifying(&state, &presented(&stored)).await;
+        assert_eq!(session, None);
+    }
+}
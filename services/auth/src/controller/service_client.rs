@@ -0,0 +1,191 @@
+use crate::{
+    configuration::Settings,
+    model,
+    model::service_client::{valid_audience, valid_scope, MAXIMUM_CLIENT_NAME_LENGTH},
+    repository,
+    utilities::jwt,
+    Result,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RegistrationResult {
+    InvalidName,
+    InvalidScope(String),
+    InvalidAudience(String),
+    Created(model::ServiceClient),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TokenResult {
+    UnsupportedGrantType,
+    InvalidClient,
+    InvalidScope,
+    Issued(model::TokenResponse),
+}
+
+pub async fn register<S: repository::ServiceClients>(
+    clients: &S,
+    request: &model::ServiceClientRequest,
+    created_by: model::CredentialId,
+) -> Result<RegistrationResult> {
+    let name = request.name.trim();
+    if name.is_empty() || name.len() > MAXIMUM_CLIENT_NAME_LENGTH {
+        return Ok(RegistrationResult::InvalidName);
+    }
+    if let Some(scope) = request.scopes.iter().find(|scope| !valid_scope(scope)) {
+        return Ok(RegistrationResult::InvalidScope(scope.clone()));
+    }
+    if let Some(audience) = request.audiences.iter().find(|audience| !valid_audience(audience)) {
+        return Ok(RegistrationResult::InvalidAudience(audience.clone()));
+    }
+    let request = model::ServiceClientRequest {
+        name: String::from(name),
+        ..request.clone()
+    };
+    Ok(RegistrationResult::Created(clients.create(&request, created_by).await?))
+}
+
+/// The client credentials grant (RFC 6749 section 4.4): the client
+/// authenticates with its own secret and receives a token for itself.
+pub async fn issue_token<S: repository::ServiceClients>(
+    clients: &S,
+    request: &model::TokenRequest,
+    settings: &Settings,
+) -> Result<TokenResult> {
+    if request.grant_type != model::CLIENT_CREDENTIALS_GRANT {
+        return Ok(TokenResult::UnsupportedGrantType);
+    }
+    let client = match clients.by_client_id(&request.client_id).await? {
+        Some(client) if client.authenticate(&settings.hash, &request.client_secret)? => client,
+        _ => return Ok(TokenResult::InvalidClient),
+    };
+    let scopes = match client.grant(request.scope.as_deref()) {
+        Some(scopes) => scopes,
+        None => return Ok(TokenResult::InvalidScope),
+    };
+    let lifetime = settings.service_clients.token_lifetime;
+    Ok(TokenResult::Issued(model::TokenResponse {
+        access_token: jwt::generate_service_token(
+            &settings.jwt,
+            &client.client_id,
+            &scopes,
+            &client.audiences,
+            lifetime,
+        )?,
+        token_type: String::from(model::BEARER_TOKEN_TYPE),
+        expires_in: lifetime,
+        scope: scopes.join(" "),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::{hash, test::fake};
+    use actix_rt;
+
+    const ADMIN_ID: model::CredentialId = 42;
+    const SECRET: &str = "secret";
+
+    fn client() -> model::ServiceClient {
+        model::ServiceClient {
+            secret: hash::generate(&fake::settings().hash, SECRET).unwrap(),
+            scopes: vec![String::from("bills:write")],
+            ..fake::service_client()
+        }
+    }
+
+    fn token_request(client: &model::ServiceClient, secret: &str, scope: Option<&str>) -> model::TokenRequest {
+        model::TokenRequest {
+            grant_type: String::from(model::CLIENT_CREDENTIALS_GRANT),
+            client_id: client.client_id.clone(),
+            client_secret: String::from(secret),
+            scope: scope.map(String::from),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn registers_clients_with_valid_names_and_scopes() {
+        let mut state = fake::service_state();
+        let created = fake::service_client();
+        state.service_clients.create.returns(created.clone());
+        let request = fake::service_client_request();
+        let result = register(&state.service_clients, &request, ADMIN_ID).await.unwrap();
+        assert_eq!(result, RegistrationResult::Created(created));
+        let invalid_name = model::ServiceClientRequest { name: String::from(" "), ..request.clone() };
+        let result = register(&state.service_clients, &invalid_name, ADMIN_ID).await.unwrap();
+        assert_eq!(result, RegistrationResult::InvalidName);
+        let invalid_scope = model::ServiceClientRequest { scopes: vec![String::from("Bills")], ..request.clone() };
+        let result = register(&state.service_clients, &invalid_scope, ADMIN_ID).await.unwrap();
+        assert_eq!(result, RegistrationResult::InvalidScope(String::from("Bills")));
+        let invalid_audience = model::ServiceClientRequest { audiences: vec![String::new()], ..request };
+        let result = register(&state.service_clients, &invalid_audience, ADMIN_ID).await.unwrap();
+        assert_eq!(result, RegistrationResult::InvalidAudience(String::new()));
+        assert_eq!(state.service_clients.create.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn issues_service_tokens_for_the_granted_scopes() {
+        let mut state = fake::service_state();
+        let client = client();
+        state.service_clients.by_client_id.returns(Some(client.clone()));
+        let result = issue_token(&state.service_clients, &token_request(&client, SECRET, None), state.settings)
+            .await
+            .unwrap();
+        match result {
+            TokenResult::Issued(response) => {
+                let session = jwt::verify_service_token(&fake::settings().jwt, &response.access_token).unwrap();
+                assert_eq!(session.client_id, client.client_id);
+                assert_eq!(session.scopes, client.scopes);
+                assert_eq!(response.token_type, model::BEARER_TOKEN_TYPE);
+                assert_eq!(response.expires_in, state.settings.service_clients.token_lifetime);
+            }
+            _ => panic!("expected a token to be issued"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn issues_service_tokens_for_the_audiences_of_the_client() {
+        let mut state = fake::service_state();
+        let client = model::ServiceClient { audiences: vec![String::from("https://bills.example")], ..client() };
+        state.service_clients.by_client_id.returns(Some(client.clone()));
+        let result = issue_token(&state.service_clients, &token_request(&client, SECRET, None), state.settings)
+            .await
+            .unwrap();
+        match result {
+            TokenResult::Issued(response) => {
+                let session = jwt::verify_service_token(&fake::settings().jwt, &response.access_token).unwrap();
+                assert_eq!(session.audiences, client.audiences);
+            }
+            _ => panic!("expected a token to be issued"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn rejects_unknown_clients_and_wrong_secrets() {
+        let mut state = fake::service_state();
+        let client = client();
+        state.service_clients.by_client_id.returns(None).returns(Some(client.clone()));
+        for _ in 0..2 {
+            let request = token_request(&client, "wrong", None);
+            let result = issue_token(&state.service_clients, &request, state.settings).await.unwrap();
+            assert_eq!(result, TokenResult::InvalidClient);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn rejects_other_grants_and_scopes_the_client_does_not_hold() {
+        let mut state = fake::service_state();
+        let client = client();
+        state.service_clients.by_client_id.returns(Some(client.clone()));
+        let password_grant = model::TokenRequest {
+            grant_type: String::from("password"),
+            ..token_request(&client, SECRET, None)
+        };
+        let result = issue_token(&state.service_clients, &password_grant, state.settings).await.unwrap();
+        assert_eq!(result, TokenResult::UnsupportedGrantType);
+        let request = token_request(&client, SECRET, Some("users:write"));
+        let result = issue_token(&state.service_clients, &request, state.settings).await.unwrap();
+        assert_eq!(result, TokenResult::InvalidScope);
+    }
+}
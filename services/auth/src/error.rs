@@ -10,6 +10,17 @@ pub enum Error {
     BadRequest(String),
 }
 
+impl Error {
+    /// Whether a write was rejected by a unique constraint, such as another
+    /// account taking the same email or name first.
+    pub fn is_unique_violation(&self) -> bool {
+        match self {
+            Error::DatabaseError(error) => error.is_unique_violation(),
+            _ => false,
+        }
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -53,6 +64,12 @@ impl From<ring::error::Unspecified> for Error {
     }
 }
 
+impl From<redis::RedisError> for Error {
+    fn from(error: redis::RedisError) -> Error {
+        Error::InternalServerError(error.to_string())
+    }
+}
+
 impl From<std::str::Utf8Error> for Error {
     fn from(error: std::str::Utf8Error) -> Error {
         Error::InternalServerError(error.to_string())
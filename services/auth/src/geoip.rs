@@ -0,0 +1,71 @@
+use crate::configuration::settings::DeviceSettings;
+use logging::{info, warn};
+use maxminddb::{geoip2, Reader};
+use std::{
+    marker::{Send, Sync},
+    net::IpAddr,
+    sync::Arc,
+};
+
+pub type AppLocator = GeoIpDatabase;
+
+/// Resolves the country an address belongs to. Lookups happen against a
+/// locally loaded database so addresses are never sent to a third party.
+pub trait Locator: Clone + Send + Sync {
+    fn country(&self, address: IpAddr) -> Option<String>;
+}
+
+#[derive(Clone)]
+pub struct GeoIpDatabase {
+    reader: Option<Arc<Reader<Vec<u8>>>>,
+}
+
+impl GeoIpDatabase {
+    pub fn disabled() -> GeoIpDatabase {
+        GeoIpDatabase { reader: None }
+    }
+    pub fn from_settings(settings: &DeviceSettings) -> GeoIpDatabase {
+        match &settings.geoip_database {
+            Some(path) => match Reader::open_readfile(path) {
+                Ok(reader) => {
+                    info!(path = %path, "Loaded GeoIP database");
+                    GeoIpDatabase { reader: Some(Arc::new(reader)) }
+                }
+                Err(error) => {
+                    warn!(path = %path, error = %error, "Failed to load GeoIP database; countries will not be resolved");
+                    GeoIpDatabase::disabled()
+                }
+            },
+            None => GeoIpDatabase::disabled(),
+        }
+    }
+}
+
+impl Locator for GeoIpDatabase {
+    fn country(&self, address: IpAddr) -> Option<String> {
+        let reader = self.reader.as_ref()?;
+        reader
+            .lookup::<geoip2::Country>(address)
+            .ok()?
+            .country?
+            .iso_code
+            .map(String::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_nothing_without_a_database() {
+        let settings = DeviceSettings { notify: true, geoip_database: None };
+        assert_eq!(GeoIpDatabase::from_settings(&settings).country("203.0.113.27".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn falls_back_to_no_lookups_when_the_database_cannot_be_read() {
+        let settings = DeviceSettings { notify: true, geoip_database: Some(String::from("/missing.mmdb")) };
+        assert!(GeoIpDatabase::from_settings(&settings).reader.is_none());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    handler::{error, session},
+    model,
+    repository::AuditLog,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+
+pub async fn events<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    filter: web::Query<model::AuditFilter>,
+) -> HttpResponse {
+    if let Err(denied) = session::admin(&req, &state).await {
+        return denied;
+    }
+    if filter.range().is_none() {
+        return error::respond(model::ErrorCode::InvalidRequest);
+    }
+    match state.audit_log.query(&filter).await {
+        Ok(events) => HttpResponse::Ok().json(
+            events
+                .into_iter()
+                .map(model::AuditRecord::from)
+                .collect::<Vec<model::AuditRecord>>(),
+        ),
+        Err(_) => error::internal_error(),
+    }
+}
+
+pub async fn verify<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+) -> HttpResponse {
+    if let Err(denied) = session::admin(&req, &state).await {
+        return denied;
+    }
+    match state.audit_log.verify().await {
+        Ok(verification) => HttpResponse::Ok().json(verification),
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Error,
+        utilities::{jwt, test, test::fake},
+    };
+    use actix_rt;
+    use actix_web::http;
+    use serde_json::json;
+
+    fn request_from(credentials: model::Credentials) -> HttpRequest {
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, jwt::generate_token(&fake::settings().jwt, credentials).unwrap())
+            .to_http_request()
+    }
+
+    fn admin_state(admin: &model::Credentials) -> fake::MockServiceState {
+        let mut state = fake::service_state_with(|settings| settings.admin.ids = vec![admin.id]);
+        state.devices.revocation.returns(None);
+        state
+    }
+
+    fn event() -> model::AuditEvent {
+        model::AuditEvent::new(None, model::AuditEventType::AccountCreated, Some(1), json!({})).unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn requires_a_session() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let result = events(req, web::Data::new(fake::service_state()), web::Query(model::AuditFilter::default())).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn forbids_users_who_are_not_admins() {
+        let req = request_from(fake::credentials());
+        let result = verify(req, web::Data::new(fake::service_state())).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::Forbidden);
+    }
+
+    #[actix_rt::test]
+    async fn returns_events_to_admins() {
+        let admin = fake::credentials();
+        let mut state = admin_state(&admin);
+        state.audit_log.query.returns(vec![event()]);
+        let result = events(request_from(admin), web::Data::new(state), web::Query(model::AuditFilter::default())).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_filter_times_out_of_range() {
+        let admin = fake::credentials();
+        let state = admin_state(&admin);
+        let filter = model::AuditFilter { from: Some(u64::MAX), ..model::AuditFilter::default() };
+        let result = events(request_from(admin), web::Data::new(state), web::Query(filter)).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidRequest);
+    }
+
+    #[actix_rt::test]
+    async fn returns_the_verification_to_admins() {
+        let admin = fake::credentials();
+        let mut state = admin_state(&admin);
+        let mut chain = model::AuditChain::new();
+        chain.check(&event()).unwrap();
+        state.audit_log.verify.returns(chain.verification());
+        let result = verify(request_from(admin), web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let admin = fake::credentials();
+        let mut state = admin_state(&admin);
+        state.audit_log.verify.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = verify(request_from(admin), web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{
+    controller::challenge::{self, Attempt, Outcome},
+    handler::error,
+    model,
+};
+use actix_web::{HttpRequest, HttpResponse};
+use logging::warn;
+use std::net::SocketAddr;
+
+pub const CHALLENGE_HEADER: &str = "x-challenge";
+pub const CHALLENGE_SOLUTION_HEADER: &str = "x-challenge-solution";
+
+pub(crate) fn address<T: model::Dependencies>(req: &HttpRequest, state: &model::ServiceState<T>) -> Option<String> {
+    if state.settings.challenge.trust_forwarded_for {
+        let info = req.connection_info();
+        let forwarded = info.realip_remote_addr().map(|address| match address.parse::<SocketAddr>() {
+            Ok(socket) => socket.ip().to_string(),
+            Err(_) => String::from(address),
+        });
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+    req.peer_addr().map(|socket| socket.ip().to_string())
+}
+
+fn header(req: &HttpRequest, name: &str) -> Option<String> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+fn response(req: &HttpRequest) -> Option<model::ChallengeResponse> {
+    Some(model::ChallengeResponse {
+        challenge: header(req, CHALLENGE_HEADER)?,
+        solution: header(req, CHALLENGE_SOLUTION_HEADER)?,
+    })
+}
+
+pub fn attempt<T: model::Dependencies>(req: &HttpRequest, state: &model::ServiceState<T>, account: &str) -> Attempt {
+    Attempt::new(address(req, state).as_deref(), account)
+}
+
+/// Responds with a fresh challenge when one is required and was not solved.
+pub async fn require<T: model::Dependencies>(
+    req: &HttpRequest,
+    state: &model::ServiceState<T>,
+    attempt: &Attempt,
+) -> Result<(), HttpResponse> {
+    let response = response(req);
+    match challenge::check(
+        &state.challenges,
+        &state.challenge_verifier,
+        &state.settings.challenge,
+        attempt,
+        response.as_ref(),
+    )
+    .await
+    {
+        Ok(Outcome::Passed) => Ok(()),
+        Ok(Outcome::Required(issued)) => Err(error::respond_with(model::ErrorCode::ChallengeRequired, &issued)),
+        Err(_) => Err(error::internal_error()),
+    }
+}
+
+pub async fn failed<T: model::Dependencies>(state: &model::ServiceState<T>, attempt: &Attempt) {
+    if let Err(error) = challenge::failed(&state.challenges, &state.settings.challenge, attempt).await {
+        warn!(error = %error, "Failed to record a failed attempt");
+    }
+}
+
+pub async fn succeeded<T: model::Dependencies>(state: &model::ServiceState<T>, attempt: &Attempt) {
+    if let Err(error) = challenge::succeeded(&state.challenges, &state.settings.challenge, attempt).await {
+        warn!(error = %error, "Failed to clear failed attempts");
+    }
+}
@@ -1,46 +1,80 @@
 use crate::{
     controller::credentials,
+    handler::{challenge, error},
+    metrics,
     utilities::jwt,
-    repository,
     model,
 };
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 
-pub async fn save_credentials<L, C, R>(
-    state: web::Data<model::ServiceState<L, C, R>>,
+pub async fn save_credentials<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
     json: web::Json<model::FullRequest>,
-) -> HttpResponse
-    where
-        L: repository::LoginHistory,
-        C: repository::Credentials,
-        R: repository::PasswordResetRequest
-{
+) -> HttpResponse {
     let user_credentials = model::FullRequest::from(json);
-    match credentials::create(&state.credentials, &user_credentials).await {
-        Ok(result) => match result {
-            credentials::SaveResults::Conflict => HttpResponse::Conflict().finish(),
-            credentials::SaveResults::WeakPassword(problems) => serde_json::to_string(&problems)
-                .map_or(HttpResponse::InternalServerError().finish(), |json| {
-                    HttpResponse::Forbidden().json2(&json)
-                }),
-            credentials::SaveResults::Success(stored_credentials) => {
-                jwt::set_token(HttpResponse::Created(), stored_credentials)
-                    .unwrap_or(HttpResponse::InternalServerError().finish())
+    let attempt = challenge::attempt(&req, &state, &user_credentials.email);
+    if let Err(required) = challenge::require(&req, &state, &attempt).await {
+        return required;
+    }
+    match credentials::create(
+        &state.credentials,
+        &state.invitations,
+        &state.mailer,
+        &user_credentials,
+        state.settings,
+    )
+    .await
+    {
+        Ok(result) => {
+            metrics::save(&result);
+            match result {
+                credentials::SaveResults::EmailTaken => HttpResponse::Accepted().finish(),
+                credentials::SaveResults::Success(_) if state.settings.registration.private => {
+                    HttpResponse::Accepted().finish()
+                }
+                credentials::SaveResults::Conflict => {
+                    challenge::failed(&state, &attempt).await;
+                    error::respond(model::ErrorCode::Conflict)
+                }
+                credentials::SaveResults::InvalidName(problems) => {
+                    error::respond_with(model::ErrorCode::InvalidName, &problems)
+                }
+                credentials::SaveResults::InvitationRequired => {
+                    challenge::failed(&state, &attempt).await;
+                    error::respond(model::ErrorCode::InvitationRequired)
+                }
+                credentials::SaveResults::InvitationExpired => error::respond(model::ErrorCode::Expired),
+                credentials::SaveResults::WeakPassword(problems) => {
+                    error::respond_with(model::ErrorCode::WeakPassword, &problems)
+                }
+                credentials::SaveResults::Success(stored_credentials) => {
+                    jwt::set_token(state.settings, HttpResponse::Created(), stored_credentials)
+                        .unwrap_or_else(|_| error::internal_error())
+                }
             }
-        },
-        Err(_) => HttpResponse::InternalServerError().finish(),
+        }
+        Err(_) => error::internal_error(),
     }
 }
 
 #[cfg(test)]
 mod save_credentials_handler_test {
     use super::*;
-    use crate::{repository, utilities::test::fake, error::Error};
+    use crate::{repository, utilities::{test, test::fake}, error::Error};
     use actix_rt;
     use actix_web::{http, web};
 
     const WEAK_PASSWORD: &str = "password";
 
+    fn test_request() -> HttpRequest {
+        actix_web::test::TestRequest::default().to_http_request()
+    }
+
+    fn private_state() -> fake::MockServiceState {
+        fake::service_state_with(|settings| settings.registration.private = true)
+    }
+
     #[actix_rt::test]
     async fn returns_created_on_successful_creation() {
         let mut state = fake::service_state();
@@ -52,7 +86,7 @@ mod save_credentials_handler_test {
             .get_status
             .returns(repository::CredentialStatus::None);
         state.credentials.save_credentials.returns(record);
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::CREATED);
     }
 
@@ -67,7 +101,7 @@ mod save_credentials_handler_test {
             .get_status
             .returns(repository::CredentialStatus::None);
         state.credentials.save_credentials.returns(record);
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(result.headers().contains_key(http::header::AUTHORIZATION));
     }
 
@@ -76,16 +110,37 @@ mod save_credentials_handler_test {
         let state = fake::service_state();
         let mut request = fake::full_request();
         request.password = WEAK_PASSWORD.to_string();
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::FORBIDDEN);
     }
 
+    #[actix_rt::test]
+    async fn includes_password_issues_as_error_details() {
+        let state = fake::service_state();
+        let mut request = fake::full_request();
+        request.password = WEAK_PASSWORD.to_string();
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        let body = test::error_response(&result);
+        assert_eq!(body.code, model::ErrorCode::WeakPassword);
+        assert!(body.details.is_some());
+    }
+
     #[actix_rt::test]
     async fn does_not_set_auth_header_when_password_is_too_weak() {
         let state = fake::service_state();
         let mut request = fake::full_request();
         request.password = WEAK_PASSWORD.to_string();
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[actix_rt::test]
+    async fn returns_unprocessable_entity_when_a_name_is_invalid() {
+        let state = fake::service_state();
+        let mut request = fake::full_request();
+        request.name = String::from("admin");
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
         assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
     }
 
@@ -97,7 +152,7 @@ mod save_credentials_handler_test {
             .credentials
             .get_status
             .returns(repository::CredentialStatus::Exists);
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::CONFLICT);
     }
 
@@ -109,7 +164,7 @@ mod save_credentials_handler_test {
             .credentials
             .get_status
             .returns(repository::CredentialStatus::Exists);
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
     }
 
@@ -119,7 +174,7 @@ mod save_credentials_handler_test {
         let mut state = fake::service_state();
         let request = fake::full_request();
         state.credentials.get_status.throws_error(error);
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
     }
 
@@ -129,7 +184,37 @@ mod save_credentials_handler_test {
         let mut state = fake::service_state();
         let request = fake::full_request();
         state.credentials.get_status.throws_error(error);
-        let result = save_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = save_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
     }
+
+    #[actix_rt::test]
+    async fn responds_identically_to_private_registrations_whether_or_not_the_email_is_taken() {
+        let mut state = private_state();
+        state.credentials.by_email.returns(None).returns(Some(fake::credentials()));
+        state.credentials.get_status.returns(repository::CredentialStatus::None);
+        state.credentials.save_credentials.returns(fake::credentials());
+        state.mailer.send.returns(());
+        let registered = save_credentials(test_request(), web::Data::new(state.clone()), web::Json(fake::full_request()))
+            .await;
+        let taken = save_credentials(test_request(), web::Data::new(state.clone()), web::Json(fake::full_request())).await;
+        assert_eq!(registered.status(), status_codes::ACCEPTED);
+        assert_eq!(taken.status(), registered.status());
+        assert_eq!(taken.headers().len(), registered.headers().len());
+        assert!(!registered.headers().contains_key(http::header::AUTHORIZATION));
+        test::settle().await;
+        assert_eq!(state.mailer.send.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn accepts_private_registrations_with_a_taken_email_when_the_notice_cannot_be_sent() {
+        let mut state = private_state();
+        state.credentials.by_email.returns(Some(fake::credentials()));
+        state.mailer.send.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = save_credentials(test_request(), web::Data::new(state.clone()), web::Json(fake::full_request()))
+            .await;
+        assert_eq!(result.status(), status_codes::ACCEPTED);
+        test::settle().await;
+        assert_eq!(state.mailer.send.times_called(), 1);
+    }
 }
@@ -1,42 +1,52 @@
-use crate::{controller::credentials, model, repository};
-use actix_web::{web, HttpResponse};
+use crate::{controller::credentials, handler::{error, session}, model};
+use actix_web::{web, HttpRequest, HttpResponse};
 
-pub async fn delete_credentials<L, C, R>(
-    state: web::Data<model::ServiceState<L, C, R>>,
+pub async fn delete_credentials<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
     json: web::Json<model::EmailRequest>,
-) -> HttpResponse
-    where
-        L: repository::LoginHistory,
-        C: repository::Credentials,
-        R: repository::PasswordResetRequest
-{
+) -> HttpResponse {
+    if let Err(denied) = session::not_impersonated(&req, &state.settings.jwt) {
+        return denied;
+    }
     let user_credentials = model::EmailRequest::from(json);
-    match credentials::delete(&state.credentials, &state.login_history, &user_credentials).await {
+    match credentials::delete(&state.credentials, &state.login_history, &user_credentials, &state.settings.hash).await {
         Ok(deletion) => match deletion {
-            credentials::DeleteResults::Success => HttpResponse::Accepted(),
-            _ => HttpResponse::Unauthorized(),
+            credentials::DeleteResults::Success => HttpResponse::Accepted().finish(),
+            credentials::DeleteResults::Suspended => error::respond(model::ErrorCode::Suspended),
+            _ => error::respond(model::ErrorCode::InvalidCredentials),
         },
-        Err(_) => HttpResponse::InternalServerError(),
+        Err(_) => error::internal_error(),
     }
-    .finish()
 }
 
 #[cfg(test)]
 mod delete_credentials_handler_test {
     use super::*;
-    use crate::{utilities::{test::fake, hash}, error::Error};
+    use crate::{utilities::{jwt, test::fake, hash}, error::Error};
     use actix_rt;
-    use actix_web::web;
+    use actix_web::{http, web};
+
+    fn test_request() -> HttpRequest {
+        actix_web::test::TestRequest::default().to_http_request()
+    }
+
+    fn impersonated_request() -> HttpRequest {
+        let token = jwt::generate_impersonation_token(&fake::settings().jwt, fake::credentials(), 42, 60).unwrap();
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request()
+    }
 
     #[actix_rt::test]
     async fn returns_accepted_on_successful_deletion() {
         let mut state = fake::service_state();
         let request = fake::email_request();
         let mut record = fake::credentials();
-        record.hash = hash::generate(&request.password).unwrap();
+        record.hash = hash::generate(&fake::settings().hash, &request.password).unwrap();
         state.credentials.by_email.returns(Some(record.clone()));
         state.credentials.mark_as_deleted_by_email.returns(1);
-        let result = delete_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = delete_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::ACCEPTED);
     }
 
@@ -45,7 +55,7 @@ mod delete_credentials_handler_test {
         let mut state = fake::service_state();
         let request = fake::email_request();
         state.credentials.by_email.returns(None);
-        let result = delete_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = delete_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::UNAUTHORIZED);
     }
 
@@ -55,7 +65,15 @@ mod delete_credentials_handler_test {
         let mut state = fake::service_state();
         let request = fake::email_request();
         state.credentials.by_email.throws_error(error);
-        let result = delete_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = delete_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
     }
+
+    #[actix_rt::test]
+    async fn forbids_impersonated_sessions() {
+        let state = fake::service_state();
+        let result = delete_credentials(impersonated_request(), web::Data::new(state.clone()), web::Json(fake::email_request())).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+        assert_eq!(state.credentials.mark_as_deleted_by_email.times_called(), 0);
+    }
 }
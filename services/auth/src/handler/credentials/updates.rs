@@ -1,34 +1,51 @@
 use crate::{
     controller::credentials,
+    handler::{error, session},
     utilities::jwt,
-    repository,
     model,
 };
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 
-pub async fn update_credentials<L, C, R>(
-    state: web::Data<model::ServiceState<L, C, R>>,
+pub async fn update_credentials<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
     json: web::Json<model::UpdateCredentials>,
-) -> HttpResponse
-    where
-        L: repository::LoginHistory,
-        C: repository::Credentials,
-        R: repository::PasswordResetRequest
-{
+) -> HttpResponse {
+    if let Err(denied) = session::not_impersonated(&req, &state.settings.jwt) {
+        return denied;
+    }
     let updated_credentials = model::UpdateCredentials::from(json);
     let model::UpdateCredentials {
         auth,
         credentials: updates,
     } = updated_credentials;
-    match credentials::update(&state.credentials, &state.login_history, &auth, &updates).await {
+    match credentials::update(
+        &state.credentials,
+        &state.login_history,
+        &state.mailer,
+        &auth,
+        &updates,
+        state.settings,
+    )
+    .await
+    {
         Ok(status) => match status {
             credentials::UpdateResults::Success(credentials) => {
-                jwt::set_token(HttpResponse::Ok(), credentials)
-                    .unwrap_or(HttpResponse::InternalServerError().finish())
+                jwt::set_token(state.settings, HttpResponse::Ok(), credentials)
+                    .unwrap_or_else(|_| error::internal_error())
+            }
+            credentials::UpdateResults::Pending(credentials) => {
+                jwt::set_token(state.settings, HttpResponse::Accepted(), credentials)
+                    .unwrap_or_else(|_| error::internal_error())
+            }
+            credentials::UpdateResults::InvalidName(problems) => {
+                error::respond_with(model::ErrorCode::InvalidName, &problems)
             }
-            _ => HttpResponse::Unauthorized().finish(),
+            credentials::UpdateResults::Conflict => error::respond(model::ErrorCode::Conflict),
+            credentials::UpdateResults::Suspended => error::respond(model::ErrorCode::Suspended),
+            _ => error::respond(model::ErrorCode::InvalidCredentials),
         },
-        Err(_) => HttpResponse::InternalServerError().finish(),
+        Err(_) => error::internal_error(),
     }
 }
 
@@ -39,36 +56,75 @@ mod update_credentials_handler_test {
     use actix_rt;
     use actix_web::{http, web};
 
+    fn test_request() -> HttpRequest {
+        actix_web::test::TestRequest::default().to_http_request()
+    }
+
+    fn impersonated_request() -> HttpRequest {
+        let token = jwt::generate_impersonation_token(&fake::settings().jwt, fake::credentials(), 42, 60).unwrap();
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request()
+    }
+
     #[actix_rt::test]
     async fn returns_okay_on_successful_authentication() {
         let mut state = fake::service_state();
-        let request = fake::update_credentials_request();
+        let mut request = fake::update_credentials_request();
         let mut record = fake::credentials();
-        record.hash = hash::generate(&request.auth.password).unwrap();
+        record.hash = hash::generate(&fake::settings().hash, &request.auth.password).unwrap();
+        request.credentials.email = None;
         state.credentials.by_email.returns(Some(record.clone()));
+        state.credentials.by_name.returns(None);
         state.credentials.update_credentials.returns(record.clone());
-        let result = update_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::OKAY);
     }
 
     #[actix_rt::test]
     async fn sets_auth_header_on_successful_authentication() {
         let mut state = fake::service_state();
-        let request = fake::update_credentials_request();
+        let mut request = fake::update_credentials_request();
         let mut record = fake::credentials();
-        record.hash = hash::generate(&request.auth.password).unwrap();
+        record.hash = hash::generate(&fake::settings().hash, &request.auth.password).unwrap();
+        request.credentials.email = None;
         state.credentials.by_email.returns(Some(record.clone()));
+        state.credentials.by_name.returns(None);
         state.credentials.update_credentials.returns(record.clone());
-        let result = update_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert!(result.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[actix_rt::test]
+    async fn returns_accepted_when_an_email_change_is_pending() {
+        let mut state = fake::service_state();
+        let request = fake::update_credentials_request();
+        let mut record = fake::credentials();
+        record.hash = hash::generate(&fake::settings().hash, &request.auth.password).unwrap();
+        state.credentials.by_email.returns(Some(record.clone()));
+        state.credentials.by_name.returns(None);
+        state.credentials.update_with_email_change.returns((record.clone(), fake::email_change()));
+        state.mailer.send.returns(()).returns(());
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::ACCEPTED);
         assert!(result.headers().contains_key(http::header::AUTHORIZATION));
     }
 
+    #[actix_rt::test]
+    async fn returns_unprocessable_entity_when_the_new_name_is_invalid() {
+        let state = fake::service_state();
+        let mut request = fake::update_credentials_request();
+        request.credentials.name = Some(String::from("admin"));
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+    }
+
     #[actix_rt::test]
     async fn returns_unauthorized_on_failed_authentication() {
         let mut state = fake::service_state();
         let request = fake::update_credentials_request();
         state.credentials.by_email.returns(None);
-        let result = update_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::UNAUTHORIZED);
     }
 
@@ -77,7 +133,7 @@ mod update_credentials_handler_test {
         let mut state = fake::service_state();
         let request = fake::update_credentials_request();
         state.credentials.by_email.returns(None);
-        let result = update_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
     }
 
@@ -87,7 +143,7 @@ mod update_credentials_handler_test {
         let mut state = fake::service_state();
         let request = fake::update_credentials_request();
         state.credentials.by_email.throws_error(error);
-        let result = update_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
     }
 
@@ -97,7 +153,16 @@ mod update_credentials_handler_test {
         let mut state = fake::service_state();
         let request = fake::update_credentials_request();
         state.credentials.by_email.throws_error(error);
-        let result = update_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = update_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
     }
+
+    #[actix_rt::test]
+    async fn forbids_impersonated_sessions() {
+        let state = fake::service_state();
+        let request = fake::update_credentials_request();
+        let result = update_credentials(impersonated_request(), web::Data::new(state.clone()), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+        assert_eq!(state.credentials.update_credentials.times_called(), 0);
+    }
 }
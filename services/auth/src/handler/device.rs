@@ -0,0 +1,114 @@
+use crate::{
+    controller::device,
+    handler::{challenge, error},
+    model,
+    utilities::jwt,
+};
+use actix_web::{http, web, HttpRequest, HttpResponse};
+use logging::warn;
+
+fn header<'a>(req: &'a HttpRequest, name: http::header::HeaderName) -> Option<&'a str> {
+    req.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+fn sighting<T: model::Dependencies>(req: &HttpRequest, state: &model::ServiceState<T>) -> model::Sighting {
+    model::Sighting::new(
+        header(req, http::header::USER_AGENT),
+        header(req, http::header::ACCEPT_LANGUAGE),
+        challenge::address(req, state).and_then(|address| address.parse().ok()),
+    )
+}
+
+/// Notifying is best effort; a failure is logged and never blocks the sign in.
+pub async fn observe<T: model::Dependencies>(
+    req: &HttpRequest,
+    state: &model::ServiceState<T>,
+    credentials: &model::Credentials,
+) {
+    if !state.settings.devices.notify {
+        return;
+    }
+    if let Err(error) = device::observe(
+        &state.devices,
+        &state.locator,
+        &state.mailer,
+        credentials,
+        sighting(req, state),
+        &state.settings.links,
+    )
+    .await
+    {
+        warn!(error = %error, "Failed to check the sign in device");
+    }
+}
+
+pub async fn disown<T: model::Dependencies>(
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::DeviceAlertConfirmation>,
+) -> HttpResponse {
+    let confirmation = model::DeviceAlertConfirmation::from(json);
+    match device::disown(&state.devices, &confirmation, &state.settings.hash).await {
+        Ok(result) => match result {
+            device::DisownResults::Disowned => jwt::clear_token(&state.settings.cookies, HttpResponse::Ok()),
+            device::DisownResults::Expired => error::respond(model::ErrorCode::Expired),
+            _ => error::respond(model::ErrorCode::InvalidToken),
+        },
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utilities::test::fake, error::Error};
+    use actix_rt;
+    use std::time::SystemTime;
+
+    #[actix_rt::test]
+    async fn returns_okay_and_clears_the_session_cookie_when_disowned() {
+        let confirmation = fake::device_alert_confirmation();
+        let mut state = fake::service_state();
+        state.devices.alert_by_id.returns(Some(fake::disownable_device_alert(&confirmation)));
+        state.devices.disown.returns(true);
+        let result = disown(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        assert!(result.cookies().any(|cookie| cookie.name() == csrf::SESSION_COOKIE));
+    }
+
+    #[actix_rt::test]
+    async fn returns_gone_when_the_alert_was_already_used() {
+        let confirmation = fake::device_alert_confirmation();
+        let mut alert = fake::disownable_device_alert(&confirmation);
+        let mut state = fake::service_state();
+        alert.used_at = Some(SystemTime::now());
+        state.devices.alert_by_id.returns(Some(alert));
+        let result = disown(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::GONE);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unauthorized_when_no_alert_is_found() {
+        let mut state = fake::service_state();
+        state.devices.alert_by_id.returns(None);
+        let result = disown(web::Data::new(state), web::Json(fake::device_alert_confirmation())).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let mut state = fake::service_state();
+        state.devices.alert_by_id.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = disown(web::Data::new(state), web::Json(fake::device_alert_confirmation())).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+
+    #[actix_rt::test]
+    async fn does_not_block_the_sign_in_when_the_check_fails() {
+        let mut state = fake::service_state();
+        state.locator.country.returns(None);
+        state.devices.observe.throws_error(Error::InternalServerError(String::from("testing")));
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        observe(&req, &state, &fake::credentials()).await;
+        assert_eq!(state.devices.observe.times_called(), 1);
+    }
+}
@@ -0,0 +1,89 @@
+use actix_web::{web, HttpResponse};
+use crate::{
+    controller::email_change,
+    handler::error,
+    utilities::jwt,
+    model,
+};
+
+pub async fn confirm_email_change<T: model::Dependencies>(
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::EmailChangeConfirmation>,
+) -> HttpResponse {
+    let confirmation = model::EmailChangeConfirmation::from(json);
+    match email_change::confirm(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash).await {
+        Ok(result) => match result {
+            email_change::ConfirmResults::Confirmed(credentials) => {
+                jwt::set_token(state.settings, HttpResponse::Ok(), credentials)
+                    .unwrap_or_else(|_| error::internal_error())
+            }
+            email_change::ConfirmResults::Expired => error::respond(model::ErrorCode::Expired),
+            email_change::ConfirmResults::Conflict => error::respond(model::ErrorCode::Conflict),
+            _ => error::respond(model::ErrorCode::InvalidToken),
+        },
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utilities::test::fake, error::Error};
+    use actix_rt;
+    use actix_web::http;
+    use std::time::SystemTime;
+
+    #[actix_rt::test]
+    async fn returns_okay_and_sets_auth_header_when_confirmed() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::pending_email_change(&confirmation)));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.credentials.by_email.returns(None);
+        state.credentials.update_with_confirmed_email_change.returns(Some(fake::credentials()));
+        let result = confirm_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        assert!(result.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[actix_rt::test]
+    async fn returns_gone_when_the_change_was_already_confirmed() {
+        let confirmation = fake::email_change_confirmation();
+        let mut change = fake::pending_email_change(&confirmation);
+        let mut state = fake::service_state();
+        change.confirmed_at = Some(SystemTime::now());
+        state.email_changes.by_id.returns(Some(change));
+        let result = confirm_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::GONE);
+    }
+
+    #[actix_rt::test]
+    async fn returns_conflict_when_the_new_email_is_taken() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::pending_email_change(&confirmation)));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.credentials.by_email.returns(Some(fake::credentials()));
+        let result = confirm_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::CONFLICT);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unauthorized_when_the_token_does_not_match() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::email_change()));
+        let result = confirm_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = confirm_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,5 @@
+mod confirm_email_change;
+mod revert_email_change;
+
+pub use confirm_email_change::*;
+pub use revert_email_change::*;
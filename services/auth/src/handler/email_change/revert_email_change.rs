@@ -0,0 +1,64 @@
+use actix_web::{web, HttpResponse};
+use crate::{controller::email_change, handler::error, model};
+
+pub async fn revert_email_change<T: model::Dependencies>(
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::EmailChangeConfirmation>,
+) -> HttpResponse {
+    let confirmation = model::EmailChangeConfirmation::from(json);
+    match email_change::revert(&state.email_changes, &state.credentials, &confirmation, &state.settings.hash).await {
+        Ok(result) => match result {
+            email_change::RevertResults::Reverted => HttpResponse::Ok().finish(),
+            email_change::RevertResults::Expired => error::respond(model::ErrorCode::Expired),
+            _ => error::respond(model::ErrorCode::InvalidToken),
+        },
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utilities::test::fake, error::Error};
+    use actix_rt;
+    use std::time::SystemTime;
+
+    #[actix_rt::test]
+    async fn returns_okay_when_reverted() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(Some(fake::revertible_email_change(&confirmation)));
+        state.email_changes.revert.returns(true);
+        let result = revert_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn returns_gone_when_already_reverted() {
+        let confirmation = fake::email_change_confirmation();
+        let mut change = fake::revertible_email_change(&confirmation);
+        let mut state = fake::service_state();
+        change.reverted_at = Some(SystemTime::now());
+        state.email_changes.by_id.returns(Some(change));
+        let result = revert_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::GONE);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unauthorized_when_no_change_is_found() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.returns(None);
+        let result = revert_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let confirmation = fake::email_change_confirmation();
+        let mut state = fake::service_state();
+        state.email_changes.by_id.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = revert_email_change(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::model::{ErrorCode, ErrorResponse};
+use actix_web::{dev::HttpResponseBuilder, error::InternalError, web, HttpResponse};
+use serde::Serialize;
+
+fn status(code: ErrorCode) -> HttpResponseBuilder {
+    match code {
+        ErrorCode::InvalidCredentials | ErrorCode::Suspended | ErrorCode::InvalidToken => {
+            HttpResponse::Unauthorized()
+        }
+        ErrorCode::Expired => HttpResponse::Gone(),
+        ErrorCode::Conflict => HttpResponse::Conflict(),
+        ErrorCode::WeakPassword
+        | ErrorCode::InvalidCsrfToken
+        | ErrorCode::Forbidden
+        | ErrorCode::InvitationRequired => HttpResponse::Forbidden(),
+        ErrorCode::InvalidName | ErrorCode::InvalidRequest => HttpResponse::UnprocessableEntity(),
+        ErrorCode::ChallengeRequired => HttpResponse::PreconditionRequired(),
+        ErrorCode::InternalError => HttpResponse::InternalServerError(),
+    }
+}
+
+pub fn respond(code: ErrorCode) -> HttpResponse {
+    status(code).json(ErrorResponse::new(code))
+}
+
+pub fn respond_with<T: Serialize>(code: ErrorCode, details: &T) -> HttpResponse {
+    serde_json::to_value(details).map_or_else(
+        |_| internal_error(),
+        |details| status(code).json(ErrorResponse::new(code).with_details(details)),
+    )
+}
+
+pub fn internal_error() -> HttpResponse {
+    respond(ErrorCode::InternalError)
+}
+
+/// Bodies that fail to deserialize get the error envelope rather than
+/// actix's plain text response.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .error_handler(|error, _| InternalError::from_response(error, respond(ErrorCode::InvalidRequest)).into())
+}
+
+pub fn form_config() -> web::FormConfig {
+    web::FormConfig::default()
+        .error_handler(|error, _| InternalError::from_response(error, respond(ErrorCode::InvalidRequest)).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::{name, test};
+
+    #[test]
+    fn responds_with_the_status_for_the_code() {
+        assert_eq!(respond(ErrorCode::Suspended).status(), status_codes::UNAUTHORIZED);
+        assert_eq!(respond(ErrorCode::Expired).status(), status_codes::GONE);
+        assert_eq!(internal_error().status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn includes_the_code_and_message_in_the_body() {
+        let body = test::error_response(&respond(ErrorCode::Conflict));
+        assert_eq!(body.code, ErrorCode::Conflict);
+        assert_eq!(body.message, ErrorCode::Conflict.message());
+        assert_eq!(body.details, None);
+    }
+
+    #[test]
+    fn includes_details_as_json() {
+        let issues = match name::validate("a", &[]) {
+            name::Validity::Invalid(issues) => issues,
+            name::Validity::Valid => panic!("expected the name to be invalid"),
+        };
+        let body = test::error_response(&respond_with(ErrorCode::InvalidName, &issues));
+        assert_eq!(body.details, Some(serde_json::to_value(&issues).unwrap()));
+    }
+}
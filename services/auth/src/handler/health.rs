@@ -0,0 +1,46 @@
+use crate::{model, repository::Health};
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+const UP: &str = "up";
+const DOWN: &str = "down";
+
+pub async fn live() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "status": UP }))
+}
+
+pub async fn ready<T: model::Dependencies>(state: web::Data<model::ServiceState<T>>) -> HttpResponse {
+    match state.health.database().await {
+        Ok(_) => HttpResponse::Ok().json(json!({ "status": UP, "database": UP })),
+        Err(_) => HttpResponse::ServiceUnavailable().json(json!({ "status": DOWN, "database": DOWN })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, utilities::test::fake};
+    use actix_rt;
+
+    #[actix_rt::test]
+    async fn live_returns_okay() {
+        let result = live().await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn ready_returns_okay_when_the_database_responds() {
+        let mut state = fake::service_state();
+        state.health.database.returns(());
+        let result = ready(web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn ready_returns_service_unavailable_when_the_database_is_down() {
+        let mut state = fake::service_state();
+        state.health.database.throws_error(Error::DatabaseError(database::Error::from("timed out")));
+        let result = ready(web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::SERVICE_UNAVAILABLE);
+    }
+}
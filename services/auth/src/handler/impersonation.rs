@@ -0,0 +1,112 @@
+use crate::{
+    controller::impersonation,
+    handler::{error, session},
+    model,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+
+pub async fn create<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::ImpersonationRequest>,
+) -> HttpResponse {
+    let admin = match session::admin(&req, &state).await {
+        Ok(admin) => admin,
+        Err(denied) => return denied,
+    };
+    match impersonation::impersonate(
+        &state.credentials,
+        &state.audit_log,
+        &json,
+        admin.id,
+        state.settings.admin.impersonation_lifetime,
+        &state.settings.jwt,
+    )
+    .await
+    {
+        Ok(impersonation::ImpersonationResult::Issued(response)) => HttpResponse::Created().json(response),
+        Ok(impersonation::ImpersonationResult::NotFound) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "user_id" }))
+        }
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Error,
+        utilities::{jwt, test, test::fake},
+    };
+    use actix_rt;
+    use actix_web::http;
+
+    const ADMIN_ID: model::CredentialId = 42;
+
+    fn request_with(token: String) -> HttpRequest {
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request()
+    }
+
+    fn admin() -> model::Credentials {
+        model::Credentials { id: ADMIN_ID, ..fake::credentials() }
+    }
+
+    fn admin_state() -> fake::MockServiceState {
+        let mut state = fake::service_state_with(|settings| settings.admin.ids = vec![ADMIN_ID]);
+        state.devices.revocation.returns(None);
+        state
+    }
+
+    fn impersonation_request() -> web::Json<model::ImpersonationRequest> {
+        web::Json(model::ImpersonationRequest { user_id: fake::numeric_id() })
+    }
+
+    #[actix_rt::test]
+    async fn forbids_users_who_are_not_admins() {
+        let req = request_with(jwt::generate_token(&fake::settings().jwt, fake::credentials()).unwrap());
+        let result = create(req, web::Data::new(fake::service_state()), impersonation_request()).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn forbids_impersonating_from_an_impersonation_token() {
+        let token = jwt::generate_impersonation_token(&fake::settings().jwt, admin(), ADMIN_ID, 60).unwrap();
+        let result = create(request_with(token), web::Data::new(admin_state()), impersonation_request()).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::Forbidden);
+    }
+
+    #[actix_rt::test]
+    async fn returns_created_with_the_token() {
+        let mut state = admin_state();
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.audit_log.record.returns(());
+        let req = request_with(jwt::generate_token(&fake::settings().jwt, admin()).unwrap());
+        let result = create(req, web::Data::new(state.clone()), impersonation_request()).await;
+        assert_eq!(result.status(), status_codes::CREATED);
+        assert_eq!(state.audit_log.record.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unprocessable_entity_for_an_unknown_user() {
+        let mut state = admin_state();
+        state.credentials.by_id.returns(None);
+        let req = request_with(jwt::generate_token(&fake::settings().jwt, admin()).unwrap());
+        let result = create(req, web::Data::new(state), impersonation_request()).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidRequest);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let mut state = admin_state();
+        state.credentials.by_id.throws_error(Error::InternalServerError(String::from("testing")));
+        let req = request_with(jwt::generate_token(&fake::settings().jwt, admin()).unwrap());
+        let result = create(req, web::Data::new(state), impersonation_request()).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    controller::invitation,
+    handler::{error, session},
+    model,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+
+pub async fn create<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::InvitationRequest>,
+) -> HttpResponse {
+    let admin = match session::admin(&req, &state).await {
+        Ok(admin) => admin,
+        Err(denied) => return denied,
+    };
+    match invitation::invite(
+        &state.invitations,
+        &state.mailer,
+        &json,
+        admin.id,
+        state.settings.registration.invitation_lifetime,
+        &state.settings.links,
+    )
+    .await
+    {
+        Ok(invitation::InviteResult::InvalidEmail) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "email" }))
+        }
+        Ok(invitation::InviteResult::InvalidRole) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "role" }))
+        }
+        Ok(invitation::InviteResult::Created(invitation)) => {
+            HttpResponse::Created().json(model::InvitationResponse::from(invitation))
+        }
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Error,
+        utilities::{jwt, test, test::fake},
+    };
+    use actix_rt;
+    use actix_web::http;
+
+    fn request_from(credentials: model::Credentials) -> HttpRequest {
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, jwt::generate_token(&fake::settings().jwt, credentials).unwrap())
+            .to_http_request()
+    }
+
+    fn admin_state(admin: &model::Credentials) -> fake::MockServiceState {
+        let mut state = fake::service_state_with(|settings| settings.admin.ids = vec![admin.id]);
+        state.devices.revocation.returns(None);
+        state
+    }
+
+    fn invitation_request() -> web::Json<model::InvitationRequest> {
+        web::Json(model::InvitationRequest {
+            email: fake::email_address(),
+            role: None,
+            expires_in: None,
+        })
+    }
+
+    #[actix_rt::test]
+    async fn forbids_users_who_are_not_admins() {
+        let req = request_from(fake::credentials());
+        let result = create(req, web::Data::new(fake::service_state()), invitation_request()).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn returns_created_with_the_invitation() {
+        let admin = fake::credentials();
+        let mut state = admin_state(&admin);
+        state.invitations.create.returns(fake::invitation());
+        state.mailer.send.returns(());
+        let result = create(request_from(admin), web::Data::new(state), invitation_request()).await;
+        assert_eq!(result.status(), status_codes::CREATED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unprocessable_entity_for_an_invalid_role() {
+        let admin = fake::credentials();
+        let mut request = invitation_request();
+        request.role = Some(String::from("not a role"));
+        let result = create(request_from(admin.clone()), web::Data::new(admin_state(&admin)), request).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidRequest);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let admin = fake::credentials();
+        let mut state = admin_state(&admin);
+        state.invitations.create.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = create(request_from(admin), web::Data::new(state), invitation_request()).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
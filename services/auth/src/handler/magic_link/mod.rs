@@ -0,0 +1,5 @@
+mod request_magic_link;
+mod sign_in;
+
+pub use request_magic_link::*;
+pub use sign_in::*;
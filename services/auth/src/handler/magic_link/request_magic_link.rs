@@ -0,0 +1,65 @@
+use actix_web::{web, HttpResponse};
+use crate::{
+    controller::magic_link,
+    handler::error,
+    model,
+};
+
+pub async fn request_magic_link<T: model::Dependencies>(
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::MagicLinkRequest>,
+) -> HttpResponse {
+    let request = model::MagicLinkRequest::from(json);
+    magic_link::request_magic_link(&state.magic_links, &state.mailer, &request.email, &state.settings.links)
+        .await
+        .map_or_else(|_| error::internal_error(), |_| HttpResponse::Accepted().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_rt;
+    use super::*;
+    use crate::{utilities::test::fake, error::Error};
+
+    #[actix_rt::test]
+    async fn returns_accepted_when_a_link_is_sent() {
+        let mut state = fake::service_state();
+        let request = fake::magic_link_request();
+        state.magic_links.generate.returns(Some(fake::magic_link()));
+        state.mailer.send.returns(());
+        let result = request_magic_link(web::Data::new(state), web::Json(request))
+            .await;
+        assert_eq!(result.status(), status_codes::ACCEPTED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_accepted_when_no_matching_user_exists() {
+        let mut state = fake::service_state();
+        let request = fake::magic_link_request();
+        state.magic_links.generate.returns(None);
+        let result = request_magic_link(web::Data::new(state), web::Json(request))
+            .await;
+        assert_eq!(result.status(), status_codes::ACCEPTED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_accepted_when_the_link_cannot_be_mailed() {
+        let mut state = fake::service_state();
+        let request = fake::magic_link_request();
+        state.magic_links.generate.returns(Some(fake::magic_link()));
+        state.mailer.send.throws_error(Error::InternalServerError(String::from("SMTP is down")));
+        let result = request_magic_link(web::Data::new(state), web::Json(request))
+            .await;
+        assert_eq!(result.status(), status_codes::ACCEPTED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_when_an_unexpected_error_occurs() {
+        let mut state = fake::service_state();
+        let request = fake::magic_link_request();
+        state.magic_links.generate.throws_error(Error::InternalServerError(String::from("Somethings amiss")));
+        let result = request_magic_link(web::Data::new(state), web::Json(request))
+            .await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,97 @@
+use actix_web::{web, HttpResponse};
+use crate::{
+    controller::magic_link,
+    handler::error,
+    utilities::jwt,
+    model,
+};
+
+pub async fn sign_in<T: model::Dependencies>(
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::MagicLinkConfirmation>,
+) -> HttpResponse {
+    let confirmation = model::MagicLinkConfirmation::from(json);
+    match magic_link::sign_in(
+        &state.magic_links,
+        &state.credentials,
+        &state.login_history,
+        &confirmation,
+        &state.settings.hash,
+    )
+    .await
+    {
+        Ok(result) => match result {
+            magic_link::SignInResults::Valid(credentials) => {
+                jwt::set_token(state.settings, HttpResponse::Ok(), credentials)
+                    .unwrap_or_else(|_| error::internal_error())
+            }
+            magic_link::SignInResults::Expired => error::respond(model::ErrorCode::Expired),
+            magic_link::SignInResults::Suspended => error::respond(model::ErrorCode::Suspended),
+            _ => error::respond(model::ErrorCode::InvalidToken),
+        },
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utilities::test::fake, error::Error};
+    use actix_rt;
+    use actix_web::http;
+    use std::time::SystemTime;
+
+    #[actix_rt::test]
+    async fn returns_okay_and_sets_auth_header_on_successful_sign_in() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut state = fake::service_state();
+        state.magic_links.by_id.returns(Some(fake::valid_magic_link(&confirmation)));
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.magic_links.consume.returns(true);
+        let result = sign_in(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        assert!(result.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[actix_rt::test]
+    async fn returns_gone_when_the_link_has_been_used() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut link = fake::valid_magic_link(&confirmation);
+        let mut state = fake::service_state();
+        link.used_at = Some(SystemTime::now());
+        state.magic_links.by_id.returns(Some(link));
+        let result = sign_in(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::GONE);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unauthorized_when_the_account_is_suspended() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut credentials = fake::credentials();
+        let mut state = fake::service_state();
+        credentials.locked_at = Some(SystemTime::now());
+        state.magic_links.by_id.returns(Some(fake::valid_magic_link(&confirmation)));
+        state.credentials.by_id.returns(Some(credentials));
+        let result = sign_in(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[actix_rt::test]
+    async fn returns_unauthorized_when_no_link_is_found() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut state = fake::service_state();
+        state.magic_links.by_id.returns(None);
+        let result = sign_in(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let confirmation = fake::magic_link_confirmation();
+        let mut state = fake::service_state();
+        state.magic_links.by_id.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = sign_in(web::Data::new(state), web::Json(confirmation)).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{handler::error, metrics, model, repository::Health};
+use actix_web::{web, HttpResponse};
+
+pub async fn export<T: model::Dependencies>(state: web::Data<model::ServiceState<T>>) -> HttpResponse {
+    metrics::pool(state.health.pool());
+    match metrics::export() {
+        Ok(body) => HttpResponse::Ok().content_type(metrics::CONTENT_TYPE).body(body),
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use actix_rt;
+
+    #[actix_rt::test]
+    async fn returns_okay_with_pool_utilization() {
+        let mut state = fake::service_state();
+        state.health.pool.returns(database::PoolState { connections: 3, idle_connections: 1, max_size: 15 });
+        let result = export(web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+}
@@ -1,3 +1,17 @@
+pub mod audit;
+pub mod challenge;
 pub mod credentials;
+pub mod device;
+pub mod email_change;
+pub(crate) mod error;
+pub mod health;
+pub mod impersonation;
+pub mod invitation;
+pub mod magic_link;
+pub mod metrics;
+pub mod openapi;
 pub mod verification;
-pub mod password_reset;
\ No newline at end of file
+pub mod password_reset;
+pub mod personal_access_token;
+pub mod service_client;
+mod session;
@@ -0,0 +1,18 @@
+use crate::openapi;
+use actix_web::HttpResponse;
+
+pub async fn specification() -> HttpResponse {
+    HttpResponse::Ok().json(openapi::specification())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_rt;
+
+    #[actix_rt::test]
+    async fn returns_okay() {
+        let result = specification().await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+}
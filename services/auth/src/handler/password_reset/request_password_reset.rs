@@ -1,23 +1,26 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use crate::{
     controller::password_reset,
-    repository,
+    handler::{challenge, error},
     model,
 };
 
-pub async fn request_password_reset<L, C, R>(
-    state: web::Data<model::ServiceState<L, C, R>>,
+/// Every reset request counts towards the challenge threshold, since the
+/// response never reveals whether the email belongs to an account.
+pub async fn request_password_reset<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
     json: web::Json<model::ResetRequest>,
-) -> HttpResponse
-    where
-        L: repository::LoginHistory,
-        C: repository::Credentials,
-        R: repository::PasswordResetRequest
-{
+) -> HttpResponse {
     let request = model::ResetRequest::from(json);
-    password_reset::request_password_reset(&state.reset_request, &request.email).await
-        .map_or(
-            HttpResponse::InternalServerError().finish(),
+    let attempt = challenge::attempt(&req, &state, &request.email);
+    if let Err(required) = challenge::require(&req, &state, &attempt).await {
+        return required;
+    }
+    challenge::failed(&state, &attempt).await;
+    password_reset::request_password_reset(&state.reset_request, &request.email, &state.settings.hash).await
+        .map_or_else(
+            |_| error::internal_error(),
             | record | HttpResponse::Accepted().json2(&record))
 }
 
@@ -27,13 +30,17 @@ mod tests {
     use super::*;
     use crate::{utilities::test::fake, error::Error};
 
+    fn test_request() -> HttpRequest {
+        actix_web::test::TestRequest::default().to_http_request()
+    }
+
     #[actix_rt::test]
     async fn returns_accepted_when_a_record_cannot_be_created() {
         let mut state = fake::service_state();
         let reset_request = fake::reset_request();
         let reset_record = fake::password_reset_request();
         state.reset_request.generate.returns(Some(reset_record.clone()));
-        let result = request_password_reset(web::Data::new(state), web::Json(reset_request))
+        let result = request_password_reset(test_request(), web::Data::new(state), web::Json(reset_request))
             .await;
         assert_eq!(result.status(), status_codes::ACCEPTED);
     }
@@ -43,7 +50,7 @@ mod tests {
         let mut state = fake::service_state();
         let reset_request = fake::reset_request();
         state.reset_request.generate.returns(None);
-        let result = request_password_reset(web::Data::new(state), web::Json(reset_request))
+        let result = request_password_reset(test_request(), web::Data::new(state), web::Json(reset_request))
             .await;
         assert_eq!(result.status(), status_codes::ACCEPTED);
     }
@@ -53,7 +60,7 @@ mod tests {
         let mut state = fake::service_state();
         let reset_request = fake::reset_request();
         state.reset_request.generate.throws_error(Error::InternalServerError(String::from("Somethings amiss")));
-        let result = request_password_reset(web::Data::new(state), web::Json(reset_request))
+        let result = request_password_reset(test_request(), web::Data::new(state), web::Json(reset_request))
             .await;
         assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
     }
@@ -1,39 +1,53 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use crate::{
-    repository,
     controller::password_reset,
+    handler::{error, session},
+    metrics,
     model,
 };
 
-pub async fn reset_password<L, C, R>(
-    state: web::Data<model::ServiceState<L, C, R>>,
+pub async fn reset_password<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
     json: web::Json<model::ResetConfirmation>,
-) -> HttpResponse
-    where
-        L: repository::LoginHistory,
-        C: repository::Credentials,
-        R: repository::PasswordResetRequest
-{
+) -> HttpResponse {
+    if let Err(denied) = session::not_impersonated(&req, &state.settings.jwt) {
+        return denied;
+    }
     let request = model::ResetConfirmation::from(json);
-    password_reset::reset_password(&state.reset_request, &state.credentials, &request)
+    password_reset::reset_password(&state.reset_request, &state.credentials, &request, &state.settings.hash)
         .await
-        .map_or_else(|_| HttpResponse::InternalServerError().finish(), | result  | match result {
-            password_reset::ResetResult::WeakPassword(problems) => serde_json::to_string(&problems)
-                .map_or_else(|_| HttpResponse::InternalServerError().finish(), | json | {
-                    HttpResponse::Forbidden().json(&json)
-                }),
-            password_reset::ResetResult::Expired => HttpResponse::Gone().finish(),
-            _ => HttpResponse::Accepted().finish(),
+        .map_or_else(|_| error::internal_error(), | result  | {
+            metrics::reset(&result);
+            match result {
+                password_reset::ResetResult::WeakPassword(problems) => {
+                    error::respond_with(model::ErrorCode::WeakPassword, &problems)
+                }
+                password_reset::ResetResult::Expired => error::respond(model::ErrorCode::Expired),
+                _ => HttpResponse::Accepted().finish(),
+            }
         })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{utilities::{test::fake, hash}, error::Error, configuration::PASSWORD_RESET_TIME_PERIOD};
+    use crate::{utilities::{jwt, test::fake, hash}, error::Error, configuration::PASSWORD_RESET_TIME_PERIOD};
+    use actix_web::http;
     use std::{time::{SystemTime, Duration}, ops::Sub};
     use actix_rt;
 
+    fn test_request() -> HttpRequest {
+        actix_web::test::TestRequest::default().to_http_request()
+    }
+
+    fn impersonated_request() -> HttpRequest {
+        let token = jwt::generate_impersonation_token(&fake::settings().jwt, fake::credentials(), 42, 60).unwrap();
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request()
+    }
+
     #[actix_rt::test]
     async fn returns_gone_if_the_reset_request_has_expired() {
         let credentials = fake::credentials();
@@ -43,7 +57,7 @@ mod tests {
         reset_record.created_at = SystemTime::now().sub(Duration::from_secs(PASSWORD_RESET_TIME_PERIOD));
         state.reset_request.by_id.returns(Some(reset_record));
         state.credentials.update_password_hash.returns(credentials.clone());
-        let result = reset_password(web::Data::new(state), web::Json(request))
+        let result = reset_password(test_request(), web::Data::new(state), web::Json(request))
             .await;
         assert_eq!(result.status(), status_codes::GONE);
     }
@@ -54,7 +68,7 @@ mod tests {
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
         state.reset_request.by_id.throws_error(error.clone());
-        let result = reset_password(web::Data::new(state), web::Json(request))
+        let result = reset_password(test_request(), web::Data::new(state), web::Json(request))
             .await;
         assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
     }
@@ -64,7 +78,7 @@ mod tests {
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
         state.reset_request.by_id.returns(None);
-        let result = reset_password(web::Data::new(state), web::Json(request))
+        let result = reset_password(test_request(), web::Data::new(state), web::Json(request))
             .await;
         assert_eq!(result.status(), status_codes::ACCEPTED);
     }
@@ -75,7 +89,7 @@ mod tests {
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
         state.reset_request.by_id.returns(Some(reset_record));
-        let result = reset_password(web::Data::new(state), web::Json(request))
+        let result = reset_password(test_request(), web::Data::new(state), web::Json(request))
             .await;
         assert_eq!(result.status(), status_codes::ACCEPTED);
     }
@@ -86,10 +100,10 @@ mod tests {
         let mut reset_record = fake::password_reset_request();
         let request = fake::password_reset_data();
         let mut state = fake::service_state();
-        reset_record.reset_token = hash::generate(&request.reset_token).unwrap();
+        reset_record.reset_token = hash::generate(&fake::settings().hash, &request.reset_token).unwrap();
         state.reset_request.by_id.returns(Some(reset_record));
         state.credentials.update_password_hash.returns(credentials.clone());
-        let result = reset_password(web::Data::new(state), web::Json(request))
+        let result = reset_password(test_request(), web::Data::new(state), web::Json(request))
             .await;
         assert_eq!(result.status(), status_codes::ACCEPTED);
     }
@@ -101,11 +115,20 @@ mod tests {
         let mut request = fake::password_reset_data();
         let mut state = fake::service_state();
         request.password = fake::weak_password();
-        reset_record.reset_token = hash::generate(&request.reset_token).unwrap();
+        reset_record.reset_token = hash::generate(&fake::settings().hash, &request.reset_token).unwrap();
         state.reset_request.by_id.returns(Some(reset_record));
         state.credentials.update_password_hash.returns(credentials.clone());
-        let result = reset_password(web::Data::new(state), web::Json(request))
+        let result = reset_password(test_request(), web::Data::new(state), web::Json(request))
+            .await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn forbids_impersonated_sessions() {
+        let state = fake::service_state();
+        let result = reset_password(impersonated_request(), web::Data::new(state.clone()), web::Json(fake::password_reset_data()))
             .await;
         assert_eq!(result.status(), status_codes::FORBIDDEN);
+        assert_eq!(state.credentials.update_password_hash.times_called(), 0);
     }
 }
@@ -0,0 +1,168 @@
+use crate::{
+    controller::personal_access_token,
+    handler::{error, session},
+    model,
+    repository::PersonalAccessTokens,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+
+/// Tokens are managed from a signed in session; neither impersonation nor
+/// another personal access token may mint or revoke them.
+async fn owner<T: model::Dependencies>(
+    req: &HttpRequest,
+    state: &model::ServiceState<T>,
+) -> Result<model::Session, HttpResponse> {
+    session::not_impersonated(req, &state.settings.jwt)?;
+    session::active(req, state).await
+}
+
+pub async fn create<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::PersonalAccessTokenRequest>,
+) -> HttpResponse {
+    let owner = match owner(&req, &state).await {
+        Ok(owner) => owner,
+        Err(denied) => return denied,
+    };
+    match personal_access_token::create(&state.personal_access_tokens, owner.id, &json).await {
+        Ok(personal_access_token::CreationResult::Created(token)) => {
+            HttpResponse::Created().json(model::PersonalAccessTokenResponse::created(token))
+        }
+        Ok(personal_access_token::CreationResult::InvalidName) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "name" }))
+        }
+        Ok(personal_access_token::CreationResult::InvalidScope(scope)) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "scopes", "scope": scope }))
+        }
+        Ok(personal_access_token::CreationResult::InvalidExpiry) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "expires_in" }))
+        }
+        Err(_) => error::internal_error(),
+    }
+}
+
+pub async fn list<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+) -> HttpResponse {
+    let owner = match owner(&req, &state).await {
+        Ok(owner) => owner,
+        Err(denied) => return denied,
+    };
+    match state.personal_access_tokens.for_user(owner.id).await {
+        Ok(tokens) => HttpResponse::Ok().json(
+            tokens
+                .into_iter()
+                .map(model::PersonalAccessTokenResponse::from)
+                .collect::<Vec<model::PersonalAccessTokenResponse>>(),
+        ),
+        Err(_) => error::internal_error(),
+    }
+}
+
+pub async fn revoke<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::PersonalAccessTokenRevocation>,
+) -> HttpResponse {
+    let owner = match owner(&req, &state).await {
+        Ok(owner) => owner,
+        Err(denied) => return denied,
+    };
+    match state.personal_access_tokens.revoke(owner.id, &json.id).await {
+        Ok(true) => HttpResponse::Accepted().finish(),
+        Ok(false) => error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "id" })),
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Error,
+        utilities::{hash, jwt, test, test::fake},
+    };
+    use actix_rt;
+    use actix_web::http;
+
+    fn request_with(token: String) -> HttpRequest {
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request()
+    }
+
+    fn signed_in() -> HttpRequest {
+        request_with(jwt::generate_token(&fake::settings().jwt, fake::credentials()).unwrap())
+    }
+
+    fn signed_in_state() -> fake::MockServiceState {
+        let mut state = fake::service_state();
+        state.devices.revocation.returns(None);
+        state
+    }
+
+    fn revocation() -> web::Json<model::PersonalAccessTokenRevocation> {
+        web::Json(model::PersonalAccessTokenRevocation { id: hash::token() })
+    }
+
+    #[actix_rt::test]
+    async fn returns_created_with_the_token() {
+        let mut state = signed_in_state();
+        state.personal_access_tokens.create.returns(fake::personal_access_token());
+        let json = web::Json(fake::personal_access_token_request());
+        let result = create(signed_in(), web::Data::new(state), json).await;
+        assert_eq!(result.status(), status_codes::CREATED);
+    }
+
+    #[actix_rt::test]
+    async fn requires_a_session_that_is_not_impersonated() {
+        let json = web::Json(fake::personal_access_token_request());
+        let result = create(request_with(String::from("invalid")), web::Data::new(fake::service_state()), json).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        let token = jwt::generate_impersonation_token(&fake::settings().jwt, fake::credentials(), 42, 60).unwrap();
+        let result = list(request_with(token), web::Data::new(fake::service_state())).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::Forbidden);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unprocessable_entity_for_an_invalid_request() {
+        let json = web::Json(model::PersonalAccessTokenRequest {
+            expires_in: Some(0),
+            ..fake::personal_access_token_request()
+        });
+        let result = create(signed_in(), web::Data::new(signed_in_state()), json).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidRequest);
+    }
+
+    #[actix_rt::test]
+    async fn lists_tokens_for_the_session() {
+        let mut state = signed_in_state();
+        state.personal_access_tokens.for_user.returns(vec![fake::personal_access_token()]);
+        let result = list(signed_in(), web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn revokes_only_tokens_that_exist() {
+        let mut state = signed_in_state();
+        state.devices.revocation.returns(None);
+        state.personal_access_tokens.revoke.returns(true).returns(false);
+        let result = revoke(signed_in(), web::Data::new(state.clone()), revocation()).await;
+        assert_eq!(result.status(), status_codes::ACCEPTED);
+        let result = revoke(signed_in(), web::Data::new(state), revocation()).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let mut state = signed_in_state();
+        state.personal_access_tokens.revoke.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = revoke(signed_in(), web::Data::new(state), revocation()).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
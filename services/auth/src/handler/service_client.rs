@@ -0,0 +1,190 @@
+use crate::{
+    controller::service_client,
+    handler::{error, session},
+    model,
+    repository::ServiceClients,
+};
+use actix_web::{http, web, HttpRequest, HttpResponse};
+use serde_json::json;
+use std::time::Duration;
+
+const NO_STORE: &str = "no-store";
+const NO_CACHE: &str = "no-cache";
+
+pub async fn create<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::ServiceClientRequest>,
+) -> HttpResponse {
+    let admin = match session::admin(&req, &state).await {
+        Ok(admin) => admin,
+        Err(denied) => return denied,
+    };
+    match service_client::register(&state.service_clients, &json, admin.id).await {
+        Ok(service_client::RegistrationResult::Created(client)) => {
+            HttpResponse::Created().json(model::ServiceClientCredentials::from(client))
+        }
+        Ok(service_client::RegistrationResult::InvalidName) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "name" }))
+        }
+        Ok(service_client::RegistrationResult::InvalidScope(scope)) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "scopes", "scope": scope }))
+        }
+        Ok(service_client::RegistrationResult::InvalidAudience(audience)) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "audiences", "audience": audience }))
+        }
+        Err(_) => error::internal_error(),
+    }
+}
+
+pub async fn rotate<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::ServiceClientRotation>,
+) -> HttpResponse {
+    let admin = match session::admin(&req, &state).await {
+        Ok(admin) => admin,
+        Err(denied) => return denied,
+    };
+    let grace = Duration::from_secs(state.settings.service_clients.secret_grace);
+    match state.service_clients.rotate(&json.client_id, admin.id, grace).await {
+        Ok(Some(client)) => HttpResponse::Ok().json(model::ServiceClientCredentials::from(client)),
+        Ok(None) => error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "client_id" })),
+        Err(_) => error::internal_error(),
+    }
+}
+
+pub async fn token<T: model::Dependencies>(
+    state: web::Data<model::ServiceState<T>>,
+    form: web::Form<model::TokenRequest>,
+) -> HttpResponse {
+    match service_client::issue_token(&state.service_clients, &form, state.settings).await {
+        Ok(service_client::TokenResult::Issued(response)) => HttpResponse::Ok()
+            .header(http::header::CACHE_CONTROL, NO_STORE)
+            .header(http::header::PRAGMA, NO_CACHE)
+            .json(response),
+        Ok(service_client::TokenResult::InvalidClient) => error::respond(model::ErrorCode::InvalidCredentials),
+        Ok(service_client::TokenResult::UnsupportedGrantType) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "grant_type" }))
+        }
+        Ok(service_client::TokenResult::InvalidScope) => {
+            error::respond_with(model::ErrorCode::InvalidRequest, &json!({ "field": "scope" }))
+        }
+        Err(_) => error::internal_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Error,
+        utilities::{hash, jwt, test, test::fake},
+    };
+    use actix_rt;
+
+    const ADMIN_ID: model::CredentialId = 42;
+
+    fn request_with(token: String) -> HttpRequest {
+        actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request()
+    }
+
+    fn admin_request() -> HttpRequest {
+        let admin = model::Credentials { id: ADMIN_ID, ..fake::credentials() };
+        request_with(jwt::generate_token(&fake::settings().jwt, admin).unwrap())
+    }
+
+    fn admin_state() -> fake::MockServiceState {
+        let mut state = fake::service_state_with(|settings| settings.admin.ids = vec![ADMIN_ID]);
+        state.devices.revocation.returns(None);
+        state
+    }
+
+    fn rotation() -> web::Json<model::ServiceClientRotation> {
+        web::Json(model::ServiceClientRotation { client_id: hash::token() })
+    }
+
+    fn token_request(client: &model::ServiceClient, secret: &str) -> web::Form<model::TokenRequest> {
+        web::Form(model::TokenRequest {
+            grant_type: String::from(model::CLIENT_CREDENTIALS_GRANT),
+            client_id: client.client_id.clone(),
+            client_secret: String::from(secret),
+            scope: None,
+        })
+    }
+
+    #[actix_rt::test]
+    async fn forbids_users_who_are_not_admins() {
+        let req = request_with(jwt::generate_token(&fake::settings().jwt, fake::credentials()).unwrap());
+        let result = create(req, web::Data::new(fake::service_state()), web::Json(fake::service_client_request()))
+            .await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+        let req = request_with(jwt::generate_token(&fake::settings().jwt, fake::credentials()).unwrap());
+        let result = rotate(req, web::Data::new(fake::service_state()), rotation()).await;
+        assert_eq!(result.status(), status_codes::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn returns_created_with_the_secret() {
+        let mut state = admin_state();
+        state.service_clients.create.returns(fake::service_client());
+        let result = create(admin_request(), web::Data::new(state), web::Json(fake::service_client_request())).await;
+        assert_eq!(result.status(), status_codes::CREATED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unprocessable_entity_for_invalid_scopes() {
+        let request = model::ServiceClientRequest {
+            scopes: vec![String::from("all the things")],
+            ..fake::service_client_request()
+        };
+        let result = create(admin_request(), web::Data::new(admin_state()), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidRequest);
+    }
+
+    #[actix_rt::test]
+    async fn rotates_the_secret_of_known_clients() {
+        let mut state = admin_state();
+        state.devices.revocation.returns(None);
+        state.service_clients.rotate.returns(Some(fake::service_client())).returns(None);
+        let result = rotate(admin_request(), web::Data::new(state.clone()), rotation()).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        let result = rotate(admin_request(), web::Data::new(state), rotation()).await;
+        assert_eq!(result.status(), status_codes::UNPROCESSABLE_ENTITY);
+    }
+
+    #[actix_rt::test]
+    async fn issues_tokens_that_are_not_cached() {
+        let mut state = fake::service_state();
+        let client = model::ServiceClient {
+            secret: hash::generate(&fake::settings().hash, "secret").unwrap(),
+            ..fake::service_client()
+        };
+        state.service_clients.by_client_id.returns(Some(client.clone()));
+        let result = token(web::Data::new(state), token_request(&client, "secret")).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        assert_eq!(result.headers().get(http::header::CACHE_CONTROL).unwrap(), NO_STORE);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unauthorized_for_invalid_client_credentials() {
+        let mut state = fake::service_state();
+        let client = fake::service_client();
+        state.service_clients.by_client_id.returns(None);
+        let result = token(web::Data::new(state), token_request(&client, "secret")).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidCredentials);
+    }
+
+    #[actix_rt::test]
+    async fn returns_internal_server_error_on_unexpected_error() {
+        let mut state = fake::service_state();
+        let client = fake::service_client();
+        state.service_clients.by_client_id.throws_error(Error::InternalServerError(String::from("testing")));
+        let result = token(web::Data::new(state), token_request(&client, "secret")).await;
+        assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
+    }
+}
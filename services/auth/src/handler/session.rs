@@ -0,0 +1,53 @@
+use crate::{configuration::settings::JwtSettings, handler::error, model, repository::Devices, utilities::jwt};
+use actix_web::{HttpRequest, HttpResponse};
+
+pub fn current(req: &HttpRequest, settings: &JwtSettings) -> Option<model::Session> {
+    jwt::token(req).and_then(|token| jwt::verify_token(settings, &token).ok())
+}
+
+/// A service client's token; these are never accepted as user sessions.
+pub fn service(req: &HttpRequest, settings: &JwtSettings) -> Option<model::ServiceSession> {
+    jwt::token(req).and_then(|token| jwt::verify_service_token(settings, &token).ok())
+}
+
+/// A personal access token, recognised by its prefix rather than decoded.
+pub fn personal_access_token(req: &HttpRequest) -> Option<String> {
+    jwt::token(req).filter(|token| token.starts_with(model::PERSONAL_ACCESS_TOKEN_PREFIX))
+}
+
+/// The current session, unless the account's sessions were revoked after
+/// its token was issued.
+pub async fn active<T: model::Dependencies>(
+    req: &HttpRequest,
+    state: &model::ServiceState<T>,
+) -> Result<model::Session, HttpResponse> {
+    let session = current(req, &state.settings.jwt).ok_or_else(|| error::respond(model::ErrorCode::InvalidToken))?;
+    match state.devices.revocation(session.id).await {
+        Ok(Some(revocation)) if revocation.revokes(session.issued_at) => {
+            Err(error::respond(model::ErrorCode::InvalidToken))
+        }
+        Ok(_) => Ok(session),
+        Err(_) => Err(error::internal_error()),
+    }
+}
+
+/// Impersonation tokens may look around but never change credentials.
+pub fn not_impersonated(req: &HttpRequest, settings: &JwtSettings) -> Result<(), HttpResponse> {
+    match current(req, settings) {
+        Some(session) if session.actor.is_some() => Err(error::respond(model::ErrorCode::Forbidden)),
+        _ => Ok(()),
+    }
+}
+
+pub async fn admin<T: model::Dependencies>(
+    req: &HttpRequest,
+    state: &model::ServiceState<T>,
+) -> Result<model::Session, HttpResponse> {
+    match current(req, &state.settings.jwt) {
+        Some(session) if session.actor.is_some() || !state.settings.admin.ids.contains(&session.id) => {
+            Err(error::respond(model::ErrorCode::Forbidden))
+        }
+        Some(_) => active(req, state).await,
+        None => Err(error::respond(model::ErrorCode::InvalidToken)),
+    }
+}
@@ -1,79 +1,166 @@
 use crate::{
-    controller::authorization,
+    controller::{authorization, personal_access_token},
+    handler::{challenge, device, error, session},
+    metrics,
     utilities::jwt,
-    repository,
     model,
 };
-use actix_web::{web, HttpResponse};
-
-pub async fn authenticate_credentials<L, C, R>(
-    state: web::Data<model::ServiceState<L, C, R>>,
-    json: web::Json<model::NameRequest>,
-) -> HttpResponse
-    where
-        L: repository::LoginHistory,
-        C: repository::Credentials,
-        R: repository::PasswordResetRequest
-{
-    let user_credentials = model::NameRequest::from(json);
-    match authorization::authorize(&user_credentials, &state.credentials, &state.login_history)
+use actix_web::{web, HttpRequest, HttpResponse};
+
+pub async fn authenticate_credentials<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+    json: web::Json<model::LoginRequest>,
+) -> HttpResponse {
+    let user_credentials = model::LoginRequest::from(json);
+    let attempt = challenge::attempt(&req, &state, &user_credentials.identifier);
+    if let Err(required) = challenge::require(&req, &state, &attempt).await {
+        return required;
+    }
+    match authorization::authorize(&user_credentials, &state.credentials, &state.login_history, &state.settings.hash)
         .await
     {
-        Ok(stored_credentials) => match stored_credentials {
-            authorization::Results::Valid(credentials) => {
-                jwt::set_token(HttpResponse::Ok(), credentials)
-                    .unwrap_or(HttpResponse::InternalServerError().finish())
+        Ok(stored_credentials) => {
+            metrics::authorization(&stored_credentials);
+            match stored_credentials {
+                authorization::Results::Valid(credentials) => {
+                    challenge::succeeded(&state, &attempt).await;
+                    device::observe(&req, &state, &credentials).await;
+                    jwt::set_token(state.settings, HttpResponse::Ok(), credentials)
+                        .unwrap_or_else(|_| error::internal_error())
+                }
+                authorization::Results::Suspended => {
+                    challenge::failed(&state, &attempt).await;
+                    error::respond(model::ErrorCode::Suspended)
+                }
+                _ => {
+                    challenge::failed(&state, &attempt).await;
+                    error::respond(model::ErrorCode::InvalidCredentials)
+                }
             }
-            _ => HttpResponse::Unauthorized().finish(),
-        },
-        Err(_) => HttpResponse::InternalServerError().finish(),
+        }
+        Err(_) => error::internal_error(),
     }
 }
 
+pub async fn verify_session<T: model::Dependencies>(
+    req: HttpRequest,
+    state: web::Data<model::ServiceState<T>>,
+) -> HttpResponse {
+    if let Some(service) = session::service(&req, &state.settings.jwt) {
+        return HttpResponse::Ok().json(service);
+    }
+    if let Some(token) = session::personal_access_token(&req) {
+        return match personal_access_token::verify(
+            &state.personal_access_tokens,
+            &state.credentials,
+            &state.devices,
+            &token,
+            &state.settings.hash,
+            state.settings.jwt.expiration,
+        )
+        .await
+        {
+            Ok(Some(session)) => HttpResponse::Ok().json(session),
+            Ok(None) => error::respond(model::ErrorCode::InvalidToken),
+            Err(_) => error::internal_error(),
+        };
+    }
+    match session::active(&req, &state).await {
+        Ok(session) => HttpResponse::Ok().json(session),
+        Err(denied) => denied,
+    }
+}
+
+pub async fn sign_out<T: model::Dependencies>(state: web::Data<model::ServiceState<T>>) -> HttpResponse {
+    jwt::clear_token(&state.settings.cookies, HttpResponse::Ok())
+}
+
 #[cfg(test)]
 mod verification_handler_test {
     use super::*;
-    use crate::{utilities::{test::fake, hash}, error::Error};
+    use crate::{utilities::{test, test::fake, hash}, error::Error};
     use actix_rt;
     use actix_web::{http, web};
+    use std::time::{Duration, SystemTime};
+
+    fn test_request() -> HttpRequest {
+        actix_web::test::TestRequest::default().to_http_request()
+    }
+
+    fn challenged_state() -> fake::MockServiceState {
+        fake::service_state_with(|settings| settings.challenge.enabled = true)
+    }
+
+    fn challenge() -> model::Challenge {
+        model::Challenge {
+            algorithm: String::from(model::PROOF_OF_WORK),
+            challenge: String::from("challenge"),
+            difficulty: 1,
+            expires_at: 0,
+        }
+    }
 
     #[actix_rt::test]
     async fn returns_okay_on_successful_authentication() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         let mut record = fake::credentials();
-        record.hash = hash::generate(&request.password).unwrap();
+        record.hash = hash::generate(&fake::settings().hash, &request.password).unwrap();
         state.credentials.by_name.returns(Some(record.clone()));
-        let result = authenticate_credentials(web::Data::new(state), web::Json(request)).await;
+        state.devices.observe.returns(vec![]);
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::OKAY);
     }
 
     #[actix_rt::test]
     async fn sets_auth_header_on_successful_authentication() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         let mut record = fake::credentials();
-        record.hash = hash::generate(&request.password).unwrap();
+        record.hash = hash::generate(&fake::settings().hash, &request.password).unwrap();
         state.credentials.by_name.returns(Some(record.clone()));
-        let result = authenticate_credentials(web::Data::new(state), web::Json(request)).await;
+        state.devices.observe.returns(vec![]);
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(result.headers().contains_key(http::header::AUTHORIZATION));
     }
 
     #[actix_rt::test]
     async fn returns_unauthorized_on_failed_authentication() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
+        state.credentials.by_name.returns(None);
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn returns_an_invalid_credentials_code_on_failed_authentication() {
+        let mut state = fake::service_state();
+        let request = fake::login_request();
         state.credentials.by_name.returns(None);
-        let result = authenticate_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidCredentials);
+    }
+
+    #[actix_rt::test]
+    async fn returns_a_suspended_code_when_the_account_is_suspended() {
+        let mut state = fake::service_state();
+        let request = fake::login_request();
+        let mut record = fake::credentials();
+        record.locked_at = Some(SystemTime::now());
+        state.credentials.by_name.returns(Some(record));
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::Suspended);
     }
 
     #[actix_rt::test]
     async fn does_not_set_auth_header_on_failed_authentication() {
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         state.credentials.by_name.returns(None);
-        let result = authenticate_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
     }
 
@@ -81,9 +168,9 @@ mod verification_handler_test {
     async fn returns_internal_server_error_on_unexpected_error() {
         let error = Error::InternalServerError("testing".to_string());
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         state.credentials.by_name.throws_error(error);
-        let result = authenticate_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert_eq!(result.status(), status_codes::INTERNAL_SERVER_ERROR);
     }
 
@@ -91,9 +178,168 @@ mod verification_handler_test {
     async fn does_not_set_auth_header_on_unexpected_error() {
         let error = Error::InternalServerError("testing".to_string());
         let mut state = fake::service_state();
-        let request = fake::name_request();
+        let request = fake::login_request();
         state.credentials.by_name.throws_error(error);
-        let result = authenticate_credentials(web::Data::new(state), web::Json(request)).await;
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
         assert!(!result.headers().contains_key(http::header::AUTHORIZATION));
     }
+
+    #[actix_rt::test]
+    async fn accepts_a_bearer_token() {
+        let token = jwt::generate_token(&fake::settings().jwt, fake::credentials()).unwrap();
+        let req = actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request();
+        let mut state = fake::service_state();
+        state.devices.revocation.returns(None);
+        assert_eq!(verify_session(req, web::Data::new(state)).await.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn accepts_a_session_cookie() {
+        let token = jwt::generate_token(&fake::settings().jwt, fake::credentials()).unwrap();
+        let req = actix_web::test::TestRequest::default()
+            .cookie(http::Cookie::new(csrf::SESSION_COOKIE, token))
+            .to_http_request();
+        let mut state = fake::service_state();
+        state.devices.revocation.returns(None);
+        assert_eq!(verify_session(req, web::Data::new(state)).await.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn accepts_a_service_token() {
+        let scopes = [String::from("bills:write")];
+        let token = jwt::generate_service_token(&fake::settings().jwt, "importer", &scopes, &[], 60).unwrap();
+        let req = actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request();
+        let result = verify_session(req, web::Data::new(fake::service_state())).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn accepts_a_personal_access_token() {
+        let secret = hash::token();
+        let stored = model::PersonalAccessToken {
+            token: hash::generate(&fake::settings().hash, &secret).unwrap(),
+            ..fake::personal_access_token()
+        };
+        let req = actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, model::personal_access_token::encode(&stored.id, &secret))
+            .to_http_request();
+        let mut state = fake::service_state();
+        state.personal_access_tokens.by_id.returns(Some(stored)).returns(None);
+        state.personal_access_tokens.used.returns(());
+        state.credentials.by_id.returns(Some(fake::credentials()));
+        state.devices.revocation.returns(None);
+        let result = verify_session(req.clone(), web::Data::new(state.clone())).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        let result = verify_session(req, web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_a_missing_or_invalid_session() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let result = verify_session(req, web::Data::new(fake::service_state())).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidToken);
+        let req = actix_web::test::TestRequest::default()
+            .cookie(http::Cookie::new(csrf::SESSION_COOKIE, "forged"))
+            .to_http_request();
+        assert_eq!(verify_session(req, web::Data::new(fake::service_state())).await.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_a_session_issued_before_sessions_were_revoked() {
+        let token = jwt::generate_token(&fake::settings().jwt, fake::credentials()).unwrap();
+        let req = actix_web::test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, token)
+            .to_http_request();
+        let mut state = fake::service_state();
+        state.devices.revocation.returns(Some(model::SessionRevocation {
+            revoked_at: SystemTime::now() + Duration::from_secs(1),
+        }));
+        let result = verify_session(req, web::Data::new(state)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        assert_eq!(test::error_response(&result).code, model::ErrorCode::InvalidToken);
+    }
+
+    #[actix_rt::test]
+    async fn checks_the_sign_in_device_on_successful_authentication() {
+        let mut state = fake::service_state();
+        let request = fake::login_request();
+        let mut record = fake::credentials();
+        record.hash = hash::generate(&fake::settings().hash, &request.password).unwrap();
+        state.credentials.by_name.returns(Some(record));
+        state.devices.observe.returns(vec![]);
+        let result = authenticate_credentials(test_request(), web::Data::new(state.clone()), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        assert_eq!(state.devices.observe.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn sign_out_clears_the_session_cookie() {
+        let result = sign_out(web::Data::new(fake::service_state())).await;
+        assert!(result.cookies().any(|cookie| cookie.name() == csrf::SESSION_COOKIE));
+    }
+
+    #[actix_rt::test]
+    async fn returns_okay_on_successful_authentication_by_email() {
+        let mut state = fake::service_state();
+        let request = fake::email_login_request();
+        let mut record = fake::credentials();
+        record.hash = hash::generate(&fake::settings().hash, &request.password).unwrap();
+        state.credentials.by_email.returns(Some(record.clone()));
+        state.devices.observe.returns(vec![]);
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+    }
+
+    #[actix_rt::test]
+    async fn returns_unauthorized_on_failed_authentication_by_email() {
+        let mut state = fake::service_state();
+        let request = fake::email_login_request();
+        state.credentials.by_email.returns(None);
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn requires_a_challenge_after_repeated_failures() {
+        let mut state = challenged_state();
+        state.challenges.failures.returns(state.settings.challenge.threshold);
+        state.challenge_verifier.issue.returns(challenge());
+        let result = authenticate_credentials(test_request(), web::Data::new(state), web::Json(fake::login_request())).await;
+        assert_eq!(result.status(), status_codes::PRECONDITION_REQUIRED);
+        let body = test::error_response(&result);
+        assert_eq!(body.code, model::ErrorCode::ChallengeRequired);
+        assert_eq!(body.details.unwrap()["challenge"], "challenge");
+    }
+
+    #[actix_rt::test]
+    async fn records_a_failure_on_failed_authentication() {
+        let mut state = challenged_state();
+        state.challenges.failures.returns(0);
+        state.challenges.record_failure.returns(());
+        state.credentials.by_name.returns(None);
+        let result = authenticate_credentials(test_request(), web::Data::new(state.clone()), web::Json(fake::login_request())).await;
+        assert_eq!(result.status(), status_codes::UNAUTHORIZED);
+        assert_eq!(state.challenges.record_failure.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn clears_failures_on_successful_authentication() {
+        let mut state = challenged_state();
+        let request = fake::login_request();
+        let mut record = fake::credentials();
+        record.hash = hash::generate(&fake::settings().hash, &request.password).unwrap();
+        state.challenges.failures.returns(0);
+        state.challenges.clear.returns(());
+        state.credentials.by_name.returns(Some(record));
+        state.devices.observe.returns(vec![]);
+        let result = authenticate_credentials(test_request(), web::Data::new(state.clone()), web::Json(request)).await;
+        assert_eq!(result.status(), status_codes::OKAY);
+        assert_eq!(state.challenges.clear.times_called(), 1);
+    }
 }
@@ -1,10 +1,17 @@
 mod error;
 
+pub mod challenge;
 pub mod configuration;
 pub mod constants;
 pub mod controller;
+pub mod geoip;
 pub mod handler;
+pub mod mail;
+pub mod metrics;
+pub mod migration;
 pub mod model;
+pub mod openapi;
+pub mod relay;
 pub mod repository;
 pub mod routes;
 pub mod utilities;
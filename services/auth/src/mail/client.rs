@@ -0,0 +1,127 @@
+use crate::{
+    configuration::{security::REDACTED, settings::MailSettings},
+    error::Error,
+    mail::Message,
+    Result,
+};
+use actix_web::web;
+use async_trait::async_trait;
+use lettre::{smtp::authentication::Credentials, SmtpClient, Transport};
+use lettre_email::Email;
+use logging::{debug, info};
+use std::fmt;
+
+pub type AppMailer = MailClient;
+
+#[derive(Clone)]
+pub struct SmtpConfiguration {
+    pub host: String,
+    pub user: String,
+    pub password: String,
+}
+
+impl fmt::Debug for SmtpConfiguration {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("SmtpConfiguration")
+            .field("host", &self.host)
+            .field("user", &self.user)
+            .field("password", &REDACTED)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct MailClient {
+    sender: String,
+    smtp: Option<SmtpConfiguration>,
+}
+
+impl fmt::Debug for MailClient {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("MailClient")
+            .field("sender", &self.sender)
+            .field("smtp", &self.smtp)
+            .finish()
+    }
+}
+
+impl MailClient {
+    pub fn new(sender: &str, smtp: Option<SmtpConfiguration>) -> MailClient {
+        MailClient {
+            sender: String::from(sender),
+            smtp,
+        }
+    }
+    pub fn from_settings(settings: &MailSettings) -> MailClient {
+        MailClient::new(
+            &settings.sender,
+            settings.smtp_host.as_ref().map(|host| SmtpConfiguration {
+                host: host.clone(),
+                user: settings.smtp_user.clone(),
+                password: settings.smtp_password.clone(),
+            }),
+        )
+    }
+}
+
+fn deliver(sender: &str, smtp: &SmtpConfiguration, message: &Message) -> Result<()> {
+    let email = Email::builder()
+        .to(message.to.as_str())
+        .from(sender)
+        .subject(message.subject.as_str())
+        .text(message.body.as_str())
+        .build()
+        .map_err(|error| Error::InternalServerError(error.to_string()))?;
+    let mut transport = SmtpClient::new_simple(&smtp.host)
+        .map_err(|error| Error::InternalServerError(error.to_string()))?
+        .credentials(Credentials::new(smtp.user.clone(), smtp.password.clone()))
+        .transport();
+    transport
+        .send(email.into())
+        .map(|_| ())
+        .map_err(|error| Error::InternalServerError(error.to_string()))
+}
+
+#[async_trait]
+impl super::Mailer for MailClient {
+    async fn send(&self, message: &Message) -> Result<()> {
+        match &self.smtp {
+            Some(smtp) => {
+                let sender = self.sender.clone();
+                let smtp = smtp.clone();
+                let message = message.clone();
+                web::block(move || deliver(&sender, &smtp, &message))
+                    .await
+                    .map_err(|error| Error::InternalServerError(error.to_string()))
+            }
+            None => {
+                info!(subject = %message.subject, "Mail not sent, no SMTP host configured");
+                if environment::in_development() {
+                    debug!("{}", &message.body);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_output_redacts_the_smtp_password() {
+        let smtp = SmtpConfiguration {
+            host: String::from("smtp.example.com"),
+            user: String::from("mailer"),
+            password: String::from("smtp-password"),
+        };
+        let client = MailClient::new("no-reply@example.com", Some(smtp));
+        let debug = format!("{:?}", client);
+        assert!(debug.contains("smtp.example.com"));
+        assert!(debug.contains(REDACTED));
+        assert!(!debug.contains("smtp-password"));
+    }
+}
@@ -0,0 +1,30 @@
+use crate::Result;
+use async_trait::async_trait;
+use std::marker::{Send, Sync};
+
+mod client;
+pub mod templates;
+
+pub use client::*;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Message {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Message {
+    pub fn new(to: &str, subject: &str, body: &str) -> Message {
+        Message {
+            to: String::from(to),
+            subject: String::from(subject),
+            body: String::from(body),
+        }
+    }
+}
+
+#[async_trait]
+pub trait Mailer: Clone + Send + Sync {
+    async fn send(&self, message: &Message) -> Result<()>;
+}
@@ -0,0 +1,169 @@
+use crate::{configuration::{links, settings::LinkSettings}, mail::Message, model};
+
+const MAGIC_LINK_SUBJECT: &str = "Your sign in link";
+const EMAIL_CHANGE_SUBJECT: &str = "Confirm your new email address";
+const EMAIL_CHANGE_NOTICE_SUBJECT: &str = "Your email address is being changed";
+const INVITATION_SUBJECT: &str = "You have been invited to byThePeoples";
+const NEW_DEVICE_SUBJECT: &str = "New sign in to your account";
+const REGISTRATION_ATTEMPT_SUBJECT: &str = "Someone tried to register with your email address";
+
+pub fn magic_link(settings: &LinkSettings, email: &str, id: &str, token: &str) -> Message {
+    Message::new(
+        email,
+        MAGIC_LINK_SUBJECT,
+        &format!(
+            "Use the link below to sign in. It can only be used once and expires shortly.\n\n{}\n\nIf you did not request this link you can safely ignore this email.",
+            links::magic_link(settings, id, token)
+        ),
+    )
+}
+
+pub fn email_change(settings: &LinkSettings, new_email: &str, id: &str, token: &str) -> Message {
+    Message::new(
+        new_email,
+        EMAIL_CHANGE_SUBJECT,
+        &format!(
+            "Use the link below to confirm this address for your account. Your email address will not change until it is confirmed.\n\n{}\n\nIf you did not request this change you can safely ignore this email.",
+            links::email_change(settings, id, token)
+        ),
+    )
+}
+
+pub fn email_change_notice(
+    settings: &LinkSettings,
+    old_email: &str,
+    new_email: &str,
+    id: &str,
+    revert_token: &str,
+) -> Message {
+    Message::new(
+        old_email,
+        EMAIL_CHANGE_NOTICE_SUBJECT,
+        &format!(
+            "A request was made to change the email address on your account to {}.\n\nIf you did not make this request, use the link below to keep this address on your account.\n\n{}",
+            new_email,
+            links::email_revert(settings, id, revert_token)
+        ),
+    )
+}
+
+pub fn invitation(settings: &LinkSettings, email: &str, token: &str) -> Message {
+    Message::new(
+        email,
+        INVITATION_SUBJECT,
+        &format!(
+            "You have been invited to create an account. Use the link below to register with this email address before the invitation expires.\n\n{}\n\nIf you were not expecting this invitation you can safely ignore this email.",
+            links::invitation(settings, token)
+        ),
+    )
+}
+
+pub fn registration_attempt(settings: &LinkSettings, email: &str) -> Message {
+    Message::new(
+        email,
+        REGISTRATION_ATTEMPT_SUBJECT,
+        &format!(
+            "Someone tried to create an account with this email address, but it already belongs to your account.\n\nIf this was you, sign in or reset your password at the link below.\n\n{}\n\nIf it was not you, you can safely ignore this email. Your account has not been changed.",
+            links::ui(settings)
+        ),
+    )
+}
+
+pub fn new_device(
+    settings: &LinkSettings,
+    email: &str,
+    sighting: &model::Sighting,
+    id: &str,
+    token: &str,
+) -> Message {
+    let agent = if sighting.agent.is_empty() { "an unknown browser" } else { sighting.agent.as_str() };
+    let location = match &sighting.country {
+        Some(country) => format!("{} ({})", sighting.network, country),
+        None => sighting.network.clone(),
+    };
+    Message::new(
+        email,
+        NEW_DEVICE_SUBJECT,
+        &format!(
+            "Your account was just signed in to from a device or network we have not seen before.\n\nBrowser: {}\nNetwork: {}\n\nIf this was you, there is nothing you need to do. If it was not, use the link below to sign out everywhere. You will then need to reset your password.\n\n{}",
+            agent,
+            location,
+            links::device_alert(settings, id, token)
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::{hash, test::fake};
+
+    #[test]
+    fn magic_link_is_addressed_to_the_recipient() {
+        let settings = &fake::settings().links;
+        let email = fake::email_address();
+        let message = magic_link(settings, &email, &hash::token(), &hash::token());
+        assert_eq!(message.to, email);
+    }
+
+    #[test]
+    fn magic_link_contains_the_sign_in_link() {
+        let settings = &fake::settings().links;
+        let (id, token) = (hash::token(), hash::token());
+        let message = magic_link(settings, &fake::email_address(), &id, &token);
+        assert!(message.body.contains(&links::magic_link(settings, &id, &token)));
+    }
+
+    #[test]
+    fn invitation_is_addressed_to_the_invitee_and_contains_the_registration_link() {
+        let settings = &fake::settings().links;
+        let email = fake::email_address();
+        let token = hash::token();
+        let message = invitation(settings, &email, &token);
+        assert_eq!(message.to, email);
+        assert!(message.body.contains(&links::invitation(settings, &token)));
+    }
+
+    #[test]
+    fn registration_attempt_is_addressed_to_the_owner_and_links_to_the_ui() {
+        let settings = &fake::settings().links;
+        let email = fake::email_address();
+        let message = registration_attempt(settings, &email);
+        assert_eq!(message.to, email);
+        assert!(message.body.contains(&links::ui(settings)));
+    }
+
+    #[test]
+    fn new_device_describes_the_sign_in_and_contains_the_revoke_link() {
+        let settings = &fake::settings().links;
+        let email = fake::email_address();
+        let sighting = fake::sighting().located(Some(String::from("NZ")));
+        let (id, token) = (hash::token(), hash::token());
+        let message = new_device(settings, &email, &sighting, &id, &token);
+        assert_eq!(message.to, email);
+        assert!(message.body.contains(&sighting.agent));
+        assert!(message.body.contains("203.0.113.0/24 (NZ)"));
+        assert!(message.body.contains(&links::device_alert(settings, &id, &token)));
+    }
+
+    #[test]
+    fn email_change_is_addressed_to_the_new_email() {
+        let settings = &fake::settings().links;
+        let email = fake::email_address();
+        let (id, token) = (hash::token(), hash::token());
+        let message = email_change(settings, &email, &id, &token);
+        assert_eq!(message.to, email);
+        assert!(message.body.contains(&links::email_change(settings, &id, &token)));
+    }
+
+    #[test]
+    fn email_change_notice_is_addressed_to_the_old_email_and_contains_the_revert_link() {
+        let settings = &fake::settings().links;
+        let (old_email, new_email) = (fake::email_address(), fake::email_address());
+        let (id, token) = (hash::token(), hash::token());
+        let message = email_change_notice(settings, &old_email, &new_email, &id, &token);
+        assert_eq!(message.to, old_email);
+        assert!(message.body.contains(&new_email));
+        assert!(message.body.contains(&links::email_revert(settings, &id, &token)));
+    }
+}
@@ -1,36 +1,68 @@
 use environment;
+use logging::{error, info};
 use btp_auth_server::{
     configuration::{
-        database::TEST_DATABASE_CONFIG,
+        security,
+        Settings,
     },
+    migration,
     server,
     model,
+    relay,
+    repository,
+    utilities::hash,
 };
 
+const SERVICE_NAME: &str = "auth";
 const DATABASE_INITIALIZATION_FAILURE: &str = "Failed to initialize database";
+const DUMMY_HASH_FAILURE: &str = "Failed to generate the dummy password hash";
+const INVALID_SETTINGS_EXIT_CODE: i32 = 1;
 
-pub async fn run_migrations<T: model::Database>(db: &T) -> std::result::Result<(), database::Error> {
-    let path_to_migrations = environment::path("/src/sql/migrations");
-    db.migrate(&path_to_migrations).await?;
-    println!("Migration Successful.\n");
-    Ok(())
+fn start_relay(db: &model::DatabaseConnection, settings: &Settings) {
+    let outbox = &settings.outbox;
+    if let Some(url) = &outbox.redis_url {
+        match relay::RedisPublisher::new(url, &outbox.stream, outbox.max_length) {
+            Ok(publisher) => {
+                info!(stream = %outbox.stream, "Relaying outbox events");
+                relay::start(repository::OutboxRepository::new(db.clone()), publisher, outbox);
+            }
+            Err(error) => error!(error = %error, "Failed to start the outbox relay"),
+        }
+    }
 }
 
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
-    let db = model::DatabaseConnection::new(TEST_DATABASE_CONFIG)
+    logging::init(SERVICE_NAME);
+    let settings: &'static Settings = match Settings::load() {
+        Ok(settings) => Box::leak(Box::new(settings)),
+        Err(error) => {
+            error!(problems = ?error.problems, "Invalid settings");
+            std::process::exit(INVALID_SETTINGS_EXIT_CODE);
+        }
+    };
+    info!(summary = %security::summary(settings), "Loaded settings");
+    let problems = security::problems(settings);
+    if !problems.is_empty() && (environment::in_production() || environment::in_staging()) {
+        error!(problems = ?problems, "Refusing to start with insecure settings");
+        std::process::exit(INVALID_SETTINGS_EXIT_CODE);
+    }
+    hash::dummy_hash(&settings.hash).expect(DUMMY_HASH_FAILURE);
+    let db = model::DatabaseConnection::new(settings.database.configuration())
         .await
         .expect(DATABASE_INITIALIZATION_FAILURE);
-    let state = model::initialize_state(&db);
+    let state = model::initialize_state(&db, settings);
     if environment::in_production() {
-        println!("In production");
+        info!("In production");
+        start_relay(&db, settings);
         server::production(state.clone())
             .await
     } else {
-        println!("In development");
-        run_migrations(&db)
+        info!("In development");
+        migration::run(&db)
             .await
             .expect(DATABASE_INITIALIZATION_FAILURE);
+        start_relay(&db, settings);
         server::development(state.clone()).await
     }
 }
@@ -0,0 +1,275 @@
+use crate::controller::{authorization, credentials, password_reset};
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpRequest,
+};
+use futures::future::{ok, Ready};
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramTimer, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Instant,
+};
+
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+const UNMATCHED_ROUTE: &str = "unmatched";
+const AUTHORIZATION_RESULTS: [&str; 4] = ["valid", "suspended", "invalid", "none"];
+const SAVE_RESULTS: [&str; 7] = [
+    "success",
+    "weak_password",
+    "invalid_name",
+    "conflict",
+    "invitation_required",
+    "invitation_expired",
+    "email_taken",
+];
+const RESET_RESULTS: [&str; 5] = ["success", "weak_password", "invalid_token", "not_found", "expired"];
+const HASH: &str = "hash";
+const VERIFY: &str = "verify";
+const ARGON2_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+struct Metrics {
+    registry: Registry,
+    http_request_duration: HistogramVec,
+    authorization_results: IntCounterVec,
+    save_results: IntCounterVec,
+    reset_results: IntCounterVec,
+    argon2_duration: HistogramVec,
+    pool_connections: IntGaugeVec,
+}
+
+fn counter(name: &str, help: &str, label: &str, values: &[&str]) -> IntCounterVec {
+    let counter = IntCounterVec::new(Opts::new(name, help), &[label]).unwrap();
+    values.iter().for_each(|value| {
+        counter.with_label_values(&[value]);
+    });
+    counter
+}
+
+impl Metrics {
+    fn new() -> Metrics {
+        let metrics = Metrics {
+            registry: Registry::new(),
+            http_request_duration: HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "HTTP request duration by route"),
+                &["method", "route", "status"],
+            )
+            .unwrap(),
+            authorization_results: counter(
+                "auth_authorization_results_total",
+                "Credential verification outcomes",
+                "result",
+                &AUTHORIZATION_RESULTS,
+            ),
+            save_results: counter(
+                "auth_credential_save_results_total",
+                "Credential creation outcomes",
+                "result",
+                &SAVE_RESULTS,
+            ),
+            reset_results: counter(
+                "auth_password_reset_results_total",
+                "Password reset outcomes",
+                "result",
+                &RESET_RESULTS,
+            ),
+            argon2_duration: HistogramVec::new(
+                HistogramOpts::new("auth_argon2_duration_seconds", "Argon2 hash and verify duration")
+                    .buckets(ARGON2_BUCKETS.to_vec()),
+                &["operation"],
+            )
+            .unwrap(),
+            pool_connections: IntGaugeVec::new(
+                Opts::new("auth_database_pool_connections", "Database connection pool utilization"),
+                &["state"],
+            )
+            .unwrap(),
+        };
+        let registry = &metrics.registry;
+        registry.register(Box::new(metrics.http_request_duration.clone())).unwrap();
+        registry.register(Box::new(metrics.authorization_results.clone())).unwrap();
+        registry.register(Box::new(metrics.save_results.clone())).unwrap();
+        registry.register(Box::new(metrics.reset_results.clone())).unwrap();
+        registry.register(Box::new(metrics.argon2_duration.clone())).unwrap();
+        registry.register(Box::new(metrics.pool_connections.clone())).unwrap();
+        metrics
+    }
+}
+
+static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+pub fn route(req: &HttpRequest) -> String {
+    req.match_pattern().unwrap_or_else(|| String::from(UNMATCHED_ROUTE))
+}
+
+pub fn authorization(result: &authorization::Results) {
+    let label = match result {
+        authorization::Results::Valid(_) => AUTHORIZATION_RESULTS[0],
+        authorization::Results::Suspended => AUTHORIZATION_RESULTS[1],
+        authorization::Results::Invalid => AUTHORIZATION_RESULTS[2],
+        authorization::Results::None => AUTHORIZATION_RESULTS[3],
+    };
+    METRICS.authorization_results.with_label_values(&[label]).inc();
+}
+
+pub fn save(result: &credentials::SaveResults) {
+    let label = match result {
+        credentials::SaveResults::Success(_) => SAVE_RESULTS[0],
+        credentials::SaveResults::WeakPassword(_) => SAVE_RESULTS[1],
+        credentials::SaveResults::InvalidName(_) => SAVE_RESULTS[2],
+        credentials::SaveResults::Conflict => SAVE_RESULTS[3],
+        credentials::SaveResults::InvitationRequired => SAVE_RESULTS[4],
+        credentials::SaveResults::InvitationExpired => SAVE_RESULTS[5],
+        credentials::SaveResults::EmailTaken => SAVE_RESULTS[6],
+    };
+    METRICS.save_results.with_label_values(&[label]).inc();
+}
+
+pub fn reset(result: &password_reset::ResetResult) {
+    let label = match result {
+        password_reset::ResetResult::Success(_) => RESET_RESULTS[0],
+        password_reset::ResetResult::WeakPassword(_) => RESET_RESULTS[1],
+        password_reset::ResetResult::InvalidToken => RESET_RESULTS[2],
+        password_reset::ResetResult::NotFound => RESET_RESULTS[3],
+        password_reset::ResetResult::Expired => RESET_RESULTS[4],
+    };
+    METRICS.reset_results.with_label_values(&[label]).inc();
+}
+
+pub fn time_hash() -> HistogramTimer {
+    METRICS.argon2_duration.with_label_values(&[HASH]).start_timer()
+}
+
+pub fn time_verify() -> HistogramTimer {
+    METRICS.argon2_duration.with_label_values(&[VERIFY]).start_timer()
+}
+
+pub fn pool(state: database::PoolState) {
+    let gauges = &METRICS.pool_connections;
+    let in_use = state.connections.saturating_sub(state.idle_connections);
+    gauges.with_label_values(&["open"]).set(i64::from(state.connections));
+    gauges.with_label_values(&["idle"]).set(i64::from(state.idle_connections));
+    gauges.with_label_values(&["in_use"]).set(i64::from(in_use));
+    gauges.with_label_values(&["max"]).set(i64::from(state.max_size));
+}
+
+pub fn export() -> prometheus::Result<String> {
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&METRICS.registry.gather(), &mut buffer)?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S> for RequestMetrics
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestMetricsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestMetricsMiddleware { service })
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service for RequestMetricsMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let method = req.method().to_string();
+        let response = self.service.call(req);
+        Box::pin(async move {
+            let response = response.await;
+            let (route, status) = match &response {
+                Ok(response) => (route(response.request()), response.status()),
+                Err(error) => (String::from(UNMATCHED_ROUTE), error.as_response_error().status_code()),
+            };
+            METRICS
+                .http_request_duration
+                .with_label_values(&[&method, &route, status.as_str()])
+                .observe(started.elapsed().as_secs_f64());
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routes, utilities::test::fake};
+    use actix_rt;
+    use actix_web::{test, App};
+
+    fn requests(method: &str, route: &str, status: &str) -> u64 {
+        METRICS
+            .http_request_duration
+            .with_label_values(&[method, route, status])
+            .get_sample_count()
+    }
+
+    #[actix_rt::test]
+    async fn labels_requests_by_their_matched_route() {
+        let mut server = test::init_service(App::new().wrap(RequestMetrics).configure(routes::configuration)).await;
+        let live = requests("GET", "/health/live", "200");
+        let unmatched = requests("GET", UNMATCHED_ROUTE, "404");
+        test::call_service(&mut server, test::TestRequest::get().uri("/health/live").to_request()).await;
+        test::call_service(&mut server, test::TestRequest::get().uri("/health/unknown").to_request()).await;
+        assert_eq!(requests("GET", "/health/live", "200"), live + 1);
+        assert_eq!(requests("GET", UNMATCHED_ROUTE, "404"), unmatched + 1);
+    }
+
+    #[test]
+    fn counts_authorization_results() {
+        let counter = METRICS.authorization_results.with_label_values(&["valid"]);
+        let before = counter.get();
+        authorization(&authorization::Results::Valid(fake::credentials()));
+        assert_eq!(counter.get(), before + 1);
+    }
+
+    #[test]
+    fn exports_every_result_variant() {
+        let exported = export().unwrap();
+        for result in RESET_RESULTS.iter() {
+            assert!(exported.contains(&format!("auth_password_reset_results_total{{result=\"{}\"}}", result)));
+        }
+        for result in SAVE_RESULTS.iter() {
+            assert!(exported.contains(&format!("auth_credential_save_results_total{{result=\"{}\"}}", result)));
+        }
+    }
+
+    #[test]
+    fn reports_pool_utilization() {
+        pool(database::PoolState { connections: 5, idle_connections: 2, max_size: 15 });
+        assert_eq!(METRICS.pool_connections.with_label_values(&["in_use"]).get(), 3);
+    }
+}
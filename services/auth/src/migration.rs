@@ -0,0 +1,16 @@
+use crate::{model, repository, Result};
+use logging::{info, warn};
+
+const MIGRATIONS_PATH: &str = "/src/sql/migrations";
+
+/// Applies the SQL migrations, then the data migrations that need the
+/// service's own code, such as computing name skeletons.
+pub async fn run<T: model::Database>(db: &T) -> Result<()> {
+    db.migrate(&environment::path(MIGRATIONS_PATH)).await?;
+    let collisions = repository::backfill_name_skeletons(db).await?;
+    if !collisions.is_empty() {
+        warn!(ids = ?collisions, "Accounts with confusable names were left without a name skeleton");
+    }
+    info!("Migration successful");
+    Ok(())
+}
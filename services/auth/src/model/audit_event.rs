@@ -0,0 +1,326 @@
+use database::Timestamp;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::{model::CredentialId, utilities::hash, Result};
+
+/// The end of year 9999, the last moment Postgres timestamps are expected to hold.
+pub const MAXIMUM_FILTER_TIMESTAMP: u64 = 253_402_300_799;
+
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+pub mod query {
+    pub const LOCK: &str = "LOCK TABLE auth.audit_event IN EXCLUSIVE MODE";
+    pub const LAST: &str = "SELECT sequence, event_type, user_id, details, created_at, previous_hash, hash FROM auth.audit_event ORDER BY sequence DESC LIMIT 1";
+    pub const CREATE: &str = "INSERT INTO auth.audit_event(sequence, event_type, user_id, details, created_at, previous_hash, hash) VALUES ($1, $2, $3, $4, $5, $6, $7)";
+    pub const AFTER: &str = "SELECT sequence, event_type, user_id, details, created_at, previous_hash, hash FROM auth.audit_event WHERE sequence > $1 ORDER BY sequence LIMIT $2";
+    pub const FILTER: &str = "SELECT sequence, event_type, user_id, details, created_at, previous_hash, hash FROM auth.audit_event
+ WHERE ($1::int IS NULL OR user_id = $1)
+   AND ($2::varchar IS NULL OR event_type = $2)
+   AND ($3::timestamp IS NULL OR created_at >= $3)
+   AND ($4::timestamp IS NULL OR created_at <= $4)
+ ORDER BY sequence
+ LIMIT $5";
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventType {
+    AccountCreated,
+    PasswordChanged,
+    AccountSuspended,
+    AccountDeleted,
+    ResetRequested,
+    InvitationCreated,
+    SessionsRevoked,
+    ImpersonationIssued,
+    ServiceClientCreated,
+    ServiceClientRotated,
+    PersonalAccessTokenCreated,
+    PersonalAccessTokenRevoked,
+}
+
+impl AuditEventType {
+    pub const ALL: [AuditEventType; 12] = [
+        AuditEventType::AccountCreated,
+        AuditEventType::PasswordChanged,
+        AuditEventType::AccountSuspended,
+        AuditEventType::AccountDeleted,
+        AuditEventType::ResetRequested,
+        AuditEventType::InvitationCreated,
+        AuditEventType::SessionsRevoked,
+        AuditEventType::ImpersonationIssued,
+        AuditEventType::ServiceClientCreated,
+        AuditEventType::ServiceClientRotated,
+        AuditEventType::PersonalAccessTokenCreated,
+        AuditEventType::PersonalAccessTokenRevoked,
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditEventType::AccountCreated => "account_created",
+            AuditEventType::PasswordChanged => "password_changed",
+            AuditEventType::AccountSuspended => "account_suspended",
+            AuditEventType::AccountDeleted => "account_deleted",
+            AuditEventType::ResetRequested => "reset_requested",
+            AuditEventType::InvitationCreated => "invitation_created",
+            AuditEventType::SessionsRevoked => "sessions_revoked",
+            AuditEventType::ImpersonationIssued => "impersonation_issued",
+            AuditEventType::ServiceClientCreated => "service_client_created",
+            AuditEventType::ServiceClientRotated => "service_client_rotated",
+            AuditEventType::PersonalAccessTokenCreated => "personal_access_token_created",
+            AuditEventType::PersonalAccessTokenRevoked => "personal_access_token_revoked",
+        }
+    }
+}
+
+fn microseconds(time: Timestamp) -> Result<u128> {
+    Ok(time.duration_since(UNIX_EPOCH)?.as_micros())
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuditEvent {
+    pub sequence: i64,
+    pub event_type: String,
+    pub user_id: Option<CredentialId>,
+    pub details: String,
+    pub created_at: Timestamp,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl AuditEvent {
+    pub fn new(
+        previous: Option<&AuditEvent>,
+        event_type: AuditEventType,
+        user_id: Option<CredentialId>,
+        details: serde_json::Value,
+    ) -> Result<AuditEvent> {
+        let created_at = UNIX_EPOCH + Duration::from_micros(microseconds(SystemTime::now())? as u64);
+        let mut event = AuditEvent {
+            sequence: previous.map_or(1, |previous| previous.sequence + 1),
+            event_type: String::from(event_type.name()),
+            user_id,
+            details: details.to_string(),
+            created_at,
+            previous_hash: previous.map_or(String::from(GENESIS_HASH), |previous| previous.hash.clone()),
+            hash: String::new(),
+        };
+        event.hash = event.digest()?;
+        Ok(event)
+    }
+    pub fn digest(&self) -> Result<String> {
+        let content = json!([
+            self.sequence,
+            self.event_type,
+            self.user_id,
+            self.details,
+            microseconds(self.created_at)?.to_string(),
+            self.previous_hash,
+        ]);
+        Ok(hash::digest(&content.to_string()))
+    }
+}
+
+impl From<database::Row> for AuditEvent {
+    fn from(row: database::Row) -> AuditEvent {
+        AuditEvent {
+            sequence: row.get(0),
+            event_type: row.get(1),
+            user_id: row.get(2),
+            details: row.get(3),
+            created_at: row.get(4),
+            previous_hash: row.get(5),
+            hash: row.get(6),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub sequence: i64,
+    pub event_type: String,
+    pub user_id: Option<CredentialId>,
+    pub details: serde_json::Value,
+    pub created_at: u64,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl From<AuditEvent> for AuditRecord {
+    fn from(event: AuditEvent) -> AuditRecord {
+        AuditRecord {
+            sequence: event.sequence,
+            event_type: event.event_type,
+            user_id: event.user_id,
+            details: serde_json::from_str(&event.details).unwrap_or(serde_json::Value::Null),
+            created_at: event.created_at.duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()),
+            previous_hash: String::from(event.previous_hash.trim()),
+            hash: String::from(event.hash.trim()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditProblemKind {
+    Gap,
+    BrokenLink,
+    Edited,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuditProblem {
+    pub sequence: i64,
+    pub kind: AuditProblemKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub events: i64,
+    pub last_hash: String,
+    pub problems: Vec<AuditProblem>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditChain {
+    sequence: i64,
+    hash: String,
+    events: i64,
+    problems: Vec<AuditProblem>,
+}
+
+impl Default for AuditChain {
+    fn default() -> AuditChain {
+        AuditChain::new()
+    }
+}
+
+impl AuditChain {
+    pub fn new() -> AuditChain {
+        AuditChain {
+            sequence: 0,
+            hash: String::from(GENESIS_HASH),
+            events: 0,
+            problems: vec![],
+        }
+    }
+    fn problem(&mut self, event: &AuditEvent, kind: AuditProblemKind) {
+        self.problems.push(AuditProblem { sequence: event.sequence, kind });
+    }
+    pub fn check(&mut self, event: &AuditEvent) -> Result<()> {
+        if event.sequence != self.sequence + 1 {
+            self.problem(event, AuditProblemKind::Gap);
+        }
+        if event.previous_hash.trim() != self.hash {
+            self.problem(event, AuditProblemKind::BrokenLink);
+        }
+        if event.digest()? != event.hash.trim() {
+            self.problem(event, AuditProblemKind::Edited);
+        }
+        self.sequence = event.sequence;
+        self.hash = String::from(event.hash.trim());
+        self.events += 1;
+        Ok(())
+    }
+    pub fn last_sequence(&self) -> i64 {
+        self.sequence
+    }
+    pub fn verification(self) -> AuditVerification {
+        AuditVerification {
+            valid: self.problems.is_empty(),
+            events: self.events,
+            last_hash: self.hash,
+            problems: self.problems,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuditFilter {
+    pub user_id: Option<CredentialId>,
+    pub event_type: Option<AuditEventType>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+impl AuditFilter {
+    /// The `from` and `to` bounds as times, or `None` when either is out of range.
+    pub fn range(&self) -> Option<(Option<SystemTime>, Option<SystemTime>)> {
+        Some((filter_time(self.from)?, filter_time(self.to)?))
+    }
+}
+
+fn filter_time(seconds: Option<u64>) -> Option<Option<SystemTime>> {
+    match seconds {
+        None => Some(None),
+        Some(seconds) if seconds > MAXIMUM_FILTER_TIMESTAMP => None,
+        Some(seconds) => UNIX_EPOCH.checked_add(Duration::from_secs(seconds)).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(length: usize) -> Vec<AuditEvent> {
+        let mut events: Vec<AuditEvent> = vec![];
+        for index in 0..length {
+            let event_type = AuditEventType::ALL[index % AuditEventType::ALL.len()];
+            let event = AuditEvent::new(events.last(), event_type, Some(index as i32), json!({})).unwrap();
+            events.push(event);
+        }
+        events
+    }
+
+    fn verify(events: &[AuditEvent]) -> AuditVerification {
+        let mut audit = AuditChain::new();
+        events.iter().for_each(|event| audit.check(event).unwrap());
+        audit.verification()
+    }
+
+    #[test]
+    fn accepts_an_intact_chain() {
+        let events = chain(5);
+        let verification = verify(&events);
+        assert!(verification.valid);
+        assert_eq!(verification.events, 5);
+        assert_eq!(verification.last_hash, events[4].hash);
+    }
+
+    #[test]
+    fn detects_edited_events() {
+        let mut events = chain(3);
+        events[1].user_id = Some(1000);
+        let verification = verify(&events);
+        assert_eq!(verification.problems, vec![AuditProblem { sequence: 2, kind: AuditProblemKind::Edited }]);
+    }
+
+    #[test]
+    fn detects_removed_events() {
+        let mut events = chain(4);
+        events.remove(1);
+        let verification = verify(&events);
+        assert!(!verification.valid);
+        assert_eq!(verification.problems[0], AuditProblem { sequence: 3, kind: AuditProblemKind::Gap });
+        assert_eq!(verification.problems[1].kind, AuditProblemKind::BrokenLink);
+    }
+
+    #[test]
+    fn detects_rehashed_events_that_break_the_chain() {
+        let mut events = chain(3);
+        events[1].details = String::from("{\"forged\":true}");
+        events[1].hash = events[1].digest().unwrap();
+        let verification = verify(&events);
+        assert_eq!(verification.problems, vec![AuditProblem { sequence: 3, kind: AuditProblemKind::BrokenLink }]);
+    }
+
+    #[test]
+    fn rejects_filter_times_out_of_range() {
+        let filter = AuditFilter { from: Some(0), to: Some(MAXIMUM_FILTER_TIMESTAMP), ..AuditFilter::default() };
+        assert_eq!(filter.range(), Some((Some(UNIX_EPOCH), UNIX_EPOCH.checked_add(Duration::from_secs(MAXIMUM_FILTER_TIMESTAMP)))));
+        let filter = AuditFilter { to: Some(u64::MAX), ..AuditFilter::default() };
+        assert_eq!(filter.range(), None);
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use crate::utilities::hash;
+
+pub const PROOF_OF_WORK: &str = "sha256";
+
+pub mod query {
+    pub const FAILURES: &str = "SELECT COALESCE(MAX(attempts), 0)::bigint FROM auth.failed_attempt WHERE key = ANY($1) AND updated_at > $2";
+    pub const RECORD_FAILURE: &str = "INSERT INTO auth.failed_attempt(key) SELECT unnest($1::char(64)[])
+ ON CONFLICT (key) DO
+     UPDATE
+     SET
+      attempts = CASE WHEN failed_attempt.updated_at > $2 THEN failed_attempt.attempts + 1 ELSE 1 END,
+      updated_at = CURRENT_TIMESTAMP";
+    pub const CLEAR: &str = "DELETE FROM auth.failed_attempt WHERE key = ANY($1)";
+    pub const PRUNE: &str = "DELETE FROM auth.redeemed_challenge WHERE expires_at < CURRENT_TIMESTAMP";
+    pub const REDEEM: &str = "INSERT INTO auth.redeemed_challenge(digest, expires_at) VALUES ($1, $2) ON CONFLICT (digest) DO NOTHING";
+}
+
+/// Failures are counted against digests so raw addresses and identifiers
+/// are never stored.
+pub fn address_key(address: &str) -> String {
+    hash::digest(&format!("address:{}", address.trim()))
+}
+
+pub fn account_key(account: &str) -> String {
+    hash::digest(&format!("account:{}", account.trim().to_lowercase()))
+}
+
+pub struct Attempts {
+    pub count: i64,
+}
+
+impl From<database::Row> for Attempts {
+    fn from(row: database::Row) -> Attempts {
+        Attempts { count: row.get(0) }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Challenge {
+    pub algorithm: String,
+    pub challenge: String,
+    pub difficulty: u32,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChallengeResponse {
+    pub challenge: String,
+    pub solution: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_keys_ignore_case_and_whitespace() {
+        assert_eq!(account_key(" Someone@Example.org"), account_key("someone@example.org"));
+        assert_ne!(account_key("someone"), address_key("someone"));
+    }
+}
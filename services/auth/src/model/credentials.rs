@@ -1,5 +1,5 @@
 use crate::{
-    configuration::ACCOUNT_LOCK_DURATION_IN_SECONDS,
+    configuration::{settings::HashSettings, ACCOUNT_LOCK_DURATION_IN_SECONDS},
     utilities::hash,
     Result,
 };
@@ -8,17 +8,19 @@ use std::time::{SystemTime, Duration};
 pub type CredentialId = i32;
 
 pub mod query {
-    pub const NAME: &'static str = "SELECT id, email, name, hash, created_at, updated_at, deleted_at, locked_at FROM auth.credentials WHERE name = $1";
+    pub const NAME: &'static str = "SELECT id, email, name, hash, created_at, updated_at, deleted_at, locked_at FROM auth.credentials WHERE name_skeleton = $1 OR (name_skeleton IS NULL AND name = $2)";
     pub const EMAIL: &str = "SELECT id, email, name, hash, created_at, updated_at, deleted_at, locked_at FROM auth.credentials WHERE email = $1";
     pub const ID: &str = "SELECT id, email, name, hash, created_at, updated_at, deleted_at, locked_at FROM auth.credentials WHERE id = $1";
-    pub const CREATE: &str = "INSERT INTO auth.credentials(name, email, hash) VALUES ($1, $2, $3) RETURNING id, email, name, hash, created_at, updated_at, deleted_at, locked_at";
+    pub const CREATE: &str = "INSERT INTO auth.credentials(name, email, hash, name_skeleton) VALUES ($1, $2, $3, $4) RETURNING id, email, name, hash, created_at, updated_at, deleted_at, locked_at";
     pub const DELETED_AT: &str =
-        "SELECT deleted_at FROM auth.credentials WHERE name = $1 OR email = $2";
-    pub const UPDATE: &str = "UPDATE auth.credentials SET name = $1, hash = $2, email = $3, updated_at = CURRENT_TIMESTAMP, deleted_at = null WHERE id = $4 RETURNING id, email, name, hash, created_at, updated_at, deleted_at, locked_at";
+        "SELECT deleted_at FROM auth.credentials WHERE name_skeleton = $1 OR name = $2 OR email = $3";
+    pub const UPDATE: &str = "UPDATE auth.credentials SET name = $1, hash = $2, email = $3, name_skeleton = $5, updated_at = CURRENT_TIMESTAMP, deleted_at = null WHERE id = $4 RETURNING id, email, name, hash, created_at, updated_at, deleted_at, locked_at";
     pub const DELETE_BY_EMAIL: &str =
-        "UPDATE auth.credentials SET deleted_at = CURRENT_TIMESTAMP WHERE email = $1";
+        "UPDATE auth.credentials SET deleted_at = CURRENT_TIMESTAMP WHERE email = $1 RETURNING id";
     pub const SUSPEND: &str =
         "UPDATE auth.credentials SET locked_at = CURRENT_TIMESTAMP WHERE id = $1";
+    pub const WITHOUT_SKELETON: &str = "SELECT id, name FROM auth.credentials WHERE name_skeleton IS NULL ORDER BY id";
+    pub const SET_SKELETON: &str = "UPDATE auth.credentials SET name_skeleton = $2 WHERE id = $1 AND name_skeleton IS NULL";
     pub const UPDATE_PASSWORD_HASH: &str =  "UPDATE auth.credentials SET hash = $2 WHERE id = $1 RETURNING id, email, name, hash, created_at, updated_at, deleted_at, locked_at";
 }
 
@@ -44,8 +46,8 @@ impl Credentials {
                 .unwrap_or(false)
         }))
     }
-    pub fn password_matches(&self, password: &str) -> Result<bool> {
-        hash::authenticate(password, &self.hash)
+    pub fn password_matches(&self, settings: &HashSettings, password: &str) -> Result<bool> {
+        hash::authenticate(settings, password, &self.hash)
     }
 }
 
@@ -86,6 +88,30 @@ impl From<database::Row> for AffectedRows {
     }
 }
 
+pub struct Identifier {
+    pub id: CredentialId,
+}
+
+impl From<database::Row> for Identifier {
+    fn from(row: database::Row) -> Self {
+        Identifier { id: row.get(0) }
+    }
+}
+
+pub struct Named {
+    pub id: CredentialId,
+    pub name: String,
+}
+
+impl From<database::Row> for Named {
+    fn from(row: database::Row) -> Self {
+        Named {
+            id: row.get(0),
+            name: row.get(1),
+        }
+    }
+}
+
 #[cfg(test)]
 mod credentials_model_test {
     use crate::configuration::ACCOUNT_LOCK_DURATION_IN_SECONDS;
@@ -0,0 +1,238 @@
+use database::Timestamp;
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use crate::{
+    configuration::{settings::HashSettings, DEVICE_ALERT_TIME_PERIOD},
+    model::CredentialId,
+    utilities::hash,
+    Result,
+};
+
+pub const UNKNOWN_NETWORK: &str = "unknown";
+
+pub mod query {
+    pub const FAMILIARITY: &str = "SELECT COUNT(*),
+        COUNT(*) FILTER (WHERE fingerprint = $2),
+        COUNT(*) FILTER (WHERE network = $3),
+        COUNT(*) FILTER (WHERE country = $4)
+ FROM auth.known_device WHERE user_id = $1";
+    pub const SEEN: &str = "INSERT INTO auth.known_device(user_id, fingerprint, network, country) VALUES ($1, $2, $3, $4)
+ ON CONFLICT (user_id, fingerprint, network) DO
+     UPDATE
+     SET
+      country = COALESCE(EXCLUDED.country, known_device.country),
+      last_seen = CURRENT_TIMESTAMP";
+    pub const CREATE_ALERT: &str = "INSERT INTO auth.device_alert(id, user_id, token, fingerprint, network) VALUES ($1, $2, $3, $4, $5) RETURNING id, user_id, token, fingerprint, network, created_at, used_at";
+    pub const ALERT_BY_ID: &str = "SELECT id, user_id, token, fingerprint, network, created_at, used_at FROM auth.device_alert WHERE id = $1";
+    pub const USE_ALERT: &str = "UPDATE auth.device_alert SET used_at = CURRENT_TIMESTAMP WHERE id = $1 AND used_at IS NULL";
+    pub const FORGET: &str = "DELETE FROM auth.known_device WHERE user_id = $1 AND fingerprint = $2 AND network = $3";
+    pub const REVOKE_SESSIONS: &str = "INSERT INTO auth.session_revocation(user_id, revoked_at) VALUES ($1, CURRENT_TIMESTAMP)
+ ON CONFLICT (user_id) DO UPDATE SET revoked_at = EXCLUDED.revoked_at";
+    pub const REVOKED_AT: &str = "SELECT revoked_at FROM auth.session_revocation WHERE user_id = $1";
+    pub const LOCK_PASSWORD: &str = "UPDATE auth.credentials SET hash = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1";
+}
+
+/// Addresses are reduced to their /24 (IPv4) or /48 (IPv6) prefix so a
+/// changing address from the same provider is not reported as new.
+pub fn network(address: IpAddr) -> String {
+    match address {
+        IpAddr::V4(address) => {
+            let [a, b, c, _] = address.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        IpAddr::V6(address) => {
+            let segments = address.segments();
+            format!("{:x}:{:x}:{:x}::/48", segments[0], segments[1], segments[2])
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sighting {
+    pub fingerprint: String,
+    pub network: String,
+    pub address: Option<IpAddr>,
+    pub country: Option<String>,
+    pub agent: String,
+}
+
+impl Sighting {
+    pub fn new(agent: Option<&str>, language: Option<&str>, address: Option<IpAddr>) -> Sighting {
+        let agent = agent.unwrap_or("").trim();
+        Sighting {
+            fingerprint: hash::digest(&format!("device:{}\n{}", agent, language.unwrap_or("").trim())),
+            network: address.map_or_else(|| String::from(UNKNOWN_NETWORK), network),
+            address,
+            country: None,
+            agent: String::from(agent),
+        }
+    }
+    pub fn located(self, country: Option<String>) -> Sighting {
+        Sighting { country, ..self }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Novelty {
+    Device,
+    Network,
+    Country,
+}
+
+pub struct Familiarity {
+    pub devices: i64,
+    pub device: i64,
+    pub network: i64,
+    pub country: i64,
+}
+
+impl Familiarity {
+    /// The first sighting for an account is its baseline and never reported.
+    pub fn novelties(&self, sighting: &Sighting) -> Vec<Novelty> {
+        let mut novelties = vec![];
+        if self.devices == 0 {
+            return novelties;
+        }
+        if self.device == 0 {
+            novelties.push(Novelty::Device);
+        }
+        if self.network == 0 {
+            novelties.push(Novelty::Network);
+        }
+        if sighting.country.is_some() && self.country == 0 {
+            novelties.push(Novelty::Country);
+        }
+        novelties
+    }
+}
+
+impl From<database::Row> for Familiarity {
+    fn from(row: database::Row) -> Familiarity {
+        Familiarity {
+            devices: row.get(0),
+            device: row.get(1),
+            network: row.get(2),
+            country: row.get(3),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DeviceAlert {
+    pub id: String,
+    pub user_id: CredentialId,
+    pub token: String,
+    pub fingerprint: String,
+    pub network: String,
+    pub created_at: Timestamp,
+    pub used_at: Option<Timestamp>,
+}
+
+impl DeviceAlert {
+    pub fn expired(&self) -> Result<bool> {
+        Ok(SystemTime::now().duration_since(self.created_at)? > Duration::from_secs(DEVICE_ALERT_TIME_PERIOD))
+    }
+    pub fn used(&self) -> bool {
+        self.used_at.is_some()
+    }
+    pub fn matches_token(&self, settings: &HashSettings, token: &str) -> Result<bool> {
+        hash::authenticate(settings, token, &self.token)
+    }
+}
+
+impl From<database::Row> for DeviceAlert {
+    fn from(row: database::Row) -> DeviceAlert {
+        DeviceAlert {
+            id: row.get(0),
+            user_id: row.get(1),
+            token: row.get(2),
+            fingerprint: row.get(3),
+            network: row.get(4),
+            created_at: row.get(5),
+            used_at: row.get(6),
+        }
+    }
+}
+
+pub struct SessionRevocation {
+    pub revoked_at: Timestamp,
+}
+
+impl SessionRevocation {
+    /// Tokens carry whole seconds, so one issued in the same second as the
+    /// revocation is treated as revoked.
+    pub fn revokes(&self, issued_at: usize) -> bool {
+        self.revoked_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(true, |revoked_at| issued_at as u64 <= revoked_at.as_secs())
+    }
+}
+
+impl From<database::Row> for SessionRevocation {
+    fn from(row: database::Row) -> SessionRevocation {
+        SessionRevocation { revoked_at: row.get(0) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use std::{net::Ipv4Addr, ops::Sub};
+
+    fn familiarity(devices: i64, device: i64, network: i64, country: i64) -> Familiarity {
+        Familiarity { devices, device, network, country }
+    }
+
+    #[test]
+    fn network_keeps_only_the_address_prefix() {
+        assert_eq!(network("203.0.113.27".parse().unwrap()), "203.0.113.0/24");
+        assert_eq!(network("2001:db8:85a3::8a2e:370:7334".parse().unwrap()), "2001:db8:85a3::/48");
+    }
+
+    #[test]
+    fn sightings_from_the_same_browser_share_a_fingerprint() {
+        let address = Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 27)));
+        let first = Sighting::new(Some("Firefox"), Some("en-NZ"), address);
+        let second = Sighting::new(Some("Firefox "), Some("en-NZ"), None);
+        assert_eq!(first.fingerprint, second.fingerprint);
+        assert_ne!(first.fingerprint, Sighting::new(Some("Chrome"), Some("en-NZ"), address).fingerprint);
+        assert_eq!(second.network, UNKNOWN_NETWORK);
+    }
+
+    #[test]
+    fn novelties_are_empty_for_the_first_sighting() {
+        let sighting = fake::sighting().located(Some(String::from("NZ")));
+        assert!(familiarity(0, 0, 0, 0).novelties(&sighting).is_empty());
+    }
+
+    #[test]
+    fn novelties_report_each_unfamiliar_attribute() {
+        let sighting = fake::sighting().located(Some(String::from("NZ")));
+        assert!(familiarity(2, 1, 1, 1).novelties(&sighting).is_empty());
+        assert_eq!(familiarity(2, 0, 1, 1).novelties(&sighting), vec![Novelty::Device]);
+        assert_eq!(
+            familiarity(2, 1, 0, 0).novelties(&sighting),
+            vec![Novelty::Network, Novelty::Country]
+        );
+        assert!(familiarity(2, 1, 1, 0).novelties(&fake::sighting()).is_empty());
+    }
+
+    #[test]
+    fn expired_returns_true_once_the_alert_period_has_passed() {
+        let mut alert = fake::device_alert();
+        assert!(!alert.expired().unwrap());
+        alert.created_at = SystemTime::now().sub(Duration::from_secs(DEVICE_ALERT_TIME_PERIOD + 1));
+        assert!(alert.expired().unwrap());
+    }
+
+    #[test]
+    fn revokes_tokens_issued_up_to_the_revocation() {
+        let revocation = SessionRevocation { revoked_at: UNIX_EPOCH + Duration::from_secs(1000) };
+        assert!(revocation.revokes(999));
+        assert!(revocation.revokes(1000));
+        assert!(!revocation.revokes(1001));
+    }
+}
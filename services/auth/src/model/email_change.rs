@@ -0,0 +1,118 @@
+use database::Timestamp;
+use std::time::{SystemTime, Duration};
+use crate::{
+    configuration::{settings::HashSettings, EMAIL_CHANGE_REVERT_TIME_PERIOD, EMAIL_CHANGE_TIME_PERIOD},
+    model::CredentialId,
+    utilities::hash,
+    Result,
+};
+
+pub mod query {
+    pub const GET_BY_ID: &str = "SELECT id, user_id, old_email, new_email, token, revert_token, created_at, confirmed_at, reverted_at FROM auth.email_change WHERE id = $1";
+    pub const CREATE: &str = "INSERT INTO auth.email_change(id, user_id, old_email, new_email, token, revert_token) VALUES($1, $2, $3, $4, $5, $6) RETURNING id, user_id, old_email, new_email, token, revert_token, created_at, confirmed_at, reverted_at";
+    pub const CANCEL_PENDING: &str = "DELETE FROM auth.email_change WHERE user_id = $1 AND confirmed_at IS NULL AND reverted_at IS NULL";
+    pub const CONFIRM: &str = "UPDATE auth.email_change SET confirmed_at = CURRENT_TIMESTAMP WHERE id = $1 AND confirmed_at IS NULL AND reverted_at IS NULL";
+    pub const REVERT: &str = "UPDATE auth.email_change SET reverted_at = CURRENT_TIMESTAMP WHERE id = $1 AND reverted_at IS NULL";
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EmailChange {
+    pub id: String,
+    pub user_id: CredentialId,
+    pub old_email: String,
+    pub new_email: String,
+    pub token: String,
+    pub revert_token: String,
+    pub created_at: Timestamp,
+    pub confirmed_at: Option<Timestamp>,
+    pub reverted_at: Option<Timestamp>,
+}
+
+impl EmailChange {
+    pub fn expired(&self) -> Result<bool> {
+        Ok(SystemTime::now().duration_since(self.created_at)? > Duration::from_secs(EMAIL_CHANGE_TIME_PERIOD))
+    }
+    pub fn revert_expired(&self) -> Result<bool> {
+        Ok(SystemTime::now().duration_since(self.created_at)? > Duration::from_secs(EMAIL_CHANGE_REVERT_TIME_PERIOD))
+    }
+    pub fn confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+    pub fn reverted(&self) -> bool {
+        self.reverted_at.is_some()
+    }
+    pub fn matches_token(&self, settings: &HashSettings, token: &str) -> Result<bool> {
+        hash::authenticate(settings, token, &self.token)
+    }
+    pub fn matches_revert_token(&self, settings: &HashSettings, token: &str) -> Result<bool> {
+        hash::authenticate(settings, token, &self.revert_token)
+    }
+}
+
+impl From<database::Row> for EmailChange {
+    fn from(row: database::Row) -> EmailChange {
+        EmailChange {
+            id: row.get(0),
+            user_id: row.get(1),
+            old_email: row.get(2),
+            new_email: row.get(3),
+            token: row.get(4),
+            revert_token: row.get(5),
+            created_at: row.get(6),
+            confirmed_at: row.get(7),
+            reverted_at: row.get(8),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{hash, test::fake};
+    use crate::configuration::{EMAIL_CHANGE_REVERT_TIME_PERIOD, EMAIL_CHANGE_TIME_PERIOD};
+    use std::time::{SystemTime, Duration};
+    use std::ops::Sub;
+
+    #[test]
+    fn expired_returns_true_if_the_change_has_expired() {
+        let mut record = fake::email_change();
+        record.created_at = SystemTime::now().sub(Duration::from_secs(EMAIL_CHANGE_TIME_PERIOD + 1));
+        assert!(record.expired().unwrap())
+    }
+
+    #[test]
+    fn expired_returns_false_if_the_change_has_not_expired() {
+        let record = fake::email_change();
+        assert!(!record.expired().unwrap())
+    }
+
+    #[test]
+    fn revert_expired_outlasts_confirmation() {
+        let mut record = fake::email_change();
+        record.created_at = SystemTime::now().sub(Duration::from_secs(EMAIL_CHANGE_TIME_PERIOD + 1));
+        assert!(!record.revert_expired().unwrap());
+        record.created_at = SystemTime::now().sub(Duration::from_secs(EMAIL_CHANGE_REVERT_TIME_PERIOD + 1));
+        assert!(record.revert_expired().unwrap());
+    }
+
+    #[test]
+    fn matches_token_only_accepts_the_confirmation_token() {
+        let settings = &fake::settings().hash;
+        let (token, revert_token) = (hash::token(), hash::token());
+        let mut record = fake::email_change();
+        record.token = hash::generate(settings, &token).unwrap();
+        record.revert_token = hash::generate(settings, &revert_token).unwrap();
+        assert!(record.matches_token(settings, &token).unwrap());
+        assert!(!record.matches_token(settings, &revert_token).unwrap());
+    }
+
+    #[test]
+    fn matches_revert_token_only_accepts_the_revert_token() {
+        let settings = &fake::settings().hash;
+        let (token, revert_token) = (hash::token(), hash::token());
+        let mut record = fake::email_change();
+        record.token = hash::generate(settings, &token).unwrap();
+        record.revert_token = hash::generate(settings, &revert_token).unwrap();
+        assert!(record.matches_revert_token(settings, &revert_token).unwrap());
+        assert!(!record.matches_revert_token(settings, &token).unwrap());
+    }
+}
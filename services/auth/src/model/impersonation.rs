@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use crate::model::CredentialId;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImpersonationRequest {
+    pub user_id: CredentialId,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImpersonationResponse {
+    pub user_id: CredentialId,
+    pub actor: CredentialId,
+    pub expires_at: usize,
+    pub token: String,
+}
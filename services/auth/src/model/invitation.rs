@@ -0,0 +1,124 @@
+use database::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::model::CredentialId;
+
+pub const MAXIMUM_ROLE_LENGTH: usize = 32;
+
+pub mod query {
+    pub const CREATE: &str = "INSERT INTO auth.invitation(token, email, role, created_by, expires_at) VALUES ($1, $2, $3, $4, $5) RETURNING id, token, email, role, created_by, created_at, expires_at, used_at, used_by";
+    pub const BY_TOKEN: &str = "SELECT id, token, email, role, created_by, created_at, expires_at, used_at, used_by FROM auth.invitation WHERE token = $1";
+    pub const CLAIM: &str = "UPDATE auth.invitation SET used_at = CURRENT_TIMESTAMP WHERE id = $1 AND used_at IS NULL AND expires_at > CURRENT_TIMESTAMP";
+    pub const ACCEPT: &str = "UPDATE auth.invitation SET used_by = $2 WHERE id = $1";
+    pub const ASSIGN_ROLE: &str = "INSERT INTO auth.credential_role(user_id, role) VALUES ($1, $2) ON CONFLICT (user_id) DO UPDATE SET role = EXCLUDED.role, assigned_at = CURRENT_TIMESTAMP";
+}
+
+pub fn valid_role(role: &str) -> bool {
+    !role.is_empty()
+        && role.len() <= MAXIMUM_ROLE_LENGTH
+        && role
+            .chars()
+            .all(|character| character.is_ascii_lowercase() || character.is_ascii_digit() || character == '_' || character == '-')
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Invitation {
+    pub id: i32,
+    pub token: String,
+    pub email: String,
+    pub role: Option<String>,
+    pub created_by: CredentialId,
+    pub created_at: Timestamp,
+    pub expires_at: Timestamp,
+    pub used_at: Option<Timestamp>,
+    pub used_by: Option<CredentialId>,
+}
+
+impl Invitation {
+    pub fn expired(&self) -> bool {
+        SystemTime::now() >= self.expires_at
+    }
+    pub fn used(&self) -> bool {
+        self.used_at.is_some()
+    }
+    pub fn for_email(&self, email: &str) -> bool {
+        self.email.trim().eq_ignore_ascii_case(email.trim())
+    }
+}
+
+impl From<database::Row> for Invitation {
+    fn from(row: database::Row) -> Invitation {
+        Invitation {
+            id: row.get(0),
+            token: row.get(1),
+            email: row.get(2),
+            role: row.get(3),
+            created_by: row.get(4),
+            created_at: row.get(5),
+            expires_at: row.get(6),
+            used_at: row.get(7),
+            used_by: row.get(8),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InvitationRequest {
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InvitationResponse {
+    pub id: i32,
+    pub email: String,
+    pub role: Option<String>,
+    pub expires_at: u64,
+    pub token: String,
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(invitation: Invitation) -> InvitationResponse {
+        InvitationResponse {
+            id: invitation.id,
+            email: invitation.email,
+            role: invitation.role,
+            expires_at: invitation.expires_at.duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()),
+            token: invitation.token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use std::{ops::Sub, time::Duration};
+
+    #[test]
+    fn expired_returns_true_once_the_expiry_has_passed() {
+        let mut invitation = fake::invitation();
+        assert!(!invitation.expired());
+        invitation.expires_at = SystemTime::now().sub(Duration::from_secs(1));
+        assert!(invitation.expired());
+    }
+
+    #[test]
+    fn for_email_ignores_case_and_whitespace() {
+        let mut invitation = fake::invitation();
+        invitation.email = String::from("Council@Example.org");
+        assert!(invitation.for_email(" council@example.org"));
+        assert!(!invitation.for_email("someone@example.org"));
+    }
+
+    #[test]
+    fn valid_role_accepts_lowercase_identifiers_only() {
+        assert!(valid_role("council_member"));
+        assert!(!valid_role(""));
+        assert!(!valid_role("Council Member"));
+        assert!(!valid_role(&"a".repeat(MAXIMUM_ROLE_LENGTH + 1)));
+    }
+}
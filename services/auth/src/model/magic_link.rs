@@ -0,0 +1,93 @@
+use database::Timestamp;
+use std::time::{SystemTime, Duration};
+use crate::{
+    configuration::{settings::HashSettings, MAGIC_LINK_TIME_PERIOD},
+    model::CredentialId,
+    utilities::hash,
+    Result,
+};
+
+pub mod query {
+    pub const GET_BY_ID: &str = "SELECT id, user_id, token, email, created_at, used_at FROM auth.magic_link WHERE id = $1";
+    pub const CREATE: &str = "INSERT INTO auth.magic_link(id, user_id, token, email) VALUES($1, $2, $3, $4) RETURNING id, user_id, token, email, created_at, used_at";
+    pub const CONSUME: &str = "UPDATE auth.magic_link SET used_at = CURRENT_TIMESTAMP WHERE id = $1 AND used_at IS NULL";
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MagicLink {
+    pub id: String,
+    pub user_id: CredentialId,
+    pub token: String,
+    pub email: String,
+    pub created_at: Timestamp,
+    pub used_at: Option<Timestamp>,
+}
+
+impl MagicLink {
+    pub fn expired(&self) -> Result<bool> {
+        Ok(SystemTime::now().duration_since(self.created_at)? > Duration::from_secs(MAGIC_LINK_TIME_PERIOD))
+    }
+    pub fn used(&self) -> bool {
+        self.used_at.is_some()
+    }
+    pub fn matches_token(&self, settings: &HashSettings, token: &str) -> Result<bool> {
+        hash::authenticate(settings, token, &self.token)
+    }
+}
+
+impl From<database::Row> for MagicLink {
+    fn from(row: database::Row) -> MagicLink {
+        MagicLink {
+            id: row.get(0),
+            user_id: row.get(1),
+            token: row.get(2),
+            email: row.get(3),
+            created_at: row.get(4),
+            used_at: row.get(5),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{hash, test::fake};
+    use crate::configuration::MAGIC_LINK_TIME_PERIOD;
+    use std::time::{SystemTime, Duration};
+    use std::ops::Sub;
+
+    #[test]
+    fn expired_returns_true_if_the_link_has_expired() {
+        let mut record = fake::magic_link();
+        record.created_at = SystemTime::now().sub(Duration::from_secs(MAGIC_LINK_TIME_PERIOD + 1));
+        assert!(record.expired().unwrap())
+    }
+
+    #[test]
+    fn expired_returns_false_if_the_link_has_not_expired() {
+        let record = fake::magic_link();
+        assert!(!record.expired().unwrap())
+    }
+
+    #[test]
+    fn used_returns_true_once_the_link_has_been_used() {
+        let mut record = fake::magic_link();
+        record.used_at = Some(SystemTime::now());
+        assert!(record.used())
+    }
+
+    #[test]
+    fn matches_token_returns_true_if_the_hashed_token_is_valid() {
+        let settings = &fake::settings().hash;
+        let token = hash::token();
+        let mut record = fake::magic_link();
+        record.token = hash::generate(settings, &token).unwrap();
+        assert!(record.matches_token(settings, &token).unwrap())
+    }
+
+    #[test]
+    fn matches_token_returns_false_if_the_hashed_token_is_invalid() {
+        let settings = &fake::settings().hash;
+        let record = fake::magic_link();
+        assert!(!record.matches_token(settings, &hash::token()).unwrap())
+    }
+}
@@ -1,51 +1,175 @@
-use crate::repository;
+use crate::{challenge, configuration::Settings, geoip, mail, repository};
+use std::marker::{Send, Sync};
 
+pub mod audit_event;
+pub mod challenge;
 pub mod credentials;
+pub mod device;
+pub mod email_change;
 mod failed_login;
+pub mod impersonation;
+pub mod invitation;
+pub mod magic_link;
+pub mod outbox;
 pub mod password_reset;
+pub mod personal_access_token;
 mod request;
 mod response;
+pub mod service_client;
 
+pub use audit_event::*;
+pub use challenge::*;
 pub use credentials::*;
 pub use database::Client;
 pub use database::Database;
 pub use database::DatabaseClient;
 pub use database::DatabaseConnection;
+pub use device::*;
+pub use email_change::*;
 pub use failed_login::*;
+pub use impersonation::*;
+pub use invitation::*;
+pub use magic_link::*;
+pub use outbox::*;
 pub use response::*;
 pub use request::*;
 pub use password_reset::*;
+pub use personal_access_token::*;
+pub use service_client::*;
 
-pub type AppServiceState = ServiceState<
-    repository::LoginHistoryRepository<DatabaseConnection>,
-    repository::CredentialsRepository<DatabaseConnection>,
-    repository::PasswordReset<DatabaseConnection>,
->;
+pub trait Dependencies: Clone + Send + Sync + 'static {
+    type LoginHistory: repository::LoginHistory;
+    type Credentials: repository::Credentials;
+    type PasswordReset: repository::PasswordResetRequest;
+    type MagicLinks: repository::MagicLinks;
+    type EmailChanges: repository::EmailChanges;
+    type Health: repository::Health;
+    type AuditLog: repository::AuditLog;
+    type Invitations: repository::Invitations;
+    type Challenges: repository::Challenges;
+    type ChallengeVerifier: challenge::Verifier;
+    type Devices: repository::Devices;
+    type ServiceClients: repository::ServiceClients;
+    type PersonalAccessTokens: repository::PersonalAccessTokens;
+    type Locator: geoip::Locator;
+    type Mailer: mail::Mailer + 'static;
+}
+
+#[derive(Clone)]
+pub struct AppDependencies;
+
+impl Dependencies for AppDependencies {
+    type LoginHistory = repository::AppLoginHistory;
+    type Credentials = repository::AppCredentials;
+    type PasswordReset = repository::AppPasswordReset;
+    type MagicLinks = repository::AppMagicLinks;
+    type EmailChanges = repository::AppEmailChanges;
+    type Health = repository::AppHealth;
+    type AuditLog = repository::AppAuditLog;
+    type Invitations = repository::AppInvitations;
+    type Challenges = repository::AppChallenges;
+    type ChallengeVerifier = challenge::AppChallengeVerifier;
+    type Devices = repository::AppDevices;
+    type ServiceClients = repository::AppServiceClients;
+    type PersonalAccessTokens = repository::AppPersonalAccessTokens;
+    type Locator = geoip::AppLocator;
+    type Mailer = mail::AppMailer;
+}
+
+pub type AppServiceState = ServiceState<AppDependencies>;
 
 #[derive(Clone)]
-pub struct ServiceState<
-    L: repository::LoginHistory,
-    C: repository::Credentials,
-    R: repository::PasswordResetRequest,
-> {
-    pub login_history: L,
-    pub credentials: C,
-    pub reset_request: R,
+pub struct ServiceState<T: Dependencies> {
+    pub login_history: T::LoginHistory,
+    pub credentials: T::Credentials,
+    pub reset_request: T::PasswordReset,
+    pub magic_links: T::MagicLinks,
+    pub email_changes: T::EmailChanges,
+    pub health: T::Health,
+    pub audit_log: T::AuditLog,
+    pub invitations: T::Invitations,
+    pub challenges: T::Challenges,
+    pub challenge_verifier: T::ChallengeVerifier,
+    pub devices: T::Devices,
+    pub service_clients: T::ServiceClients,
+    pub personal_access_tokens: T::PersonalAccessTokens,
+    pub locator: T::Locator,
+    pub mailer: T::Mailer,
+    pub settings: &'static Settings,
 }
 
-impl<L: repository::LoginHistory, C: repository::Credentials, R: repository::PasswordResetRequest> ServiceState<L, C, R> {
-    pub fn new(login_history: L, credentials: C, reset_request: R) -> ServiceState<L, C, R> {
+impl<T: Dependencies> ServiceState<T> {
+    pub fn new(
+        login_history: T::LoginHistory,
+        credentials: T::Credentials,
+        reset_request: T::PasswordReset,
+        magic_links: T::MagicLinks,
+        email_changes: T::EmailChanges,
+        health: T::Health,
+        audit_log: T::AuditLog,
+        invitations: T::Invitations,
+        challenges: T::Challenges,
+        challenge_verifier: T::ChallengeVerifier,
+        devices: T::Devices,
+        service_clients: T::ServiceClients,
+        personal_access_tokens: T::PersonalAccessTokens,
+        locator: T::Locator,
+        mailer: T::Mailer,
+        settings: &'static Settings,
+    ) -> ServiceState<T> {
         ServiceState {
             credentials,
             login_history,
             reset_request,
+            magic_links,
+            email_changes,
+            health,
+            audit_log,
+            invitations,
+            challenges,
+            challenge_verifier,
+            devices,
+            service_clients,
+            personal_access_tokens,
+            locator,
+            mailer,
+            settings,
         }
     }
 }
 
-pub fn initialize_state(db: &DatabaseConnection) -> AppServiceState {
-    let credentials_repository = repository::CredentialsRepository::new(db.clone());
+pub fn initialize_state(db: &DatabaseConnection, settings: &'static Settings) -> AppServiceState {
+    let credentials_repository = repository::CredentialsRepository::new(db.clone(), &settings.hash);
     let login_history_repository = repository::LoginHistoryRepository::new(db.clone());
-    let reset_request = repository::PasswordReset::new(db.clone());
-    ServiceState::new(login_history_repository, credentials_repository, reset_request)
+    let reset_request = repository::PasswordReset::new(db.clone(), &settings.hash);
+    let magic_links = repository::MagicLinkRepository::new(db.clone(), &settings.hash);
+    let email_changes = repository::EmailChangeRepository::new(db.clone(), &settings.hash);
+    let health = repository::HealthRepository::new(db.clone());
+    let audit_log = repository::AuditLogRepository::new(db.clone());
+    let invitations = repository::InvitationRepository::new(db.clone());
+    let challenges = repository::ChallengeRepository::new(db.clone());
+    let challenge_verifier = challenge::ProofOfWork::new(challenges.clone(), &settings.challenge);
+    let devices = repository::DeviceRepository::new(db.clone(), &settings.hash);
+    let service_clients = repository::ServiceClientRepository::new(db.clone(), &settings.hash);
+    let personal_access_tokens = repository::PersonalAccessTokenRepository::new(db.clone(), &settings.hash);
+    let locator = geoip::GeoIpDatabase::from_settings(&settings.devices);
+    let mailer = mail::MailClient::from_settings(&settings.mail);
+    ServiceState::new(
+        login_history_repository,
+        credentials_repository,
+        reset_request,
+        magic_links,
+        email_changes,
+        health,
+        audit_log,
+        invitations,
+        challenges,
+        challenge_verifier,
+        devices,
+        service_clients,
+        personal_access_tokens,
+        locator,
+        mailer,
+        settings,
+    )
 }
@@ -0,0 +1,63 @@
+use database::Timestamp;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::model::{CredentialId, Credentials};
+
+pub mod query {
+    pub const CREATE: &str = "INSERT INTO auth.outbox(event_type, aggregate_id, payload) VALUES ($1, $2, $3)";
+    pub const PENDING: &str = "SELECT id, event_type, aggregate_id, payload, created_at FROM auth.outbox WHERE published_at IS NULL ORDER BY id LIMIT $1";
+    pub const PUBLISHED: &str = "UPDATE auth.outbox SET published_at = CURRENT_TIMESTAMP WHERE id = ANY($1) AND published_at IS NULL";
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxEventType {
+    CredentialsCreated,
+    CredentialsUpdated,
+    CredentialsSuspended,
+    CredentialsDeleted,
+}
+
+impl OutboxEventType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutboxEventType::CredentialsCreated => "credentials_created",
+            OutboxEventType::CredentialsUpdated => "credentials_updated",
+            OutboxEventType::CredentialsSuspended => "credentials_suspended",
+            OutboxEventType::CredentialsDeleted => "credentials_deleted",
+        }
+    }
+}
+
+pub fn credentials_payload(credentials: &Credentials) -> serde_json::Value {
+    json!({
+        "id": credentials.id,
+        "name": credentials.name,
+        "email": credentials.email,
+    })
+}
+
+pub fn identifier_payload(id: CredentialId) -> serde_json::Value {
+    json!({ "id": id })
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub event_type: String,
+    pub aggregate_id: CredentialId,
+    pub payload: String,
+    pub created_at: Timestamp,
+}
+
+impl From<database::Row> for OutboxEvent {
+    fn from(row: database::Row) -> OutboxEvent {
+        OutboxEvent {
+            id: row.get(0),
+            event_type: row.get(1),
+            aggregate_id: row.get(2),
+            payload: row.get(3),
+            created_at: row.get(4),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use database::Timestamp;
 use std::time::{SystemTime, Duration};
 use serde::{Serialize, Deserialize};
 use crate::{
-    configuration::PASSWORD_RESET_TIME_PERIOD,
+    configuration::{settings::HashSettings, PASSWORD_RESET_TIME_PERIOD},
     utilities::hash,
     Result,
 };
@@ -26,8 +26,8 @@ impl PasswordResetRequest {
     pub fn expired(&self) -> Result<bool> {
         Ok(SystemTime::now().duration_since(self.created_at)? > Duration::from_secs(PASSWORD_RESET_TIME_PERIOD))
     }
-    pub fn matches_token(&self, token: &str) -> Result<bool> {
-        hash::authenticate(token, &self.reset_token)
+    pub fn matches_token(&self, settings: &HashSettings, token: &str) -> Result<bool> {
+        hash::authenticate(settings, token, &self.reset_token)
     }
 }
 
@@ -53,39 +53,43 @@ mod tests {
 
     #[test]
     fn expired_returns_true_if_password_request_has_expired() {
+        let settings = &fake::settings().hash;
         let key = hash::token();
         let id = hash::token();
         let mut record = fake::password_reset_request();
         record.id = id;
-        record.reset_token = hash::generate(&key).unwrap();
+        record.reset_token = hash::generate(settings, &key).unwrap();
         record.created_at = SystemTime::now().sub(Duration::from_secs(PASSWORD_RESET_TIME_PERIOD + 1));
         assert!(record.expired().unwrap())
     }
 
     #[test]
     fn expired_returns_false_if_password_request_has_not_expired() {
+        let settings = &fake::settings().hash;
         let key = hash::token();
         let id = hash::token();
         let mut record = fake::password_reset_request();
         record.id = id;
-        record.reset_token = hash::generate(&key).unwrap();
+        record.reset_token = hash::generate(settings, &key).unwrap();
         assert!(!record.expired().unwrap())
     }
 
     #[test]
     fn matches_token_returns_true_if_the_hashed_access_key_is_valid() {
+        let settings = &fake::settings().hash;
         let key = hash::token();
         let id = hash::token();
         let mut record = fake::password_reset_request();
         record.id = id;
-        record.reset_token = hash::generate(&key).unwrap();
-        assert!(record.matches_token(&key).unwrap())
+        record.reset_token = hash::generate(settings, &key).unwrap();
+        assert!(record.matches_token(settings, &key).unwrap())
     }
 
     #[test]
     fn matches_token_returns_false_if_the_hashed_access_key_is_invalid() {
+        let settings = &fake::settings().hash;
         let id = hash::token();
         let record = fake::password_reset_request();
-        assert!(!record.matches_token(&id).unwrap())
+        assert!(!record.matches_token(settings, &id).unwrap())
     }
 }
\ No newline at end of file
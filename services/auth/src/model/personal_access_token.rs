@@ -0,0 +1,161 @@
+use database::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{configuration::settings::HashSettings, constants::ONE_DAY, model::CredentialId, utilities::hash, Result};
+
+pub const PERSONAL_ACCESS_TOKEN_PREFIX: &str = "btp_pat_";
+pub const MAXIMUM_TOKEN_NAME_LENGTH: usize = 64;
+pub const MAXIMUM_TOKEN_LIFETIME: u64 = ONE_DAY * 366;
+const ID_LENGTH: usize = 32;
+
+pub mod query {
+    pub const CREATE: &str = "INSERT INTO auth.personal_access_token(id, user_id, name, token, scopes, expires_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id, user_id, name, token, scopes, created_at, expires_at, last_used_at, revoked_at";
+    pub const BY_ID: &str = "SELECT id, user_id, name, token, scopes, created_at, expires_at, last_used_at, revoked_at FROM auth.personal_access_token WHERE id = $1";
+    pub const FOR_USER: &str = "SELECT id, user_id, name, token, scopes, created_at, expires_at, last_used_at, revoked_at FROM auth.personal_access_token WHERE user_id = $1 AND revoked_at IS NULL ORDER BY created_at";
+    pub const REVOKE: &str = "UPDATE auth.personal_access_token SET revoked_at = CURRENT_TIMESTAMP WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL";
+    pub const USED: &str = "UPDATE auth.personal_access_token SET last_used_at = CURRENT_TIMESTAMP WHERE id = $1";
+}
+
+/// Splits a presented token into the id used to look it up and the secret
+/// checked against the stored hash.
+pub fn parse(token: &str) -> Option<(&str, &str)> {
+    if !token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) {
+        return None;
+    }
+    let token = &token[PERSONAL_ACCESS_TOKEN_PREFIX.len()..];
+    if token.len() <= ID_LENGTH || !token.is_char_boundary(ID_LENGTH) {
+        return None;
+    }
+    Some(token.split_at(ID_LENGTH))
+}
+
+pub fn encode(id: &str, secret: &str) -> String {
+    format!("{}{}{}", PERSONAL_ACCESS_TOKEN_PREFIX, id, secret)
+}
+
+fn seconds(time: Timestamp) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PersonalAccessToken {
+    pub id: String,
+    pub user_id: CredentialId,
+    pub name: String,
+    pub token: String,
+    pub scopes: Vec<String>,
+    pub created_at: Timestamp,
+    pub expires_at: Option<Timestamp>,
+    pub last_used_at: Option<Timestamp>,
+    pub revoked_at: Option<Timestamp>,
+}
+
+impl PersonalAccessToken {
+    pub fn expired(&self) -> bool {
+        self.expires_at.map_or(false, |expires_at| SystemTime::now() >= expires_at)
+    }
+    pub fn revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+    pub fn matches_secret(&self, settings: &HashSettings, secret: &str) -> Result<bool> {
+        hash::authenticate(settings, secret, &self.token)
+    }
+}
+
+impl From<database::Row> for PersonalAccessToken {
+    fn from(row: database::Row) -> PersonalAccessToken {
+        PersonalAccessToken {
+            id: row.get(0),
+            user_id: row.get(1),
+            name: row.get(2),
+            token: row.get(3),
+            scopes: row.get(4),
+            created_at: row.get(5),
+            expires_at: row.get(6),
+            last_used_at: row.get(7),
+            revoked_at: row.get(8),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PersonalAccessTokenRequest {
+    pub name: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PersonalAccessTokenRevocation {
+    pub id: String,
+}
+
+/// The token itself is only included in the response to its creation.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PersonalAccessTokenResponse {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+    pub last_used_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl PersonalAccessTokenResponse {
+    pub fn created(token: PersonalAccessToken) -> PersonalAccessTokenResponse {
+        let plaintext = token.token.clone();
+        PersonalAccessTokenResponse {
+            token: Some(plaintext),
+            ..PersonalAccessTokenResponse::from(token)
+        }
+    }
+}
+
+impl From<PersonalAccessToken> for PersonalAccessTokenResponse {
+    fn from(token: PersonalAccessToken) -> PersonalAccessTokenResponse {
+        PersonalAccessTokenResponse {
+            id: token.id,
+            name: token.name,
+            scopes: token.scopes,
+            created_at: seconds(token.created_at),
+            expires_at: token.expires_at.map(seconds),
+            last_used_at: token.last_used_at.map(seconds),
+            token: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use std::{ops::Sub, time::Duration};
+
+    #[test]
+    fn parses_formatted_tokens() {
+        let id = hash::token();
+        let secret = hash::token();
+        assert_eq!(parse(&encode(&id, &secret)), Some((id.as_str(), secret.as_str())));
+        assert_eq!(parse(&format!("{}{}", PERSONAL_ACCESS_TOKEN_PREFIX, id)), None);
+        assert_eq!(parse(&format!("{}{}", id, secret)), None);
+    }
+
+    #[test]
+    fn expires_only_when_an_expiry_was_set() {
+        let mut token = fake::personal_access_token();
+        assert!(!token.expired());
+        token.expires_at = Some(SystemTime::now().sub(Duration::from_secs(1)));
+        assert!(token.expired());
+    }
+
+    #[test]
+    fn only_the_creation_response_includes_the_token() {
+        let token = fake::personal_access_token();
+        assert_eq!(PersonalAccessTokenResponse::created(token.clone()).token, Some(token.token.clone()));
+        assert_eq!(PersonalAccessTokenResponse::from(token).token, None);
+    }
+}
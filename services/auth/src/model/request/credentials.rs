@@ -1,7 +1,8 @@
 use actix_web::web;
+use paperclip::actix::Apiv2Schema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Apiv2Schema)]
 pub struct CredentialsRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
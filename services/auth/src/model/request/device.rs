@@ -0,0 +1,26 @@
+use actix_web::web;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeviceAlertConfirmation {
+    pub id: String,
+    pub token: String,
+}
+
+impl DeviceAlertConfirmation {
+    pub fn new(id: &str, token: &str) -> DeviceAlertConfirmation {
+        DeviceAlertConfirmation {
+            id: String::from(id),
+            token: String::from(token),
+        }
+    }
+}
+
+impl From<web::Json<DeviceAlertConfirmation>> for DeviceAlertConfirmation {
+    fn from(json: web::Json<DeviceAlertConfirmation>) -> DeviceAlertConfirmation {
+        DeviceAlertConfirmation {
+            id: String::from(&json.id),
+            token: String::from(&json.token),
+        }
+    }
+}
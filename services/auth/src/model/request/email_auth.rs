@@ -1,8 +1,9 @@
 use crate::model;
 use actix_web::web;
+use paperclip::actix::Apiv2Schema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Apiv2Schema)]
 pub struct EmailRequest {
     pub email: String,
     pub password: String,
@@ -0,0 +1,27 @@
+use actix_web::web;
+use paperclip::actix::Apiv2Schema;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
+pub struct EmailChangeConfirmation {
+    pub id: String,
+    pub token: String,
+}
+
+impl EmailChangeConfirmation {
+    pub fn new(id: &str, token: &str) -> EmailChangeConfirmation {
+        EmailChangeConfirmation {
+            id: String::from(id),
+            token: String::from(token),
+        }
+    }
+}
+
+impl From<web::Json<EmailChangeConfirmation>> for EmailChangeConfirmation {
+    fn from(json: web::Json<EmailChangeConfirmation>) -> EmailChangeConfirmation {
+        EmailChangeConfirmation {
+            id: String::from(&json.id),
+            token: String::from(&json.token),
+        }
+    }
+}
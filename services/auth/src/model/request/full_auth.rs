@@ -1,11 +1,14 @@
 use actix_web::web;
+use paperclip::actix::Apiv2Schema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
 pub struct FullRequest {
     pub email: String,
     pub name: String,
     pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invitation: Option<String>,
 }
 
 impl FullRequest {
@@ -14,6 +17,7 @@ impl FullRequest {
             name: String::from(name),
             email: String::from(email),
             password: String::from(password),
+            invitation: None,
         }
     }
 }
@@ -24,6 +28,7 @@ impl From<web::Json<FullRequest>> for FullRequest {
             email: String::from(&json.email),
             name: String::from(&json.name),
             password: String::from(&json.password),
+            invitation: json.invitation.clone(),
         }
     }
 }
@@ -0,0 +1,115 @@
+use crate::model;
+use actix_web::web;
+use paperclip::actix::Apiv2Schema;
+use serde::{Deserialize, Serialize};
+
+const EMAIL_SEPARATOR: char = '@';
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Identifier {
+    Name(String),
+    Email(String),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq, Apiv2Schema)]
+pub struct LoginRequest {
+    #[serde(alias = "name", alias = "email")]
+    pub identifier: String,
+    pub password: String,
+}
+
+impl LoginRequest {
+    pub fn new(identifier: &str, password: &str) -> LoginRequest {
+        LoginRequest {
+            identifier: String::from(identifier),
+            password: String::from(password),
+        }
+    }
+    pub fn identifier(&self) -> Identifier {
+        let identifier = String::from(self.identifier.trim());
+        if identifier.contains(EMAIL_SEPARATOR) {
+            Identifier::Email(identifier)
+        } else {
+            Identifier::Name(identifier)
+        }
+    }
+}
+
+impl From<model::NameRequest> for LoginRequest {
+    fn from(request: model::NameRequest) -> LoginRequest {
+        LoginRequest::new(&request.name, &request.password)
+    }
+}
+
+impl From<model::EmailRequest> for LoginRequest {
+    fn from(request: model::EmailRequest) -> LoginRequest {
+        LoginRequest::new(&request.email, &request.password)
+    }
+}
+
+impl From<web::Json<LoginRequest>> for LoginRequest {
+    fn from(json: web::Json<LoginRequest>) -> LoginRequest {
+        LoginRequest {
+            identifier: String::from(&json.identifier),
+            password: String::from(&json.password),
+        }
+    }
+}
+
+#[cfg(test)]
+mod login_request_test {
+    use super::*;
+    use crate::utilities::test::fake;
+    use serde_json;
+
+    #[test]
+    fn identifies_a_name() {
+        let name = fake::user_name();
+        let request = LoginRequest::new(&name, &fake::strong_password());
+        assert_eq!(request.identifier(), Identifier::Name(name));
+    }
+
+    #[test]
+    fn identifies_an_email() {
+        let email = fake::email_address();
+        let request = LoginRequest::new(&email, &fake::strong_password());
+        assert_eq!(request.identifier(), Identifier::Email(email));
+    }
+
+    #[test]
+    fn trims_surrounding_whitespace_from_the_identifier() {
+        let email = fake::email_address();
+        let request = LoginRequest::new(&format!(" {} ", &email), &fake::strong_password());
+        assert_eq!(request.identifier(), Identifier::Email(email));
+    }
+
+    #[test]
+    fn accepts_a_name_field_in_json() {
+        let name = fake::user_name();
+        let password = fake::strong_password();
+        let json = format!("{{ \"name\": \"{}\", \"password\": \"{}\" }}", &name, &password);
+        let result: LoginRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, LoginRequest::new(&name, &password));
+    }
+
+    #[test]
+    fn accepts_an_email_field_in_json() {
+        let email = fake::email_address();
+        let password = fake::strong_password();
+        let json = format!("{{ \"email\": \"{}\", \"password\": \"{}\" }}", &email, &password);
+        let result: LoginRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, LoginRequest::new(&email, &password));
+    }
+
+    #[test]
+    fn accepts_an_identifier_field_in_json() {
+        let email = fake::email_address();
+        let password = fake::strong_password();
+        let json = format!(
+            "{{ \"identifier\": \"{}\", \"password\": \"{}\" }}",
+            &email, &password
+        );
+        let result: LoginRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, LoginRequest::new(&email, &password));
+    }
+}
@@ -0,0 +1,46 @@
+use actix_web::web;
+use paperclip::actix::Apiv2Schema;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
+pub struct MagicLinkRequest {
+    pub email: String,
+}
+
+impl MagicLinkRequest {
+    pub fn new(email: &str) -> MagicLinkRequest {
+        MagicLinkRequest { email: String::from(email) }
+    }
+}
+
+impl From<web::Json<MagicLinkRequest>> for MagicLinkRequest {
+    fn from(json: web::Json<MagicLinkRequest>) -> MagicLinkRequest {
+        MagicLinkRequest {
+            email: String::from(&json.email),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
+pub struct MagicLinkConfirmation {
+    pub id: String,
+    pub token: String,
+}
+
+impl MagicLinkConfirmation {
+    pub fn new(id: &str, token: &str) -> MagicLinkConfirmation {
+        MagicLinkConfirmation {
+            id: String::from(id),
+            token: String::from(token),
+        }
+    }
+}
+
+impl From<web::Json<MagicLinkConfirmation>> for MagicLinkConfirmation {
+    fn from(json: web::Json<MagicLinkConfirmation>) -> MagicLinkConfirmation {
+        MagicLinkConfirmation {
+            id: String::from(&json.id),
+            token: String::from(&json.token),
+        }
+    }
+}
@@ -1,16 +1,24 @@
 use actix_web::web;
 
 mod credentials;
+mod device;
 mod email_auth;
+mod email_change;
 mod full_auth;
+mod login;
+mod magic_link;
 mod name_auth;
 mod update;
 mod password_reset;
 
 use actix_web::web::Json;
 pub use credentials::CredentialsRequest;
+pub use device::*;
 pub use email_auth::*;
+pub use email_change::*;
 pub use full_auth::FullRequest;
+pub use login::*;
+pub use magic_link::*;
 pub use name_auth::NameRequest;
 pub use password_reset::*;
 pub use update::*;
@@ -1,7 +1,8 @@
 use actix_web::web;
+use paperclip::actix::Apiv2Schema;
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
 pub struct ResetConfirmation {
     pub id: String,
     pub reset_token: String,
@@ -28,7 +29,7 @@ impl From<web::Json<ResetConfirmation>> for ResetConfirmation {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
 pub struct ResetRequest {
     pub email: String,
 }
@@ -1,8 +1,9 @@
 use crate::model;
 use actix_web::web;
+use paperclip::actix::Apiv2Schema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, Apiv2Schema)]
 pub struct UpdateCredentials {
     pub auth: model::EmailRequest,
     pub credentials: model::CredentialsRequest,
@@ -0,0 +1,111 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidCredentials,
+    Suspended,
+    InvalidToken,
+    Expired,
+    Conflict,
+    WeakPassword,
+    InvalidName,
+    InvalidCsrfToken,
+    Forbidden,
+    InvitationRequired,
+    InvalidRequest,
+    ChallengeRequired,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 13] = [
+        ErrorCode::InvalidCredentials,
+        ErrorCode::Suspended,
+        ErrorCode::InvalidToken,
+        ErrorCode::Expired,
+        ErrorCode::Conflict,
+        ErrorCode::WeakPassword,
+        ErrorCode::InvalidName,
+        ErrorCode::InvalidCsrfToken,
+        ErrorCode::Forbidden,
+        ErrorCode::InvitationRequired,
+        ErrorCode::InvalidRequest,
+        ErrorCode::ChallengeRequired,
+        ErrorCode::InternalError,
+    ];
+    pub fn message(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidCredentials => "The credentials provided are invalid",
+            ErrorCode::Suspended => "This account has been suspended",
+            ErrorCode::InvalidToken => "The link or token provided is invalid",
+            ErrorCode::Expired => "The link or token provided has expired",
+            ErrorCode::Conflict => "An account with these details already exists",
+            ErrorCode::WeakPassword => "The password provided is too weak",
+            ErrorCode::InvalidName => "The name provided is not allowed",
+            ErrorCode::InvalidCsrfToken => "The request is missing a valid CSRF token",
+            ErrorCode::Forbidden => "You do not have permission to perform this action",
+            ErrorCode::InvitationRequired => "Registration requires a valid invitation for this email address",
+            ErrorCode::InvalidRequest => "The request contains an invalid field",
+            ErrorCode::ChallengeRequired => "Solve the challenge in the details and retry the request",
+            ErrorCode::InternalError => "An unexpected error occurred",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ErrorResponse {
+    pub fn new(code: ErrorCode) -> ErrorResponse {
+        ErrorResponse {
+            code,
+            message: String::from(code.message()),
+            details: None,
+        }
+    }
+    pub fn with_details(self, details: serde_json::Value) -> ErrorResponse {
+        ErrorResponse {
+            details: Some(details),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UI_ERROR_CODES: &str = include_str!("../../../../ui/src/constants/errorCodes.ts");
+
+    #[test]
+    fn serializes_codes_in_screaming_snake_case() {
+        let json = serde_json::to_string(&ErrorResponse::new(ErrorCode::InvalidCredentials)).unwrap();
+        assert!(json.contains("\"code\":\"INVALID_CREDENTIALS\""));
+        assert!(!json.contains("details"));
+    }
+
+    #[test]
+    fn every_code_is_shared_with_the_ui() {
+        for code in ErrorCode::ALL.iter() {
+            let name = serde_json::to_value(code).unwrap();
+            let name = name.as_str().unwrap();
+            assert!(
+                UI_ERROR_CODES.contains(&format!("{} = '{}'", name, name)),
+                "{} is missing from the UI error codes",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn matches_the_csrf_rejection_code() {
+        let name = serde_json::to_value(ErrorCode::InvalidCsrfToken).unwrap();
+        assert_eq!(name, csrf::INVALID_CSRF_TOKEN);
+    }
+}
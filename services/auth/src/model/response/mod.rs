@@ -1,6 +1,11 @@
+use paperclip::actix::Apiv2Schema;
 use serde::{Serialize, Deserialize};
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+mod error;
+
+pub use error::*;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
 pub struct ResetToken {
     pub reset_token: String,
     pub id: String,
@@ -13,4 +18,16 @@ impl ResetToken {
             reset_token: reset_token.to_string(),
         }
     }
-}
\ No newline at end of file
+}
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
+pub struct Session {
+    pub id: super::CredentialId,
+    pub name: String,
+    pub email: String,
+    pub issued_at: usize,
+    pub expires_at: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<super::CredentialId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+}
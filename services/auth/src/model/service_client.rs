@@ -0,0 +1,217 @@
+use database::Timestamp;
+use paperclip::actix::Apiv2Schema;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{configuration::settings::HashSettings, model::CredentialId, utilities::hash, Result};
+
+pub const CLIENT_CREDENTIALS_GRANT: &str = "client_credentials";
+pub const SERVICE_SUBJECT: &str = "service";
+pub const BEARER_TOKEN_TYPE: &str = "Bearer";
+pub const MAXIMUM_CLIENT_NAME_LENGTH: usize = 64;
+pub const MAXIMUM_SCOPE_LENGTH: usize = 64;
+pub const MAXIMUM_AUDIENCE_LENGTH: usize = 255;
+
+pub mod query {
+    pub const CREATE: &str = "INSERT INTO auth.service_client(client_id, name, secret, scopes, created_by, audiences) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id, client_id, name, secret, previous_secret, previous_secret_expires_at, scopes, created_by, created_at, rotated_at, audiences";
+    pub const BY_CLIENT_ID: &str = "SELECT id, client_id, name, secret, previous_secret, previous_secret_expires_at, scopes, created_by, created_at, rotated_at, audiences FROM auth.service_client WHERE client_id = $1";
+    pub const ROTATE: &str = "UPDATE auth.service_client SET previous_secret = secret, previous_secret_expires_at = $3, secret = $2, rotated_at = CURRENT_TIMESTAMP WHERE client_id = $1 RETURNING id, client_id, name, secret, previous_secret, previous_secret_expires_at, scopes, created_by, created_at, rotated_at, audiences";
+}
+
+pub fn valid_scope(scope: &str) -> bool {
+    !scope.is_empty()
+        && scope.len() <= MAXIMUM_SCOPE_LENGTH
+        && scope.chars().all(|character| {
+            character.is_ascii_lowercase() || character.is_ascii_digit() || "_-:.".contains(character)
+        })
+}
+
+/// Audiences are usually the URI of the API a token is meant for, so only
+/// whitespace, which would make them ambiguous in a list, is ruled out.
+pub fn valid_audience(audience: &str) -> bool {
+    !audience.is_empty()
+        && audience.len() <= MAXIMUM_AUDIENCE_LENGTH
+        && !audience.chars().any(|character| character.is_whitespace() || character.is_control())
+}
+
+fn seconds(time: Timestamp) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+/// A confidential client acting as itself rather than on behalf of a user.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ServiceClient {
+    pub id: i32,
+    pub client_id: String,
+    pub name: String,
+    pub secret: String,
+    pub previous_secret: Option<String>,
+    pub previous_secret_expires_at: Option<Timestamp>,
+    pub scopes: Vec<String>,
+    pub created_by: CredentialId,
+    pub created_at: Timestamp,
+    pub rotated_at: Option<Timestamp>,
+    pub audiences: Vec<String>,
+}
+
+impl ServiceClient {
+    /// The secret replaced by the last rotation is still accepted until its
+    /// grace period ends, so deployments can pick up the new one.
+    pub fn authenticate(&self, settings: &HashSettings, secret: &str) -> Result<bool> {
+        if hash::authenticate(settings, secret, &self.secret)? {
+            return Ok(true);
+        }
+        match (&self.previous_secret, self.previous_secret_expires_at) {
+            (Some(previous), Some(expires_at)) if SystemTime::now() < expires_at => {
+                hash::authenticate(settings, secret, previous)
+            }
+            _ => Ok(false),
+        }
+    }
+    /// Every scope the client holds when none are requested, otherwise the
+    /// requested scopes provided the client holds all of them.
+    pub fn grant(&self, requested: Option<&str>) -> Option<Vec<String>> {
+        match requested.map(str::trim).filter(|requested| !requested.is_empty()) {
+            None => Some(self.scopes.clone()),
+            Some(requested) => {
+                let scopes: Vec<String> = requested.split_whitespace().map(String::from).collect();
+                if scopes.iter().all(|scope| self.scopes.contains(scope)) {
+                    Some(scopes)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl From<database::Row> for ServiceClient {
+    fn from(row: database::Row) -> ServiceClient {
+        ServiceClient {
+            id: row.get(0),
+            client_id: row.get(1),
+            name: row.get(2),
+            secret: row.get(3),
+            previous_secret: row.get(4),
+            previous_secret_expires_at: row.get(5),
+            scopes: row.get(6),
+            created_by: row.get(7),
+            created_at: row.get(8),
+            rotated_at: row.get(9),
+            audiences: row.get(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ServiceClientRequest {
+    pub name: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub audiences: Vec<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ServiceClientRotation {
+    pub client_id: String,
+}
+
+/// Returned once when a client is created or its secret rotated; only the
+/// hash of the secret is kept.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ServiceClientCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub audiences: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_secret_expires_at: Option<u64>,
+}
+
+impl From<ServiceClient> for ServiceClientCredentials {
+    fn from(client: ServiceClient) -> ServiceClientCredentials {
+        ServiceClientCredentials {
+            client_id: client.client_id,
+            client_secret: client.secret,
+            name: client.name,
+            scopes: client.scopes,
+            audiences: client.audiences,
+            previous_secret_expires_at: client.previous_secret_expires_at.map(seconds),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: usize,
+    pub scope: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Apiv2Schema)]
+pub struct ServiceSession {
+    pub subject_type: String,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+    pub audiences: Vec<String>,
+    pub issued_at: usize,
+    pub expires_at: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use std::{ops::Sub, time::Duration};
+
+    #[test]
+    fn accepts_the_previous_secret_until_its_grace_period_ends() {
+        let settings = &fake::settings().hash;
+        let mut client = fake::service_client();
+        client.secret = hash::generate(settings, "current").unwrap();
+        client.previous_secret = Some(hash::generate(settings, "previous").unwrap());
+        client.previous_secret_expires_at = Some(SystemTime::now() + Duration::from_secs(60));
+        assert!(client.authenticate(settings, "current").unwrap());
+        assert!(client.authenticate(settings, "previous").unwrap());
+        assert!(!client.authenticate(settings, "other").unwrap());
+        client.previous_secret_expires_at = Some(SystemTime::now().sub(Duration::from_secs(1)));
+        assert!(!client.authenticate(settings, "previous").unwrap());
+    }
+
+    #[test]
+    fn grants_only_scopes_the_client_holds() {
+        let mut client = fake::service_client();
+        client.scopes = vec![String::from("bills:write"), String::from("bills:read")];
+        assert_eq!(client.grant(None), Some(client.scopes.clone()));
+        assert_eq!(client.grant(Some(" ")), Some(client.scopes.clone()));
+        assert_eq!(client.grant(Some("bills:read")), Some(vec![String::from("bills:read")]));
+        assert_eq!(client.grant(Some("bills:read users:write")), None);
+    }
+
+    #[test]
+    fn valid_audience_rejects_empty_and_whitespace() {
+        assert!(valid_audience("https://api.bythepeoples.example"));
+        assert!(!valid_audience(""));
+        assert!(!valid_audience("two audiences"));
+        assert!(!valid_audience(&"a".repeat(MAXIMUM_AUDIENCE_LENGTH + 1)));
+    }
+
+    #[test]
+    fn valid_scope_accepts_lowercase_identifiers_only() {
+        assert!(valid_scope("bills:write"));
+        assert!(!valid_scope(""));
+        assert!(!valid_scope("Bills Write"));
+        assert!(!valid_scope(&"a".repeat(MAXIMUM_SCOPE_LENGTH + 1)));
+    }
+}
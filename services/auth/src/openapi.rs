@@ -0,0 +1,555 @@
+use crate::{
+    model,
+    routes::{
+        AUDIT_ROUTE, CREDENTIALS_ROUTE, EMAIL_CHANGE_ROUTE, IMPERSONATION_ROUTE, INVITATION_ROUTE,
+        MAGIC_LINK_ROUTE, PASSWORD_RESET_ROUTE, PERSONAL_ACCESS_TOKEN_ROUTE, SERVICE_CLIENT_ROUTE, TOKEN_ROUTE,
+        VERIFICATION_ROUTE,
+    },
+};
+use paperclip::v2::schema::Apiv2Schema;
+use serde_json::{json, Map, Value};
+use status_codes::{
+    StatusCode, ACCEPTED, CONFLICT, CREATED, FORBIDDEN, GONE, INTERNAL_SERVER_ERROR, OKAY,
+    PRECONDITION_REQUIRED, SERVICE_UNAVAILABLE, UNAUTHORIZED, UNPROCESSABLE_ENTITY,
+};
+
+const OPENAPI_VERSION: &str = "3.0.3";
+const TITLE: &str = "byThePeoples authentication";
+const JSON: &str = "application/json";
+const DEFINITIONS_REFERENCE: &str = "#/definitions/";
+const COMPONENTS_REFERENCE: &str = "#/components/schemas/";
+const ERROR_RESPONSE: &str = "ErrorResponse";
+const AUDIT_VERIFICATION_PATH: &str = "/audit/verify";
+const DEVICE_DISOWN_PATH: &str = "/devices/disown";
+const SERVICE_CLIENT_ROTATE_PATH: &str = "/service-clients/rotate";
+const LIVE_PATH: &str = "/health/live";
+const READY_PATH: &str = "/health/ready";
+const IMPERSONATED: &str = "The request carries an impersonation token";
+const CHALLENGE_REQUIRED: &str = "Too many failures; retry with the challenge in the error details solved, sent as X-Challenge and X-Challenge-Solution";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+    Patch,
+}
+
+impl Method {
+    pub const ALL: [Method; 5] = [
+        Method::Get,
+        Method::Post,
+        Method::Put,
+        Method::Delete,
+        Method::Patch,
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::Get => "get",
+            Method::Post => "post",
+            Method::Put => "put",
+            Method::Delete => "delete",
+            Method::Patch => "patch",
+        }
+    }
+}
+
+pub struct Response {
+    pub status: StatusCode,
+    pub description: &'static str,
+    pub schema: Option<&'static str>,
+    pub token: bool,
+}
+
+pub struct Operation {
+    pub path: &'static str,
+    pub method: Method,
+    pub summary: &'static str,
+    pub request: Option<&'static str>,
+    pub responses: &'static [Response],
+}
+
+const fn respond(status: StatusCode, description: &'static str) -> Response {
+    Response { status, description, schema: None, token: false }
+}
+
+const fn respond_with_token(status: StatusCode, description: &'static str) -> Response {
+    Response { status, description, schema: None, token: true }
+}
+
+const fn respond_with(status: StatusCode, description: &'static str, schema: &'static str) -> Response {
+    Response { status, description, schema: Some(schema), token: false }
+}
+
+const fn error(status: StatusCode, description: &'static str) -> Response {
+    respond_with(status, description, ERROR_RESPONSE)
+}
+
+pub const OPERATIONS: &[Operation] = &[
+    Operation {
+        path: VERIFICATION_ROUTE,
+        method: Method::Post,
+        summary: "Authenticate with a name or email and a password",
+        request: Some("LoginRequest"),
+        responses: &[
+            respond_with_token(OKAY, "Authenticated"),
+            error(UNAUTHORIZED, "Invalid credentials or a suspended account"),
+            error(PRECONDITION_REQUIRED, CHALLENGE_REQUIRED),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: VERIFICATION_ROUTE,
+        method: Method::Get,
+        summary: "Verify a token, service token or personal access token sent as a bearer header or session cookie",
+        request: None,
+        responses: &[
+            respond_with(OKAY, "The session is valid, with scopes for personal access tokens; service tokens are described by a ServiceSession instead", "Session"),
+            error(UNAUTHORIZED, "The token is missing, invalid, expired or revoked"),
+        ],
+    },
+    Operation {
+        path: VERIFICATION_ROUTE,
+        method: Method::Delete,
+        summary: "Sign out by clearing the session cookie",
+        request: None,
+        responses: &[respond(OKAY, "Signed out")],
+    },
+    Operation {
+        path: CREDENTIALS_ROUTE,
+        method: Method::Post,
+        summary: "Create credentials",
+        request: Some("FullRequest"),
+        responses: &[
+            respond_with_token(CREATED, "Created"),
+            respond(ACCEPTED, "Registration is private; accepted without signing in, whether or not the email was free"),
+            error(FORBIDDEN, "The password is too weak, or a valid invitation is required"),
+            error(GONE, "The invitation has expired"),
+            error(CONFLICT, "The name or email is already in use"),
+            error(UNPROCESSABLE_ENTITY, "The name is not allowed"),
+            error(PRECONDITION_REQUIRED, CHALLENGE_REQUIRED),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: CREDENTIALS_ROUTE,
+        method: Method::Put,
+        summary: "Update credentials",
+        request: Some("UpdateCredentials"),
+        responses: &[
+            respond_with_token(OKAY, "Updated"),
+            respond_with_token(ACCEPTED, "Updated, with an email change awaiting confirmation"),
+            error(UNAUTHORIZED, "Invalid credentials or a suspended account"),
+            error(FORBIDDEN, IMPERSONATED),
+            error(UNPROCESSABLE_ENTITY, "The name is not allowed"),
+            error(CONFLICT, "Another account has the same or a confusable name"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: CREDENTIALS_ROUTE,
+        method: Method::Delete,
+        summary: "Delete credentials",
+        request: Some("EmailRequest"),
+        responses: &[
+            respond(ACCEPTED, "Deleted"),
+            error(UNAUTHORIZED, "Invalid credentials or a suspended account"),
+            error(FORBIDDEN, IMPERSONATED),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: PASSWORD_RESET_ROUTE,
+        method: Method::Post,
+        summary: "Request a password reset",
+        request: Some("ResetRequest"),
+        responses: &[
+            respond_with(ACCEPTED, "Requested", "ResetToken"),
+            error(PRECONDITION_REQUIRED, CHALLENGE_REQUIRED),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: PASSWORD_RESET_ROUTE,
+        method: Method::Put,
+        summary: "Reset a password",
+        request: Some("ResetConfirmation"),
+        responses: &[
+            respond(ACCEPTED, "Processed"),
+            error(FORBIDDEN, "The password is too weak, or the request carries an impersonation token"),
+            error(GONE, "The reset request has expired"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: MAGIC_LINK_ROUTE,
+        method: Method::Post,
+        summary: "Email a single use sign in link",
+        request: Some("MagicLinkRequest"),
+        responses: &[
+            respond(ACCEPTED, "Requested, whether or not the email belongs to an account"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: MAGIC_LINK_ROUTE,
+        method: Method::Put,
+        summary: "Sign in with a magic link token",
+        request: Some("MagicLinkConfirmation"),
+        responses: &[
+            respond_with_token(OKAY, "Authenticated"),
+            error(UNAUTHORIZED, "The token is invalid, or the account is suspended"),
+            error(GONE, "The link has expired"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: EMAIL_CHANGE_ROUTE,
+        method: Method::Put,
+        summary: "Confirm an email change with the token sent to the new address",
+        request: Some("EmailChangeConfirmation"),
+        responses: &[
+            respond_with_token(OKAY, "The email was changed"),
+            error(UNAUTHORIZED, "The token is invalid"),
+            error(GONE, "The change has expired"),
+            error(CONFLICT, "The new email is already in use"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: EMAIL_CHANGE_ROUTE,
+        method: Method::Delete,
+        summary: "Revert an email change with the token sent to the previous address",
+        request: Some("EmailChangeConfirmation"),
+        responses: &[
+            respond(OKAY, "The previous email was restored"),
+            error(UNAUTHORIZED, "The token is invalid"),
+            error(GONE, "The revert window has passed"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: AUDIT_ROUTE,
+        method: Method::Get,
+        summary: "List audit events, filtered by user_id, event_type and a from/to range in unix seconds",
+        request: None,
+        responses: &[
+            respond(OKAY, "Matching audit events, oldest first"),
+            error(UNAUTHORIZED, "The token is missing, invalid or expired"),
+            error(FORBIDDEN, "The user is not an administrator"),
+            error(UNPROCESSABLE_ENTITY, "The from or to time is out of range"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: AUDIT_VERIFICATION_PATH,
+        method: Method::Get,
+        summary: "Verify the audit log hash chain",
+        request: None,
+        responses: &[
+            respond(OKAY, "The verification result, listing any gaps or edits"),
+            error(UNAUTHORIZED, "The token is missing, invalid or expired"),
+            error(FORBIDDEN, "The user is not an administrator"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: INVITATION_ROUTE,
+        method: Method::Post,
+        summary: "Invite an email address to register, optionally with a role",
+        request: None,
+        responses: &[
+            respond(CREATED, "The invitation, including its token, which is also emailed to the invitee"),
+            error(UNAUTHORIZED, "The token is missing, invalid or expired"),
+            error(FORBIDDEN, "The user is not an administrator"),
+            error(UNPROCESSABLE_ENTITY, "The email or role is invalid"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: DEVICE_DISOWN_PATH,
+        method: Method::Post,
+        summary: "Report a new device sign in as not yours, revoking every session and requiring a password reset",
+        request: None,
+        responses: &[
+            respond(OKAY, "Sessions revoked and the session cookie cleared"),
+            error(UNAUTHORIZED, "The alert id or token is invalid"),
+            error(GONE, "The alert has expired or was already used"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: IMPERSONATION_ROUTE,
+        method: Method::Post,
+        summary: "Issue a short lived token for a user, naming the admin in its act claim; every issuance is audited",
+        request: None,
+        responses: &[
+            respond(CREATED, "The impersonation token and its expiry"),
+            error(UNAUTHORIZED, "The token is missing, invalid or expired"),
+            error(FORBIDDEN, "The user is not an administrator, or is already impersonating"),
+            error(UNPROCESSABLE_ENTITY, "The user does not exist"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: SERVICE_CLIENT_ROUTE,
+        method: Method::Post,
+        summary: "Register a confidential service client with a name and scopes",
+        request: None,
+        responses: &[
+            respond(CREATED, "The client id and secret; the secret is only ever shown here"),
+            error(UNAUTHORIZED, "The token is missing, invalid or expired"),
+            error(FORBIDDEN, "The user is not an administrator"),
+            error(UNPROCESSABLE_ENTITY, "The name or a scope is invalid"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: SERVICE_CLIENT_ROTATE_PATH,
+        method: Method::Post,
+        summary: "Rotate a service client's secret; the previous secret is accepted until the grace period ends",
+        request: None,
+        responses: &[
+            respond(OKAY, "The new secret and when the previous one stops being accepted"),
+            error(UNAUTHORIZED, "The token is missing, invalid or expired"),
+            error(FORBIDDEN, "The user is not an administrator"),
+            error(UNPROCESSABLE_ENTITY, "The client does not exist"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: TOKEN_ROUTE,
+        method: Method::Post,
+        summary: "Client credentials grant: a form encoded grant_type, client_id, client_secret and optional scope",
+        request: None,
+        responses: &[
+            respond(OKAY, "A bearer access token for the client, limited to the granted scopes"),
+            error(UNAUTHORIZED, "The client id or secret is invalid"),
+            error(UNPROCESSABLE_ENTITY, "The grant type is unsupported or a scope is not held by the client"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: PERSONAL_ACCESS_TOKEN_ROUTE,
+        method: Method::Post,
+        summary: "Create a named personal access token with scopes and an optional expires_in in seconds, at most 366 days",
+        request: None,
+        responses: &[
+            respond(CREATED, "The token; it is only ever shown here"),
+            error(UNAUTHORIZED, "The token is missing, invalid, expired or revoked"),
+            error(FORBIDDEN, IMPERSONATED),
+            error(UNPROCESSABLE_ENTITY, "The name, a scope or the expiry is invalid"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: PERSONAL_ACCESS_TOKEN_ROUTE,
+        method: Method::Get,
+        summary: "List the signed in user's personal access tokens that have not been revoked",
+        request: None,
+        responses: &[
+            respond(OKAY, "The tokens without their secrets, oldest first"),
+            error(UNAUTHORIZED, "The token is missing, invalid, expired or revoked"),
+            error(FORBIDDEN, IMPERSONATED),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },
+    Operation {
+        path: PERSONAL_ACCESS_TOKEN_ROUTE,
+        method: Method::Delete,
+        summary: "Revoke one of the signed in user's personal access tokens by id",
+        request: None,
+        responses: &[
+            respond(ACCEPTED, "Revoked"),
+            error(UNAUTHORIZED, "The token is missing, invalid, expired or revoked"),
+            error(FORBIDDEN, IMPERSONATED),
+            error(UNPROCESSABLE_ENTITY, "The user has no such token"),
+            error(INTERNAL_SERVER_ERROR, "Unexpected error"),
+        ],
+    },    Operation {
+        path: LIVE_PATH,
+        method: Method::Get,
+        summary: "Liveness probe",
+        request: None,
+        responses: &[respond(OKAY, "The service is running")],
+    },
+    Operation {
+        path: READY_PATH,
+        method: Method::Get,
+        summary: "Readiness probe, checking the database connection",
+        request: None,
+        responses: &[
+            respond(OKAY, "The service and its database are up"),
+            respond(SERVICE_UNAVAILABLE, "The database is unreachable"),
+        ],
+    },
+];
+
+fn components_reference(value: Value) -> Value {
+    match value {
+        Value::String(reference) if reference.starts_with(DEFINITIONS_REFERENCE) => Value::String(
+            reference.replacen(DEFINITIONS_REFERENCE, COMPONENTS_REFERENCE, 1),
+        ),
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (key, components_reference(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(components_reference).collect()),
+        value => value,
+    }
+}
+
+fn schema<T: Apiv2Schema>() -> Value {
+    serde_json::to_value(T::raw_schema()).map_or(Value::Null, components_reference)
+}
+
+fn error_schema() -> Value {
+    let codes: Vec<Value> = model::ErrorCode::ALL
+        .iter()
+        .filter_map(|code| serde_json::to_value(code).ok())
+        .collect();
+    json!({
+        "type": "object",
+        "required": ["code", "message"],
+        "properties": {
+            "code": { "type": "string", "enum": codes },
+            "message": { "type": "string" },
+            "details": { "type": "object" },
+        },
+    })
+}
+
+pub fn schemas() -> Map<String, Value> {
+    let mut schemas = Map::new();
+    schemas.insert(String::from("LoginRequest"), schema::<model::LoginRequest>());
+    schemas.insert(String::from("FullRequest"), schema::<model::FullRequest>());
+    schemas.insert(String::from("EmailRequest"), schema::<model::EmailRequest>());
+    schemas.insert(String::from("CredentialsRequest"), schema::<model::CredentialsRequest>());
+    schemas.insert(String::from("UpdateCredentials"), schema::<model::UpdateCredentials>());
+    schemas.insert(String::from("ResetRequest"), schema::<model::ResetRequest>());
+    schemas.insert(String::from("ResetConfirmation"), schema::<model::ResetConfirmation>());
+    schemas.insert(String::from("ResetToken"), schema::<model::ResetToken>());
+    schemas.insert(String::from("MagicLinkRequest"), schema::<model::MagicLinkRequest>());
+    schemas.insert(String::from("MagicLinkConfirmation"), schema::<model::MagicLinkConfirmation>());
+    schemas.insert(String::from("EmailChangeConfirmation"), schema::<model::EmailChangeConfirmation>());
+    schemas.insert(String::from("Session"), schema::<model::Session>());
+    schemas.insert(String::from("ServiceSession"), schema::<model::ServiceSession>());
+    schemas.insert(String::from(ERROR_RESPONSE), error_schema());
+    schemas
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("{}{}", COMPONENTS_REFERENCE, name) })
+}
+
+fn response(response: &Response) -> Value {
+    let mut value = json!({ "description": response.description });
+    if let Some(schema) = response.schema {
+        value["content"] = json!({ JSON: { "schema": reference(schema) } });
+    }
+    if response.token {
+        value["headers"] = json!({
+            "Authorization": {
+                "description": "Token identifying the authenticated user, unless sessions are cookie only",
+                "schema": { "type": "string" },
+            },
+            "Set-Cookie": {
+                "description": "HttpOnly session cookie holding the same token, when cookie sessions are enabled",
+                "schema": { "type": "string" },
+            },
+        });
+    }
+    value
+}
+
+fn operation(operation: &Operation) -> Value {
+    let responses: Map<String, Value> = operation
+        .responses
+        .iter()
+        .map(|status| (status.status.to_string(), response(status)))
+        .collect();
+    let mut value = json!({
+        "summary": operation.summary,
+        "responses": responses,
+    });
+    if let Some(request) = operation.request {
+        value["requestBody"] = json!({
+            "required": true,
+            "content": { JSON: { "schema": reference(request) } },
+        });
+    }
+    value
+}
+
+pub fn specification() -> Value {
+    let mut paths = Map::new();
+    for documented in OPERATIONS {
+        let path = paths
+            .entry(documented.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        path[documented.method.name()] = operation(documented);
+    }
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": TITLE,
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": { "schemas": schemas() },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references(value: &Value, found: &mut Vec<String>) {
+        match value {
+            Value::Object(object) => object.iter().for_each(|(key, value)| match value {
+                Value::String(reference) if key == "$ref" => found.push(reference.clone()),
+                value => references(value, found),
+            }),
+            Value::Array(values) => values.iter().for_each(|value| references(value, found)),
+            _ => (),
+        }
+    }
+
+    #[test]
+    fn documents_every_operation() {
+        let spec = specification();
+        for documented in OPERATIONS {
+            assert!(spec["paths"][documented.path][documented.method.name()].is_object());
+        }
+    }
+
+    #[test]
+    fn every_reference_resolves_to_a_component() {
+        let spec = specification();
+        let mut found = vec![];
+        references(&spec, &mut found);
+        assert!(!found.is_empty());
+        for reference in found {
+            let name = reference.trim_start_matches(COMPONENTS_REFERENCE);
+            assert!(
+                spec["components"]["schemas"][name].is_object(),
+                "{} is not a documented schema",
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn request_schemas_include_model_fields() {
+        let schemas = schemas();
+        for field in ["email", "name", "password"].iter() {
+            assert!(schemas["FullRequest"]["properties"][field].is_object());
+        }
+        for field in ["id", "reset_token", "password"].iter() {
+            assert!(schemas["ResetConfirmation"]["properties"][field].is_object());
+        }
+    }
+}
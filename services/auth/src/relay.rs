@@ -0,0 +1,169 @@
+use crate::{configuration::settings::OutboxSettings, error::Error, model, repository, Result};
+use actix_web::{error::BlockingError, web};
+use logging::{debug, warn};
+use std::time::Duration;
+
+const FIELD_ID: &str = "id";
+const FIELD_TYPE: &str = "type";
+const FIELD_AGGREGATE_ID: &str = "aggregate_id";
+const FIELD_PAYLOAD: &str = "payload";
+
+pub trait Publisher: Clone + Send + 'static {
+    fn publish(&self, events: &[model::OutboxEvent]) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct RedisPublisher {
+    client: redis::Client,
+    stream: String,
+    max_length: usize,
+}
+
+impl RedisPublisher {
+    pub fn new(url: &str, stream: &str, max_length: usize) -> Result<RedisPublisher> {
+        Ok(RedisPublisher {
+            client: redis::Client::open(url)?,
+            stream: String::from(stream),
+            max_length,
+        })
+    }
+}
+
+impl Publisher for RedisPublisher {
+    fn publish(&self, events: &[model::OutboxEvent]) -> Result<()> {
+        let mut connection = self.client.get_connection()?;
+        let mut pipeline = redis::pipe();
+        for event in events {
+            pipeline
+                .cmd("XADD")
+                .arg(&self.stream)
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(self.max_length)
+                .arg("*")
+                .arg(FIELD_ID)
+                .arg(event.id)
+                .arg(FIELD_TYPE)
+                .arg(&event.event_type)
+                .arg(FIELD_AGGREGATE_ID)
+                .arg(event.aggregate_id)
+                .arg(FIELD_PAYLOAD)
+                .arg(&event.payload)
+                .ignore();
+        }
+        pipeline.query::<()>(&mut connection)?;
+        Ok(())
+    }
+}
+
+/// Publishes one batch of pending events and marks them as published.
+/// A failed publish leaves the whole batch pending, so consumers may see
+/// an event more than once and should deduplicate on its id.
+pub async fn relay_pending<O: repository::Outbox, P: Publisher>(
+    outbox: &O,
+    publisher: &P,
+    batch_size: i64,
+) -> Result<usize> {
+    let events = outbox.pending(batch_size).await?;
+    if events.is_empty() {
+        return Ok(0);
+    }
+    let batch = events.clone();
+    let publisher = publisher.clone();
+    web::block(move || publisher.publish(&batch))
+        .await
+        .map_err(|error| match error {
+            BlockingError::Error(error) => error,
+            BlockingError::Canceled => Error::InternalServerError(error.to_string()),
+        })?;
+    let ids: Vec<i64> = events.iter().map(|event| event.id).collect();
+    outbox.mark_published(&ids).await?;
+    Ok(events.len())
+}
+
+pub fn start<O: repository::Outbox + 'static, P: Publisher>(outbox: O, publisher: P, settings: &OutboxSettings) {
+    let poll_interval = Duration::from_millis(settings.poll_interval);
+    let batch_size = settings.batch_size;
+    actix_rt::spawn(async move {
+        loop {
+            match relay_pending(&outbox, &publisher, batch_size).await {
+                Ok(relayed) if relayed as i64 == batch_size => continue,
+                Ok(relayed) => {
+                    if relayed > 0 {
+                        debug!(relayed, "Relayed outbox events");
+                    }
+                }
+                Err(error) => warn!(error = %error, "Failed to relay outbox events"),
+            }
+            actix_rt::time::delay_for(poll_interval).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::mocks::repository::MockOutbox;
+    use std::{
+        sync::{Arc, Mutex},
+        time::SystemTime,
+    };
+
+    #[derive(Clone, Default)]
+    struct FakePublisher {
+        fails: bool,
+        published: Arc<Mutex<Vec<i64>>>,
+    }
+
+    impl Publisher for FakePublisher {
+        fn publish(&self, events: &[model::OutboxEvent]) -> Result<()> {
+            if self.fails {
+                return Err(Error::InternalServerError(String::from("testing")));
+            }
+            self.published
+                .lock()
+                .unwrap()
+                .extend(events.iter().map(|event| event.id));
+            Ok(())
+        }
+    }
+
+    fn event(id: i64) -> model::OutboxEvent {
+        model::OutboxEvent {
+            id,
+            event_type: String::from(model::OutboxEventType::CredentialsCreated.name()),
+            aggregate_id: 1,
+            payload: String::from("{}"),
+            created_at: SystemTime::now(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn publishes_pending_events_then_marks_them() {
+        let mut outbox = MockOutbox::<model::DatabaseConnection>::new();
+        outbox.pending.returns(vec![event(1), event(2)]);
+        outbox.mark_published.returns(2);
+        let publisher = FakePublisher::default();
+        assert_eq!(relay_pending(&outbox, &publisher, 10).await.unwrap(), 2);
+        assert_eq!(*publisher.published.lock().unwrap(), vec![1, 2]);
+        assert_eq!(outbox.mark_published.times_called(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn leaves_events_pending_when_publishing_fails() {
+        let mut outbox = MockOutbox::<model::DatabaseConnection>::new();
+        outbox.pending.returns(vec![event(1)]);
+        let publisher = FakePublisher { fails: true, ..FakePublisher::default() };
+        assert!(relay_pending(&outbox, &publisher, 10).await.is_err());
+        assert_eq!(outbox.mark_published.times_called(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn does_nothing_without_pending_events() {
+        let mut outbox = MockOutbox::<model::DatabaseConnection>::new();
+        outbox.pending.returns(vec![]);
+        let publisher = FakePublisher::default();
+        assert_eq!(relay_pending(&outbox, &publisher, 10).await.unwrap(), 0);
+        assert!(publisher.published.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{error::Error, model, model::audit_event, Result};
+use async_trait::async_trait;
+use std::marker::{Send, Sync};
+use tracing::instrument;
+
+const DEFAULT_QUERY_LIMIT: i64 = 100;
+const MAXIMUM_QUERY_LIMIT: i64 = 1000;
+const VERIFICATION_PAGE_SIZE: i64 = 1000;
+
+pub type AppAuditLog = AuditLogRepository<model::DatabaseConnection>;
+
+pub async fn append_audit_event(
+    transaction: &database::Transaction<'_>,
+    event_type: model::AuditEventType,
+    user_id: Option<model::CredentialId>,
+    details: serde_json::Value,
+) -> Result<()> {
+    transaction.batch(audit_event::query::LOCK).await?;
+    let last = transaction.prepare(audit_event::query::LAST).await?;
+    let previous = transaction.query::<model::AuditEvent>(&last, &[]).await?;
+    let event = model::AuditEvent::new(previous.first(), event_type, user_id, details)?;
+    transaction
+        .execute(
+            audit_event::query::CREATE,
+            &[
+                &event.sequence,
+                &event.event_type,
+                &event.user_id,
+                &event.details,
+                &event.created_at,
+                &event.previous_hash,
+                &event.hash,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditLogRepository<T: model::Database> {
+    db: T,
+}
+
+impl<T: model::Database> AuditLogRepository<T> {
+    pub fn new(db: T) -> Self { AuditLogRepository { db } }
+}
+
+#[async_trait]
+pub trait AuditLog: Send + Sync + Clone {
+    async fn query(&self, filter: &model::AuditFilter) -> Result<Vec<model::AuditEvent>>;
+    async fn verify(&self) -> Result<model::AuditVerification>;
+    async fn record(
+        &self,
+        event_type: model::AuditEventType,
+        user_id: Option<model::CredentialId>,
+        details: serde_json::Value,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: model::Database> AuditLog for AuditLogRepository<T> {
+    #[instrument(skip(self))]
+    async fn query(&self, filter: &model::AuditFilter) -> Result<Vec<model::AuditEvent>> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(audit_event::query::FILTER).await?;
+        let event_type = filter.event_type.map(|event_type| event_type.name());
+        let (from, to) = filter
+            .range()
+            .ok_or_else(|| Error::BadRequest(String::from("audit filter time out of range")))?;
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_QUERY_LIMIT)
+            .max(1)
+            .min(MAXIMUM_QUERY_LIMIT);
+        Ok(client
+            .query::<model::AuditEvent>(&stmt, &[&filter.user_id, &event_type, &from, &to, &limit])
+            .await?)
+    }
+    #[instrument(skip(self))]
+    async fn verify(&self) -> Result<model::AuditVerification> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(audit_event::query::AFTER).await?;
+        let mut chain = model::AuditChain::new();
+        loop {
+            let events = client
+                .query::<model::AuditEvent>(&stmt, &[&chain.last_sequence(), &VERIFICATION_PAGE_SIZE])
+                .await?;
+            for event in events.iter() {
+                chain.check(event)?;
+            }
+            if (events.len() as i64) < VERIFICATION_PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(chain.verification())
+    }
+    #[instrument(skip(self, details))]
+    async fn record(
+        &self,
+        event_type: model::AuditEventType,
+        user_id: Option<model::CredentialId>,
+        details: serde_json::Value,
+    ) -> Result<()> {
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        append_audit_event(&transaction, event_type, user_id, details).await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+}
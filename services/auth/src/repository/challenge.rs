@@ -0,0 +1,66 @@
+use crate::{model, model::challenge, Result};
+use async_trait::async_trait;
+use std::{
+    marker::{Send, Sync},
+    time::{Duration, SystemTime},
+};
+use tracing::instrument;
+
+pub type AppChallenges = ChallengeRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct ChallengeRepository<T: model::Database> {
+    db: T,
+}
+
+impl<T: model::Database> ChallengeRepository<T> {
+    pub fn new(db: T) -> Self { ChallengeRepository { db } }
+}
+
+#[async_trait]
+pub trait Challenges: Send + Sync + Clone {
+    async fn failures(&self, keys: &[String], window: Duration) -> Result<i64>;
+    async fn record_failure(&self, keys: &[String], window: Duration) -> Result<()>;
+    async fn clear(&self, keys: &[String]) -> Result<()>;
+    async fn redeem(&self, digest: &str, expires_at: SystemTime) -> Result<bool>;
+}
+
+#[async_trait]
+impl<T: model::Database> Challenges for ChallengeRepository<T> {
+    #[instrument(skip(self, keys))]
+    async fn failures(&self, keys: &[String], window: Duration) -> Result<i64> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(challenge::query::FAILURES).await?;
+        let since = SystemTime::now() - window;
+        Ok(client
+            .query::<model::Attempts>(&stmt, &[&keys.to_vec(), &since])
+            .await?
+            .first()
+            .map_or(0, |attempts| attempts.count))
+    }
+    #[instrument(skip(self, keys))]
+    async fn record_failure(&self, keys: &[String], window: Duration) -> Result<()> {
+        let since = SystemTime::now() - window;
+        self.db
+            .client()
+            .await?
+            .execute(challenge::query::RECORD_FAILURE, &[&keys.to_vec(), &since])
+            .await?;
+        Ok(())
+    }
+    #[instrument(skip(self, keys))]
+    async fn clear(&self, keys: &[String]) -> Result<()> {
+        self.db
+            .client()
+            .await?
+            .execute(challenge::query::CLEAR, &[&keys.to_vec()])
+            .await?;
+        Ok(())
+    }
+    #[instrument(skip(self, digest))]
+    async fn redeem(&self, digest: &str, expires_at: SystemTime) -> Result<bool> {
+        let client = self.db.client().await?;
+        client.execute(challenge::query::PRUNE, &[]).await?;
+        Ok(client.execute(challenge::query::REDEEM, &[&digest, &expires_at]).await? == 1)
+    }
+}
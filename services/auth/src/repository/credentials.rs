@@ -1,5 +1,17 @@
-use crate::{model, model::credentials, Result};
+use crate::{
+    configuration::settings::HashSettings,
+    model,
+    model::{credentials, outbox},
+    repository::{
+        accept_invitation, append_audit_event, append_outbox_event, claim_invitation, confirm_email_change,
+        request_email_change,
+    },
+    utilities::name,
+    Result,
+};
 use async_trait::async_trait;
+use serde_json::json;
+use tracing::instrument;
 use std::marker::{Send, Sync};
 
 type CredentialResults = Result<Option<model::Credentials>>;
@@ -8,6 +20,7 @@ pub type AppCredentials = CredentialsRepository<model::DatabaseConnection>;
 #[derive(Clone)]
 pub struct CredentialsRepository<T: model::Database> {
     db: T,
+    settings: HashSettings,
 }
 
 #[derive(Clone, Debug)]
@@ -18,8 +31,8 @@ pub enum CredentialStatus {
 }
 
 impl<T: model::Database> CredentialsRepository<T> {
-    pub fn new(db: T) -> CredentialsRepository<T> {
-        CredentialsRepository { db }
+    pub fn new(db: T, settings: &HashSettings) -> CredentialsRepository<T> {
+        CredentialsRepository { db, settings: settings.clone() }
     }
     async fn get_by_single_param(&self, query: &str, param: &str) -> CredentialResults {
         let client = self.db.client().await?;
@@ -31,11 +44,83 @@ impl<T: model::Database> CredentialsRepository<T> {
             .map(| credentials | credentials.clone()))
     }
     pub async fn by_name(&self, name: &str) -> CredentialResults {
-        self.get_by_single_param(credentials::query::NAME, name)
-            .await
+        self.get_by_name(name).await
+    }
+    async fn get_by_name(&self, user_name: &str) -> CredentialResults {
+        let client = self.db.client().await?;
+        let statement = client.prepare(credentials::query::NAME).await?;
+        let skeleton = name::skeleton(user_name);
+        let normalized = name::normalize(user_name);
+        Ok(client
+            .query::<model::Credentials>(&statement, &[&skeleton, &normalized])
+            .await?
+            .first()
+            .map(| credentials | credentials.clone()))
     }
 }
 
+async fn update_in(
+    transaction: &database::Transaction<'_>,
+    credentials: &model::Credentials,
+) -> Result<model::Credentials> {
+    let model::Credentials {
+        name: user_name,
+        email,
+        hash,
+        id,
+        ..
+    } = credentials;
+    let stmt = transaction.prepare(credentials::query::UPDATE).await?;
+    let skeleton = name::skeleton(user_name);
+    let updated = transaction
+        .query::<model::Credentials>(&stmt, &[&user_name, &hash, &email, &id, &skeleton])
+        .await?
+        .remove(0);
+    append_outbox_event(transaction, model::OutboxEventType::CredentialsUpdated, updated.id, outbox::credentials_payload(&updated)).await?;
+    Ok(updated)
+}
+
+async fn insert_in(
+    transaction: &database::Transaction<'_>,
+    credentials: &model::FullRequest,
+) -> Result<model::Credentials> {
+    let model::FullRequest {
+        name: user_name,
+        email,
+        password,
+        ..
+    } = credentials;
+    let stmt = transaction.prepare(credentials::query::CREATE).await?;
+    let skeleton = name::skeleton(user_name);
+    let created = transaction
+        .query::<model::Credentials>(&stmt, &[&user_name, &email, &password, &skeleton])
+        .await?
+        .remove(0);
+    append_audit_event(transaction, model::AuditEventType::AccountCreated, Some(created.id), json!({})).await?;
+    append_outbox_event(transaction, model::OutboxEventType::CredentialsCreated, created.id, outbox::credentials_payload(&created)).await?;
+    Ok(created)
+}
+
+/// Gives accounts created before names had skeletons one, oldest first.
+/// The unique index rejects a skeleton that is already taken; that account
+/// keeps none, is still matched by its exact name, and is returned so it can
+/// be reviewed. Runs with the migrations, after the index exists.
+#[instrument(skip(db))]
+pub async fn backfill_name_skeletons<T: model::Database>(db: &T) -> Result<Vec<model::CredentialId>> {
+    let client = db.client().await?;
+    let stmt = client.prepare(credentials::query::WITHOUT_SKELETON).await?;
+    let mut collisions = vec![];
+    for credentials in client.query::<credentials::Named>(&stmt, &[]).await? {
+        let skeleton = name::skeleton(&credentials.name);
+        match client.execute(credentials::query::SET_SKELETON, &[&credentials.id, &skeleton]).await {
+            Ok(_) => {}
+            Err(error) if error.is_unique_violation() => collisions.push(credentials.id),
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(collisions)
+}
+
 #[async_trait]
 pub trait Credentials: Clone + Send + Sync {
     async fn by_name(&self, name: &str) -> CredentialResults;
@@ -46,24 +131,41 @@ pub trait Credentials: Clone + Send + Sync {
         &self,
         credentials: &model::Credentials,
     ) -> Result<model::Credentials>;
+    async fn update_with_email_change(
+        &self,
+        credentials: &model::Credentials,
+        new_email: &str,
+    ) -> Result<(model::Credentials, model::EmailChange)>;
+    async fn update_with_confirmed_email_change(
+        &self,
+        credentials: &model::Credentials,
+        change_id: &str,
+    ) -> CredentialResults;
     async fn update_password_hash(&self, id: &i32, hash: &str) -> Result<model::Credentials>;
     async fn save_credentials(
         &self,
         credentials: &model::FullRequest,
     ) -> Result<model::Credentials>;
+    async fn save_invited_credentials(
+        &self,
+        credentials: &model::FullRequest,
+        invitation: &model::Invitation,
+    ) -> CredentialResults;
     async fn mark_as_deleted_by_email(&self, email: &str) -> Result<i32>;
 }
 
 #[async_trait]
 impl<T: model::Database> Credentials for CredentialsRepository<T> {
+    #[instrument(skip(self))]
     async fn by_name(&self, name: &str) -> CredentialResults {
-        self.get_by_single_param(credentials::query::NAME, name)
-            .await
+        self.get_by_name(name).await
     }
+    #[instrument(skip(self, email))]
     async fn by_email(&self, email: &str) -> CredentialResults {
         self.get_by_single_param(credentials::query::EMAIL, email)
             .await
     }
+    #[instrument(skip(self))]
     async fn by_id(&self, id: i32) -> CredentialResults {
         let client = self.db.client().await?;
         let stmt = client.prepare(credentials::query::ID).await?;
@@ -76,11 +178,13 @@ impl<T: model::Database> Credentials for CredentialsRepository<T> {
             Ok(Some(results.remove(0)))
         }
     }
-    async fn get_status(&self, name: &str, email: &str) -> Result<CredentialStatus> {
+    #[instrument(skip(self, email))]
+    async fn get_status(&self, user_name: &str, email: &str) -> Result<CredentialStatus> {
         let client = self.db.client().await?;
         let stmt = client.prepare(credentials::query::DELETED_AT).await?;
+        let skeleton = name::skeleton(user_name);
         let stored_credentials = client
-            .query::<credentials::DeletedAt>(&stmt, &[&name, &email])
+            .query::<credentials::DeletedAt>(&stmt, &[&skeleton, &user_name, &email])
             .await?;
         if stored_credentials.is_empty() {
             Ok(CredentialStatus::None)
@@ -91,53 +195,104 @@ impl<T: model::Database> Credentials for CredentialsRepository<T> {
             })
         }
     }
+    #[instrument(skip(self, credentials))]
     async fn update_credentials(
         &self,
         credentials: &model::Credentials,
     ) -> Result<model::Credentials> {
-        let model::Credentials {
-            name,
-            email,
-            hash,
-            id,
-            ..
-        } = credentials;
-        let client = self.db.client().await?;
-        let stmt = client.prepare(credentials::query::UPDATE).await?;
-        Ok(client
-            .query::<model::Credentials>(&stmt, &[&name, &hash, &email, &id])
-            .await?
-            .remove(0))
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let updated = update_in(&transaction, credentials).await?;
+        transaction.commit().await?;
+        Ok(updated)
+    }
+    /// The update and the pending email change are committed together, so
+    /// neither is kept without the other.
+    #[instrument(skip(self, credentials, new_email))]
+    async fn update_with_email_change(
+        &self,
+        credentials: &model::Credentials,
+        new_email: &str,
+    ) -> Result<(model::Credentials, model::EmailChange)> {
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let updated = update_in(&transaction, credentials).await?;
+        let change = request_email_change(&transaction, &self.settings, &updated, new_email).await?;
+        transaction.commit().await?;
+        Ok((updated, change))
     }
+    /// The change is confirmed and the new email saved together. Returns
+    /// None, and writes nothing, if the change is no longer pending.
+    #[instrument(skip(self, credentials))]
+    async fn update_with_confirmed_email_change(
+        &self,
+        credentials: &model::Credentials,
+        change_id: &str,
+    ) -> CredentialResults {
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        if !confirm_email_change(&transaction, change_id).await? {
+            return Ok(None);
+        }
+        let updated = update_in(&transaction, credentials).await?;
+        transaction.commit().await?;
+        Ok(Some(updated))
+    }
+    #[instrument(skip(self, hash))]
     async fn update_password_hash(&self, id: &i32, hash: &str) -> Result<model::Credentials> {
-        let client = &self.db.client().await?;
-        let stmt = client.prepare(credentials::query::UPDATE_PASSWORD_HASH)
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let stmt = transaction.prepare(credentials::query::UPDATE_PASSWORD_HASH)
             .await?;
-        Ok(client.query::<model::Credentials>(&stmt, &[&id, &hash]).await?.remove(0))
+        let credentials = transaction.query::<model::Credentials>(&stmt, &[&id, &hash]).await?.remove(0);
+        append_audit_event(&transaction, model::AuditEventType::PasswordChanged, Some(*id), json!({})).await?;
+        append_outbox_event(&transaction, model::OutboxEventType::CredentialsUpdated, *id, outbox::credentials_payload(&credentials)).await?;
+        transaction.commit().await?;
+        Ok(credentials)
     }
+    #[instrument(skip(self, credentials))]
     async fn save_credentials(
         &self,
         credentials: &model::FullRequest,
     ) -> Result<model::Credentials> {
-        let model::FullRequest {
-            name,
-            email,
-            password,
-        } = credentials;
-        let client = self.db.client().await?;
-        let stmt = client.prepare(credentials::query::CREATE).await?;
-        Ok(client
-            .query::<model::Credentials>(&stmt, &[&name, &email, &password])
-            .await?
-            .remove(0))
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let created = insert_in(&transaction, credentials).await?;
+        transaction.commit().await?;
+        Ok(created)
+    }
+    /// Claiming the invitation, creating the account and assigning the
+    /// invitation's role are committed together. Returns `None`, saving
+    /// nothing, when the invitation was claimed first.
+    #[instrument(skip(self, credentials, invitation))]
+    async fn save_invited_credentials(
+        &self,
+        credentials: &model::FullRequest,
+        invitation: &model::Invitation,
+    ) -> CredentialResults {
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        if !claim_invitation(&transaction, invitation.id).await? {
+            return Ok(None);
+        }
+        let created = insert_in(&transaction, credentials).await?;
+        accept_invitation(&transaction, invitation, created.id).await?;
+        transaction.commit().await?;
+        Ok(Some(created))
     }
+    #[instrument(skip(self, email))]
     async fn mark_as_deleted_by_email(&self, email: &str) -> Result<i32> {
-        let client = self.db.client().await?;
-        let stmt = client.prepare(credentials::query::DELETE_BY_EMAIL).await?;
-        Ok(client
-            .query::<credentials::AffectedRows>(&stmt, &[&email])
-            .await?
-            .first()
-            .map_or(0, |affected| affected.count))
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let stmt = transaction.prepare(credentials::query::DELETE_BY_EMAIL).await?;
+        let deleted = transaction
+            .query::<credentials::Identifier>(&stmt, &[&email])
+            .await?;
+        for credentials in deleted.iter() {
+            append_audit_event(&transaction, model::AuditEventType::AccountDeleted, Some(credentials.id), json!({})).await?;
+            append_outbox_event(&transaction, model::OutboxEventType::CredentialsDeleted, credentials.id, outbox::identifier_payload(credentials.id)).await?;
+        }
+        transaction.commit().await?;
+        Ok(deleted.len() as i32)
     }
 }
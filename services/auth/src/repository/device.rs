@@ -0,0 +1,122 @@
+use crate::{
+    configuration::settings::HashSettings,
+    model,
+    model::device,
+    repository::append_audit_event,
+    utilities::hash,
+    Result,
+};
+use async_trait::async_trait;
+use serde_json::json;
+use std::marker::{Send, Sync};
+use tracing::instrument;
+
+pub type AppDevices = DeviceRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct DeviceRepository<T: model::Database> {
+    db: T,
+    settings: HashSettings,
+}
+
+impl<T: model::Database> DeviceRepository<T> {
+    pub fn new(db: T, settings: &HashSettings) -> Self {
+        DeviceRepository { db, settings: settings.clone() }
+    }
+}
+
+#[async_trait]
+pub trait Devices: Send + Sync + Clone {
+    async fn observe(&self, user_id: model::CredentialId, sighting: &model::Sighting) -> Result<Vec<model::Novelty>>;
+    async fn alert(&self, user_id: model::CredentialId, sighting: &model::Sighting) -> Result<model::DeviceAlert>;
+    async fn alert_by_id(&self, id: &str) -> Result<Option<model::DeviceAlert>>;
+    async fn disown(&self, alert: &model::DeviceAlert) -> Result<bool>;
+    async fn revocation(&self, user_id: model::CredentialId) -> Result<Option<model::SessionRevocation>>;
+}
+
+#[async_trait]
+impl<T: model::Database> Devices for DeviceRepository<T> {
+    #[instrument(skip(self, sighting))]
+    async fn observe(&self, user_id: model::CredentialId, sighting: &model::Sighting) -> Result<Vec<model::Novelty>> {
+        let client = self.db.client().await?;
+        let familiarity = client.prepare(device::query::FAMILIARITY).await?;
+        let novelties = client
+            .query::<model::Familiarity>(
+                &familiarity,
+                &[&user_id, &sighting.fingerprint, &sighting.network, &sighting.country],
+            )
+            .await?
+            .remove(0)
+            .novelties(sighting);
+        client
+            .execute(
+                device::query::SEEN,
+                &[&user_id, &sighting.fingerprint, &sighting.network, &sighting.country],
+            )
+            .await?;
+        Ok(novelties)
+    }
+    #[instrument(skip(self, sighting))]
+    async fn alert(&self, user_id: model::CredentialId, sighting: &model::Sighting) -> Result<model::DeviceAlert> {
+        let client = self.db.client().await?;
+        let id = hash::token();
+        let token = hash::token();
+        let hashed_token = hash::generate(&self.settings, &token)?;
+        let create_alert = client.prepare(device::query::CREATE_ALERT).await?;
+        let alert = client
+            .query::<model::DeviceAlert>(
+                &create_alert,
+                &[&id, &user_id, &hashed_token, &sighting.fingerprint, &sighting.network],
+            )
+            .await?
+            .remove(0);
+        Ok(model::DeviceAlert { token, ..alert })
+    }
+    #[instrument(skip(self))]
+    async fn alert_by_id(&self, id: &str) -> Result<Option<model::DeviceAlert>> {
+        let client = self.db.client().await?;
+        let alert_by_id = client.prepare(device::query::ALERT_BY_ID).await?;
+        Ok(client
+            .query::<model::DeviceAlert>(&alert_by_id, &[&id])
+            .await?
+            .first()
+            .cloned())
+    }
+    /// Forgets the reported device, revokes every session issued so far
+    /// and replaces the password with one nobody knows, so the account can
+    /// only be recovered through a reset.
+    #[instrument(skip(self, alert))]
+    async fn disown(&self, alert: &model::DeviceAlert) -> Result<bool> {
+        let unusable_hash = hash::generate(&self.settings, &hash::token())?;
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        if transaction.execute(device::query::USE_ALERT, &[&alert.id]).await? != 1 {
+            return Ok(false);
+        }
+        transaction
+            .execute(device::query::FORGET, &[&alert.user_id, &alert.fingerprint, &alert.network])
+            .await?;
+        transaction.execute(device::query::REVOKE_SESSIONS, &[&alert.user_id]).await?;
+        transaction
+            .execute(device::query::LOCK_PASSWORD, &[&alert.user_id, &unusable_hash])
+            .await?;
+        append_audit_event(
+            &transaction,
+            model::AuditEventType::SessionsRevoked,
+            Some(alert.user_id),
+            json!({ "alert": alert.id, "network": alert.network }),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+    #[instrument(skip(self))]
+    async fn revocation(&self, user_id: model::CredentialId) -> Result<Option<model::SessionRevocation>> {
+        let client = self.db.client().await?;
+        let revoked_at = client.prepare(device::query::REVOKED_AT).await?;
+        Ok(client
+            .query::<model::SessionRevocation>(&revoked_at, &[&user_id])
+            .await?
+            .pop())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{configuration::settings::HashSettings, model, Result, utilities::hash, model::email_change};
+use async_trait::async_trait;
+use tracing::instrument;
+use std::marker::{Send, Sync};
+
+pub type AppEmailChanges = EmailChangeRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct EmailChangeRepository<T: model::Database> {
+    db: T,
+    settings: HashSettings,
+}
+
+impl<T: model::Database> EmailChangeRepository<T> {
+    pub fn new(db: T, settings: &HashSettings) -> Self {
+        EmailChangeRepository { db, settings: settings.clone() }
+    }
+}
+
+/// Replaces any pending change for the account within the caller's
+/// transaction. The returned change carries the plaintext tokens.
+pub async fn request_email_change(
+    transaction: &database::Transaction<'_>,
+    settings: &HashSettings,
+    credentials: &model::Credentials,
+    new_email: &str,
+) -> Result<model::EmailChange> {
+    let id = hash::token();
+    let token = hash::token();
+    let revert_token = hash::token();
+    let hashed_token = hash::generate(settings, &token)?;
+    let hashed_revert_token = hash::generate(settings, &revert_token)?;
+    let create_change = transaction.prepare(email_change::query::CREATE).await?;
+    transaction.execute(email_change::query::CANCEL_PENDING, &[&credentials.id]).await?;
+    let change = transaction.query::<model::EmailChange>(
+        &create_change,
+        &[&id, &credentials.id, &credentials.email, &new_email, &hashed_token, &hashed_revert_token],
+    )
+        .await?
+        .remove(0);
+    Ok(model::EmailChange {
+        token,
+        revert_token,
+        ..change
+    })
+}
+
+/// Marks a pending change as confirmed within the caller's transaction.
+/// Returns false if the change was confirmed or reverted in the meantime.
+pub async fn confirm_email_change(transaction: &database::Transaction<'_>, id: &str) -> Result<bool> {
+    Ok(transaction.execute(email_change::query::CONFIRM, &[&id]).await? == 1)
+}
+
+#[async_trait]
+pub trait EmailChanges: Send + Sync + Clone {
+    async fn request(&self, credentials: &model::Credentials, new_email: &str) -> Result<model::EmailChange>;
+    async fn by_id(&self, id: &str) -> Result<Option<model::EmailChange>>;
+    async fn revert(&self, id: &str) -> Result<bool>;
+}
+
+#[async_trait]
+impl<T: model::Database> EmailChanges for EmailChangeRepository<T> {
+    #[instrument(skip(self, credentials, new_email))]
+    async fn request(&self, credentials: &model::Credentials, new_email: &str) -> Result<model::EmailChange> {
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let change = request_email_change(&transaction, &self.settings, credentials, new_email).await?;
+        transaction.commit().await?;
+        Ok(change)
+    }
+    #[instrument(skip(self))]
+    async fn by_id(&self, id: &str) -> Result<Option<model::EmailChange>> {
+        let client = self.db.client().await?;
+        let change_by_id = client.prepare(email_change::query::GET_BY_ID).await?;
+        Ok(client.query::<model::EmailChange>(&change_by_id, &[&id])
+            .await?
+            .first()
+            .cloned())
+    }
+    #[instrument(skip(self))]
+    async fn revert(&self, id: &str) -> Result<bool> {
+        let client = self.db.client().await?;
+        Ok(client.execute(email_change::query::REVERT, &[&id]).await? == 1)
+    }
+}
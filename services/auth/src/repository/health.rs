@@ -0,0 +1,36 @@
+use crate::{model, Result};
+use async_trait::async_trait;
+use tracing::instrument;
+use std::marker::{Send, Sync};
+
+const PING: &str = "SELECT 1";
+
+pub type AppHealth = HealthRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct HealthRepository<T: model::Database> {
+    db: T,
+}
+
+impl<T: model::Database> HealthRepository<T> {
+    pub fn new(db: T) -> Self { HealthRepository { db } }
+}
+
+#[async_trait]
+pub trait Health: Send + Sync + Clone {
+    async fn database(&self) -> Result<()>;
+    fn pool(&self) -> database::PoolState;
+}
+
+#[async_trait]
+impl<T: model::Database> Health for HealthRepository<T> {
+    #[instrument(skip(self))]
+    async fn database(&self) -> Result<()> {
+        let client = self.db.client().await?;
+        client.batch(PING).await?;
+        Ok(())
+    }
+    fn pool(&self) -> database::PoolState {
+        self.db.state()
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{model, model::invitation, repository::append_audit_event, utilities::hash, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use std::{
+    marker::{Send, Sync},
+    time::{Duration, SystemTime},
+};
+use tracing::instrument;
+
+pub type AppInvitations = InvitationRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct InvitationRepository<T: model::Database> {
+    db: T,
+}
+
+impl<T: model::Database> InvitationRepository<T> {
+    pub fn new(db: T) -> Self { InvitationRepository { db } }
+}
+
+/// Marks the invitation used within the caller's transaction. Returns false
+/// when it was already used or has expired.
+pub async fn claim_invitation(transaction: &database::Transaction<'_>, id: i32) -> Result<bool> {
+    Ok(transaction.execute(invitation::query::CLAIM, &[&id]).await? == 1)
+}
+
+/// Records who used the invitation and assigns its role within the caller's
+/// transaction.
+pub async fn accept_invitation(
+    transaction: &database::Transaction<'_>,
+    invitation: &model::Invitation,
+    user_id: model::CredentialId,
+) -> Result<()> {
+    transaction.execute(invitation::query::ACCEPT, &[&invitation.id, &user_id]).await?;
+    if let Some(role) = &invitation.role {
+        transaction.execute(invitation::query::ASSIGN_ROLE, &[&user_id, role]).await?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+pub trait Invitations: Send + Sync + Clone {
+    async fn create(
+        &self,
+        request: &model::InvitationRequest,
+        created_by: model::CredentialId,
+        lifetime: Duration,
+    ) -> Result<model::Invitation>;
+    async fn by_token(&self, token: &str) -> Result<Option<model::Invitation>>;
+}
+
+#[async_trait]
+impl<T: model::Database> Invitations for InvitationRepository<T> {
+    #[instrument(skip(self, request))]
+    async fn create(
+        &self,
+        request: &model::InvitationRequest,
+        created_by: model::CredentialId,
+        lifetime: Duration,
+    ) -> Result<model::Invitation> {
+        let token = hash::token();
+        let expires_at = SystemTime::now() + lifetime;
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let stmt = transaction.prepare(invitation::query::CREATE).await?;
+        let created = transaction
+            .query::<model::Invitation>(
+                &stmt,
+                &[&hash::digest(&token), &request.email, &request.role, &created_by, &expires_at],
+            )
+            .await?
+            .remove(0);
+        append_audit_event(
+            &transaction,
+            model::AuditEventType::InvitationCreated,
+            Some(created_by),
+            json!({ "invitation": created.id, "role": created.role }),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(model::Invitation { token, ..created })
+    }
+    #[instrument(skip(self, token))]
+    async fn by_token(&self, token: &str) -> Result<Option<model::Invitation>> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(invitation::query::BY_TOKEN).await?;
+        Ok(client
+            .query::<model::Invitation>(&stmt, &[&hash::digest(token)])
+            .await?
+            .first()
+            .cloned())
+    }
+}
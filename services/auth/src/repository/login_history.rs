@@ -1,6 +1,12 @@
-use crate::{model, model::credentials, Result};
+use crate::{
+    model,
+    model::{credentials, outbox},
+    repository::{append_audit_event, append_outbox_event},
+    Result,
+};
 use async_trait::async_trait;
-use futures::future::join;
+use serde_json::json;
+use tracing::instrument;
 use std::marker::{Send, Sync};
 
 const GET_FAILED_LOGIN: &str =
@@ -50,23 +56,7 @@ impl<T: model::Database> LoginHistoryRepository<T> {
         Ok(())
     }
     pub async fn suspend(&self, user_id: &model::CredentialId) -> Result<()> {
-        let failed_logins = self.log(user_id).await?;
-        if failed_logins.exceeded_limit() {
-            let reset = self.delete(user_id);
-            if failed_logins.expired()? {
-                reset.await?;
-            } else {
-                let (..) = join(
-                    reset,
-                    self.db
-                        .client()
-                        .await?
-                        .execute(credentials::query::SUSPEND, &[&user_id]),
-                )
-                .await;
-            }
-        }
-        Ok(())
+        LoginHistory::suspend(self, user_id).await
     }
 }
 
@@ -80,6 +70,7 @@ pub trait LoginHistory: Clone + Send + Sync {
 
 #[async_trait]
 impl<T: model::Database> LoginHistory for LoginHistoryRepository<T> {
+    #[instrument(skip(self))]
     async fn log(&self, id: &model::CredentialId) -> Result<model::FailedLogin> {
         let client = self.db.client().await?;
         let stmt = client.prepare(CREATE_OR_UPDATE_FAILED_LOGIN).await?;
@@ -88,6 +79,7 @@ impl<T: model::Database> LoginHistory for LoginHistoryRepository<T> {
             .await?
             .remove(0))
     }
+    #[instrument(skip(self))]
     async fn get(&self, id: &model::CredentialId) -> Result<model::FailedLogin> {
         let client = self.db.client().await?;
         let stmt = client.prepare(GET_FAILED_LOGIN).await?;
@@ -96,6 +88,7 @@ impl<T: model::Database> LoginHistory for LoginHistoryRepository<T> {
             .await?
             .remove(0))
     }
+    #[instrument(skip(self))]
     async fn delete(&self, id: &model::CredentialId) -> Result<()> {
         self.db
             .client()
@@ -104,6 +97,7 @@ impl<T: model::Database> LoginHistory for LoginHistoryRepository<T> {
             .await?;
         Ok(())
     }
+    #[instrument(skip(self))]
     async fn suspend(&self, user_id: &model::CredentialId) -> Result<()> {
         let failed_logins = self.log(user_id).await?;
         if failed_logins.exceeded_limit() {
@@ -116,6 +110,20 @@ impl<T: model::Database> LoginHistory for LoginHistoryRepository<T> {
                 transaction
                     .execute(credentials::query::SUSPEND, &[&user_id])
                     .await?;
+                append_audit_event(
+                    &transaction,
+                    model::AuditEventType::AccountSuspended,
+                    Some(*user_id),
+                    json!({ "failed_attempts": failed_logins.attempts }),
+                )
+                .await?;
+                append_outbox_event(
+                    &transaction,
+                    model::OutboxEventType::CredentialsSuspended,
+                    *user_id,
+                    outbox::identifier_payload(*user_id),
+                )
+                .await?;
             }
             transaction.commit().await?;
         }
@@ -0,0 +1,79 @@
+use crate::{
+    configuration::settings::HashSettings,
+    model,
+    Result,
+    utilities::hash,
+    model::{credentials, magic_link},
+};
+use async_trait::async_trait;
+use tracing::instrument;
+use std::marker::{Send, Sync};
+
+pub type AppMagicLinks = MagicLinkRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct MagicLinkRepository<T: model::Database> {
+    db: T,
+    settings: HashSettings,
+}
+
+impl<T: model::Database> MagicLinkRepository<T> {
+    pub fn new(db: T, settings: &HashSettings) -> Self {
+        MagicLinkRepository { db, settings: settings.clone() }
+    }
+}
+
+#[async_trait]
+pub trait MagicLinks: Send + Sync + Clone {
+    async fn generate(&self, email: &str) -> Result<Option<model::MagicLink>>;
+    async fn by_id(&self, id: &str) -> Result<Option<model::MagicLink>>;
+    async fn consume(&self, id: &str) -> Result<bool>;
+}
+
+#[async_trait]
+impl<T: model::Database> MagicLinks for MagicLinkRepository<T> {
+    #[instrument(skip(self, email))]
+    async fn generate(&self, email: &str) -> Result<Option<model::MagicLink>> {
+        let client = self.db.client().await?;
+        let token = hash::token();
+        let id = hash::token();
+        let hashed_token = hash::generate(&self.settings, &token)?;
+        let credentials_by_email = client.prepare(credentials::query::EMAIL).await?;
+        let create_link = client.prepare(magic_link::query::CREATE).await?;
+        if let Some(credentials) = client.query::<model::Credentials>(&credentials_by_email, &[&email])
+            .await?
+            .first()
+            .filter(| credentials | credentials.deleted_at.is_none()) {
+            Ok(client.query::<model::MagicLink>(
+                &create_link,
+                &[&id, &credentials.id, &hashed_token, &credentials.email],
+            )
+                .await?
+                .first()
+                .map(| link | model::MagicLink {
+                    id,
+                    token,
+                    user_id: credentials.id,
+                    email: credentials.email.clone(),
+                    created_at: link.created_at,
+                    used_at: None,
+                }))
+        } else {
+            Ok(None)
+        }
+    }
+    #[instrument(skip(self))]
+    async fn by_id(&self, id: &str) -> Result<Option<model::MagicLink>> {
+        let client = self.db.client().await?;
+        let link_by_id = client.prepare(magic_link::query::GET_BY_ID).await?;
+        Ok(client.query::<model::MagicLink>(&link_by_id, &[&id])
+            .await?
+            .first()
+            .cloned())
+    }
+    #[instrument(skip(self))]
+    async fn consume(&self, id: &str) -> Result<bool> {
+        let client = self.db.client().await?;
+        Ok(client.execute(magic_link::query::CONSUME, &[&id]).await? == 1)
+    }
+}
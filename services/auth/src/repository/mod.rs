@@ -1,7 +1,27 @@
+mod audit_log;
+mod challenge;
 mod credentials;
+mod device;
+mod email_change;
+mod health;
+mod invitation;
 mod login_history;
+mod magic_link;
+mod outbox;
 mod password_reset;
+mod personal_access_token;
+mod service_client;
 
+pub use audit_log::*;
+pub use challenge::*;
 pub use credentials::*;
+pub use device::*;
+pub use email_change::*;
+pub use health::*;
+pub use invitation::*;
 pub use login_history::*;
+pub use magic_link::*;
+pub use outbox::*;
 pub use password_reset::*;
+pub use personal_access_token::*;
+pub use service_client::*;
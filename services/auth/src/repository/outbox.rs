@@ -0,0 +1,56 @@
+use crate::{model, model::outbox, Result};
+use async_trait::async_trait;
+use std::marker::{Send, Sync};
+use tracing::instrument;
+
+pub type AppOutbox = OutboxRepository<model::DatabaseConnection>;
+
+pub async fn append_outbox_event(
+    transaction: &database::Transaction<'_>,
+    event_type: model::OutboxEventType,
+    aggregate_id: model::CredentialId,
+    payload: serde_json::Value,
+) -> Result<()> {
+    transaction
+        .execute(
+            outbox::query::CREATE,
+            &[&event_type.name(), &aggregate_id, &payload.to_string()],
+        )
+        .await?;
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub struct OutboxRepository<T: model::Database> {
+    db: T,
+}
+
+impl<T: model::Database> OutboxRepository<T> {
+    pub fn new(db: T) -> Self { OutboxRepository { db } }
+}
+
+#[async_trait]
+pub trait Outbox: Send + Sync + Clone {
+    async fn pending(&self, limit: i64) -> Result<Vec<model::OutboxEvent>>;
+    async fn mark_published(&self, ids: &[i64]) -> Result<u64>;
+}
+
+#[async_trait]
+impl<T: model::Database> Outbox for OutboxRepository<T> {
+    #[instrument(skip(self))]
+    async fn pending(&self, limit: i64) -> Result<Vec<model::OutboxEvent>> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(outbox::query::PENDING).await?;
+        Ok(client.query::<model::OutboxEvent>(&stmt, &[&limit]).await?)
+    }
+    #[instrument(skip(self, ids))]
+    async fn mark_published(&self, ids: &[i64]) -> Result<u64> {
+        let ids = ids.to_vec();
+        Ok(self
+            .db
+            .client()
+            .await?
+            .execute(outbox::query::PUBLISHED, &[&ids])
+            .await?)
+    }
+}
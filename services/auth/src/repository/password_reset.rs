@@ -1,5 +1,14 @@
-use crate::{model, Result, utilities::hash, model::{credentials, password_reset}};
+use crate::{
+    configuration::settings::HashSettings,
+    model,
+    Result,
+    repository::append_audit_event,
+    utilities::hash,
+    model::{credentials, password_reset},
+};
 use async_trait::async_trait;
+use serde_json::json;
+use tracing::instrument;
 use std::marker::{Send, Sync};
 
 pub type AppPasswordReset = PasswordReset<model::DatabaseConnection>;
@@ -7,10 +16,13 @@ pub type AppPasswordReset = PasswordReset<model::DatabaseConnection>;
 #[derive(Clone, Debug)]
 pub struct PasswordReset<T: model::Database> {
     db: T,
+    settings: HashSettings,
 }
 
 impl<T: model::Database> PasswordReset<T> {
-    pub fn new (db: T) -> Self { PasswordReset { db } }
+    pub fn new(db: T, settings: &HashSettings) -> Self {
+        PasswordReset { db, settings: settings.clone() }
+    }
 }
 
 #[async_trait]
@@ -21,17 +33,19 @@ pub trait PasswordResetRequest: Send + Sync + Clone {
 
 #[async_trait]
 impl<T: model::Database> PasswordResetRequest for PasswordReset<T> {
+    #[instrument(skip(self, email))]
     async fn generate(&self, email: &str) -> Result<Option<model::PasswordResetRequest>> {
-        let client = self.db.client().await?;
+        let mut client = self.db.client().await?;
         let reset_token = hash::token();
         let id = hash::token();
-        let hashed_token = hash::generate(&reset_token)?;
-        let credentials_by_email = client.prepare(credentials::query::EMAIL).await?;
-        let password_reset_request = client.prepare(password_reset::query::CREATE_REQUEST).await?;
-        if let Some(credentials) = client.query::<model::Credentials>(&credentials_by_email, &[&email])
+        let hashed_token = hash::generate(&self.settings, &reset_token)?;
+        let transaction = client.transaction().await?;
+        let credentials_by_email = transaction.prepare(credentials::query::EMAIL).await?;
+        let password_reset_request = transaction.prepare(password_reset::query::CREATE_REQUEST).await?;
+        if let Some(credentials) = transaction.query::<model::Credentials>(&credentials_by_email, &[&email])
             .await?
             .first() {
-            Ok(client.query::<model::PasswordResetRequest>(
+            let request = transaction.query::<model::PasswordResetRequest>(
                 &password_reset_request,
                 &[
                     &id,
@@ -50,11 +64,15 @@ impl<T: model::Database> PasswordResetRequest for PasswordReset<T> {
                         email: credentials.email.clone(),
                         name: credentials.name.clone(),
                         created_at: request.created_at,
-                    }))
+                    });
+            append_audit_event(&transaction, model::AuditEventType::ResetRequested, Some(credentials.id), json!({})).await?;
+            transaction.commit().await?;
+            Ok(request)
         } else {
             Ok(None)
         }
     }
+    #[instrument(skip(self))]
     async fn by_id(&self, id: &str) -> Result<Option<model::PasswordResetRequest>> {
         let client = self.db.client().await?;
         let request_by_id = client.prepare(password_reset::query::GET_REQUEST_BY_ID).await?;
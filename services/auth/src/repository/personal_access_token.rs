@@ -0,0 +1,120 @@
+use crate::{
+    configuration::settings::HashSettings,
+    model,
+    model::personal_access_token,
+    repository::append_audit_event,
+    utilities::hash,
+    Result,
+};
+use async_trait::async_trait;
+use serde_json::json;
+use std::{
+    marker::{Send, Sync},
+    time::SystemTime,
+};
+use tracing::instrument;
+
+pub type AppPersonalAccessTokens = PersonalAccessTokenRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct PersonalAccessTokenRepository<T: model::Database> {
+    db: T,
+    settings: HashSettings,
+}
+
+impl<T: model::Database> PersonalAccessTokenRepository<T> {
+    pub fn new(db: T, settings: &HashSettings) -> Self {
+        PersonalAccessTokenRepository { db, settings: settings.clone() }
+    }
+}
+
+#[async_trait]
+pub trait PersonalAccessTokens: Send + Sync + Clone {
+    async fn create(
+        &self,
+        user_id: model::CredentialId,
+        request: &model::PersonalAccessTokenRequest,
+        expires_at: Option<SystemTime>,
+    ) -> Result<model::PersonalAccessToken>;
+    async fn by_id(&self, id: &str) -> Result<Option<model::PersonalAccessToken>>;
+    async fn for_user(&self, user_id: model::CredentialId) -> Result<Vec<model::PersonalAccessToken>>;
+    async fn revoke(&self, user_id: model::CredentialId, id: &str) -> Result<bool>;
+    async fn used(&self, id: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: model::Database> PersonalAccessTokens for PersonalAccessTokenRepository<T> {
+    /// The returned token carries the full plaintext token, which is never
+    /// stored.
+    #[instrument(skip(self, request))]
+    async fn create(
+        &self,
+        user_id: model::CredentialId,
+        request: &model::PersonalAccessTokenRequest,
+        expires_at: Option<SystemTime>,
+    ) -> Result<model::PersonalAccessToken> {
+        let id = hash::token();
+        let secret = hash::token();
+        let hashed_secret = hash::generate(&self.settings, &secret)?;
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let stmt = transaction.prepare(personal_access_token::query::CREATE).await?;
+        let created = transaction
+            .query::<model::PersonalAccessToken>(
+                &stmt,
+                &[&id, &user_id, &request.name, &hashed_secret, &request.scopes, &expires_at],
+            )
+            .await?
+            .remove(0);
+        append_audit_event(
+            &transaction,
+            model::AuditEventType::PersonalAccessTokenCreated,
+            Some(user_id),
+            json!({ "token": created.id, "scopes": created.scopes }),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(model::PersonalAccessToken {
+            token: personal_access_token::encode(&id, &secret),
+            ..created
+        })
+    }
+    #[instrument(skip(self))]
+    async fn by_id(&self, id: &str) -> Result<Option<model::PersonalAccessToken>> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(personal_access_token::query::BY_ID).await?;
+        Ok(client
+            .query::<model::PersonalAccessToken>(&stmt, &[&id])
+            .await?
+            .pop())
+    }
+    #[instrument(skip(self))]
+    async fn for_user(&self, user_id: model::CredentialId) -> Result<Vec<model::PersonalAccessToken>> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(personal_access_token::query::FOR_USER).await?;
+        Ok(client.query::<model::PersonalAccessToken>(&stmt, &[&user_id]).await?)
+    }
+    #[instrument(skip(self))]
+    async fn revoke(&self, user_id: model::CredentialId, id: &str) -> Result<bool> {
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        if transaction.execute(personal_access_token::query::REVOKE, &[&id, &user_id]).await? != 1 {
+            return Ok(false);
+        }
+        append_audit_event(
+            &transaction,
+            model::AuditEventType::PersonalAccessTokenRevoked,
+            Some(user_id),
+            json!({ "token": id }),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+    #[instrument(skip(self))]
+    async fn used(&self, id: &str) -> Result<()> {
+        let client = self.db.client().await?;
+        client.execute(personal_access_token::query::USED, &[&id]).await?;
+        Ok(())
+    }
+}
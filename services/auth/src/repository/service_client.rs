@@ -0,0 +1,126 @@
+use crate::{
+    configuration::settings::HashSettings,
+    model,
+    model::service_client,
+    repository::append_audit_event,
+    utilities::hash,
+    Result,
+};
+use async_trait::async_trait;
+use serde_json::json;
+use std::{
+    marker::{Send, Sync},
+    time::{Duration, SystemTime},
+};
+use tracing::instrument;
+
+pub type AppServiceClients = ServiceClientRepository<model::DatabaseConnection>;
+
+#[derive(Clone, Debug)]
+pub struct ServiceClientRepository<T: model::Database> {
+    db: T,
+    settings: HashSettings,
+}
+
+impl<T: model::Database> ServiceClientRepository<T> {
+    pub fn new(db: T, settings: &HashSettings) -> Self {
+        ServiceClientRepository { db, settings: settings.clone() }
+    }
+}
+
+fn secret() -> String {
+    format!("{}{}", hash::token(), hash::token())
+}
+
+#[async_trait]
+pub trait ServiceClients: Send + Sync + Clone {
+    async fn create(
+        &self,
+        request: &model::ServiceClientRequest,
+        created_by: model::CredentialId,
+    ) -> Result<model::ServiceClient>;
+    async fn by_client_id(&self, client_id: &str) -> Result<Option<model::ServiceClient>>;
+    async fn rotate(
+        &self,
+        client_id: &str,
+        rotated_by: model::CredentialId,
+        grace: Duration,
+    ) -> Result<Option<model::ServiceClient>>;
+}
+
+#[async_trait]
+impl<T: model::Database> ServiceClients for ServiceClientRepository<T> {
+    /// The returned client carries the plaintext secret, which is never
+    /// stored.
+    #[instrument(skip(self, request))]
+    async fn create(
+        &self,
+        request: &model::ServiceClientRequest,
+        created_by: model::CredentialId,
+    ) -> Result<model::ServiceClient> {
+        let client_id = hash::token();
+        let secret = secret();
+        let hashed_secret = hash::generate(&self.settings, &secret)?;
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let stmt = transaction.prepare(service_client::query::CREATE).await?;
+        let created = transaction
+            .query::<model::ServiceClient>(
+                &stmt,
+                &[&client_id, &request.name, &hashed_secret, &request.scopes, &created_by, &request.audiences],
+            )
+            .await?
+            .remove(0);
+        append_audit_event(
+            &transaction,
+            model::AuditEventType::ServiceClientCreated,
+            Some(created_by),
+            json!({ "client_id": created.client_id, "scopes": created.scopes, "audiences": created.audiences }),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(model::ServiceClient { secret, ..created })
+    }
+    #[instrument(skip(self))]
+    async fn by_client_id(&self, client_id: &str) -> Result<Option<model::ServiceClient>> {
+        let client = self.db.client().await?;
+        let stmt = client.prepare(service_client::query::BY_CLIENT_ID).await?;
+        Ok(client
+            .query::<model::ServiceClient>(&stmt, &[&client_id])
+            .await?
+            .first()
+            .cloned())
+    }
+    /// Replaces the secret, keeping the old one valid for the grace period.
+    #[instrument(skip(self))]
+    async fn rotate(
+        &self,
+        client_id: &str,
+        rotated_by: model::CredentialId,
+        grace: Duration,
+    ) -> Result<Option<model::ServiceClient>> {
+        let secret = secret();
+        let hashed_secret = hash::generate(&self.settings, &secret)?;
+        let previous_secret_expires_at = SystemTime::now() + grace;
+        let mut client = self.db.client().await?;
+        let transaction = client.transaction().await?;
+        let stmt = transaction.prepare(service_client::query::ROTATE).await?;
+        let rotated = match transaction
+            .query::<model::ServiceClient>(&stmt, &[&client_id, &hashed_secret, &previous_secret_expires_at])
+            .await?
+            .pop()
+        {
+            Some(rotated) => rotated,
+            None => return Ok(None),
+        };
+        append_audit_event(
+            &transaction,
+            model::AuditEventType::ServiceClientRotated,
+            Some(rotated_by),
+            json!({ "client_id": rotated.client_id, "grace": grace.as_secs() }),
+        )
+        .await?;
+        transaction.commit().await?;
+        Ok(Some(model::ServiceClient { secret, ..rotated }))
+    }
+}
@@ -0,0 +1,9 @@
+use crate::{handler::audit, model};
+use actix_web::web;
+
+pub const AUDIT_VERIFICATION_ROUTE: &str = "/verify";
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(audit::events::<model::AppDependencies>)))
+        .service(web::resource(AUDIT_VERIFICATION_ROUTE).route(web::get().to(audit::verify::<model::AppDependencies>)));
+}
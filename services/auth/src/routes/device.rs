@@ -0,0 +1,8 @@
+use crate::{handler::device, model};
+use actix_web::web;
+
+pub const DEVICE_DISOWN_ROUTE: &str = "/disown";
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource(DEVICE_DISOWN_ROUTE).route(web::post().to(device::disown::<model::AppDependencies>)));
+}
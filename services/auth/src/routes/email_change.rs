@@ -0,0 +1,10 @@
+use crate::{handler::email_change, model};
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::put().to(email_change::confirm_email_change::<model::AppDependencies>))
+            .route(web::delete().to(email_change::revert_email_change::<model::AppDependencies>)),
+    );
+}
@@ -0,0 +1,10 @@
+use crate::{handler::health, model};
+use actix_web::web;
+
+pub const LIVE_ROUTE: &str = "/live";
+pub const READY_ROUTE: &str = "/ready";
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource(LIVE_ROUTE).route(web::get().to(health::live)))
+        .service(web::resource(READY_ROUTE).route(web::get().to(health::ready::<model::AppDependencies>)));
+}
@@ -0,0 +1,6 @@
+use crate::{handler::impersonation, model};
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(impersonation::create::<model::AppDependencies>)));
+}
@@ -0,0 +1,10 @@
+use crate::{handler::magic_link, model};
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::post().to(magic_link::request_magic_link::<model::AppDependencies>))
+            .route(web::put().to(magic_link::sign_in::<model::AppDependencies>)),
+    );
+}
@@ -1,15 +1,61 @@
+use crate::{handler, model};
 use actix_web::web;
 
+mod audit;
 mod credentials;
+mod device;
+mod email_change;
+mod health;
+mod impersonation;
+mod invitation;
+mod magic_link;
 mod verification;
 mod password_reset;
+mod personal_access_token;
+mod service_client;
+mod token;
 
 pub const VERIFICATION_ROUTE: &str = "/verify";
 pub const CREDENTIALS_ROUTE: &str = "/credentials";
 pub const PASSWORD_RESET_ROUTE: &str = "/reset";
+pub const MAGIC_LINK_ROUTE: &str = "/magic-link";
+pub const EMAIL_CHANGE_ROUTE: &str = "/email-change";
+pub const OPENAPI_ROUTE: &str = "/openapi.json";
+pub const HEALTH_ROUTE: &str = "/health";
+pub const METRICS_ROUTE: &str = "/metrics";
+pub const AUDIT_ROUTE: &str = "/audit";
+pub const INVITATION_ROUTE: &str = "/invitations";
+pub const DEVICE_ROUTE: &str = "/devices";
+pub const IMPERSONATION_ROUTE: &str = "/impersonations";
+pub const SERVICE_CLIENT_ROUTE: &str = "/service-clients";
+pub const TOKEN_ROUTE: &str = "/oauth/token";
+pub const PERSONAL_ACCESS_TOKEN_ROUTE: &str = "/personal-access-tokens";
+
+pub use audit::AUDIT_VERIFICATION_ROUTE;
+pub use device::DEVICE_DISOWN_ROUTE;
+pub use health::{LIVE_ROUTE, READY_ROUTE};
+pub use service_client::SERVICE_CLIENT_ROTATE_ROUTE;
+
+fn scope(path: &str) -> actix_web::Scope {
+    web::scope(path)
+        .app_data(handler::error::json_config())
+        .app_data(handler::error::form_config())
+}
 
 pub fn configuration(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope(VERIFICATION_ROUTE).configure(verification::config))
-        .service(web::scope(CREDENTIALS_ROUTE).configure(credentials::config))
-        .service(web::scope(PASSWORD_RESET_ROUTE).configure(password_reset::config));
+    cfg.service(scope(VERIFICATION_ROUTE).configure(verification::config))
+        .service(scope(CREDENTIALS_ROUTE).configure(credentials::config))
+        .service(scope(PASSWORD_RESET_ROUTE).configure(password_reset::config))
+        .service(scope(MAGIC_LINK_ROUTE).configure(magic_link::config))
+        .service(scope(EMAIL_CHANGE_ROUTE).configure(email_change::config))
+        .service(scope(HEALTH_ROUTE).configure(health::config))
+        .service(scope(AUDIT_ROUTE).configure(audit::config))
+        .service(scope(INVITATION_ROUTE).configure(invitation::config))
+        .service(scope(DEVICE_ROUTE).configure(device::config))
+        .service(scope(IMPERSONATION_ROUTE).configure(impersonation::config))
+        .service(scope(SERVICE_CLIENT_ROUTE).configure(service_client::config))
+        .service(scope(TOKEN_ROUTE).configure(token::config))
+        .service(scope(PERSONAL_ACCESS_TOKEN_ROUTE).configure(personal_access_token::config))
+        .route(OPENAPI_ROUTE, web::get().to(handler::openapi::specification))
+        .route(METRICS_ROUTE, web::get().to(handler::metrics::export::<model::AppDependencies>));
 }
@@ -1,18 +1,10 @@
-use crate::{handler::password_reset, repository};
+use crate::{handler::password_reset, model};
 use actix_web::web;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::resource("")
-            .route(web::post().to(password_reset::request_password_reset::<
-                repository::AppLoginHistory,
-                repository::AppCredentials,
-                repository::AppPasswordReset,
-            >))
-            .route(web::put().to(password_reset::reset_password::<
-                repository::AppLoginHistory,
-                repository::AppCredentials,
-                repository::AppPasswordReset,
-            >)),
+            .route(web::post().to(password_reset::request_password_reset::<model::AppDependencies>))
+            .route(web::put().to(password_reset::reset_password::<model::AppDependencies>)),
     );
 }
@@ -0,0 +1,11 @@
+use crate::{handler::personal_access_token, model};
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::post().to(personal_access_token::create::<model::AppDependencies>))
+            .route(web::get().to(personal_access_token::list::<model::AppDependencies>))
+            .route(web::delete().to(personal_access_token::revoke::<model::AppDependencies>)),
+    );
+}
@@ -0,0 +1,12 @@
+use crate::{handler::service_client, model};
+use actix_web::web;
+
+pub const SERVICE_CLIENT_ROTATE_ROUTE: &str = "/rotate";
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(service_client::create::<model::AppDependencies>)))
+        .service(
+            web::resource(SERVICE_CLIENT_ROTATE_ROUTE)
+                .route(web::post().to(service_client::rotate::<model::AppDependencies>)),
+        );
+}
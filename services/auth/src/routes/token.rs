@@ -0,0 +1,6 @@
+use crate::{handler::service_client, model};
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(service_client::token::<model::AppDependencies>)));
+}
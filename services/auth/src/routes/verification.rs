@@ -1,12 +1,11 @@
-use crate::{handler::verification, repository};
+use crate::{handler::verification, model};
 use actix_web::web;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
-        web::resource("").route(web::post().to(verification::authenticate_credentials::<
-            repository::AppLoginHistory,
-            repository::AppCredentials,
-            repository::AppPasswordReset,
-        >)),
+        web::resource("")
+            .route(web::post().to(verification::authenticate_credentials::<model::AppDependencies>))
+            .route(web::get().to(verification::verify_session::<model::AppDependencies>))
+            .route(web::delete().to(verification::sign_out::<model::AppDependencies>)),
     );
 }
@@ -1,41 +1,107 @@
-use actix_web::{web, App, HttpServer};
+use actix_cors::{Cors, CorsFactory};
+use actix_web::{
+    http::{header, HeaderName},
+    middleware::Condition,
+    web, App, HttpServer,
+};
+use logging::info;
+use std::sync::Arc;
 use crate::{
     configuration::{
         connection,
+        settings::Settings,
     },
+    handler::challenge,
+    metrics,
     model,
     routes,
 };
 
+const ALL_ORIGINS: &str = "*";
+const ALLOWED_METHODS: [&str; 5] = ["GET", "POST", "PUT", "DELETE", "OPTIONS"];
+
+fn cors(settings: &Settings) -> Condition<CorsFactory> {
+    let request_id = HeaderName::from_static(logging::REQUEST_ID_HEADER);
+    let cors = settings
+        .cors
+        .origins
+        .iter()
+        .filter(|origin| *origin != ALL_ORIGINS)
+        .fold(Cors::new(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(ALLOWED_METHODS.to_vec())
+        .allowed_headers(vec![
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static(csrf::CSRF_HEADER),
+            HeaderName::from_static(challenge::CHALLENGE_HEADER),
+            HeaderName::from_static(challenge::CHALLENGE_SOLUTION_HEADER),
+            request_id.clone(),
+        ])
+        .expose_headers(vec![header::AUTHORIZATION, HeaderName::from_static(csrf::CSRF_HEADER), request_id])
+        .max_age(settings.cors.max_age);
+    let cors = if settings.cors.credentials {
+        cors.supports_credentials()
+    } else {
+        cors
+    };
+    Condition::new(!settings.cors.origins.is_empty(), cors.finish())
+}
+
 pub async fn development(state: model::AppServiceState) -> std::io::Result<()> {
     use listenfd::ListenFd;
-    let uri = connection::uri();
+    let settings = state.settings;
+    let uri = connection::uri(&settings.server);
+    let shutdown_timeout = settings.server.shutdown_timeout;
     let data = web::Data::new(state);
     let mut server = HttpServer::new(move || {
         App::new()
+            .wrap(csrf::Csrf::new(csrf::SESSION_COOKIE, settings.cookies.secure))
+            .wrap(cors(settings))
+            .wrap(metrics::RequestMetrics)
+            .wrap(logging::RequestTracing)
             .app_data(data.clone())
             .configure(routes::configuration)
-    });
+    })
+    .shutdown_timeout(shutdown_timeout);
     let mut listen = ListenFd::from_env();
     server = if let Some(listener) = listen.take_tcp_listener(0).unwrap() {
-        println!("Hot reloading enabled.");
+        info!("Hot reloading enabled");
         server.listen(listener)?
     } else {
-        println!("No TCP listener found");
+        info!("No TCP listener found");
         server.bind(&uri)?
     };
-    println!("Development: listening at {}", &uri);
+    info!(uri = %uri, "Development: listening");
     server.run().await
 }
 
 pub async fn production(state: model::AppServiceState) -> std::io::Result<()> {
-    let uri = connection::uri();
+    let settings = state.settings;
+    let uri = connection::uri(&settings.server);
+    let tls_configuration = settings.tls.configuration();
+    let shutdown_timeout = settings.server.shutdown_timeout;
     let data = web::Data::new(state);
     let server = HttpServer::new(move || {
         App::new()
+            .wrap(csrf::Csrf::new(csrf::SESSION_COOKIE, settings.cookies.secure))
+            .wrap(cors(settings))
+            .wrap(metrics::RequestMetrics)
+            .wrap(logging::RequestTracing)
             .app_data(data.clone())
             .configure(routes::configuration)
-    });
-    println!("Production: listening at {}", &uri);
-    server.bind(&uri)?.run().await
+    })
+    .shutdown_timeout(shutdown_timeout);
+    match tls_configuration {
+        Some(configuration) => {
+            let certificate = Arc::new(tls::ReloadableCertificate::new(configuration)?);
+            let config = tls::server_config(certificate.clone())?;
+            tls::reload_on_hangup(certificate)?;
+            info!(uri = %uri, "Production: listening with TLS");
+            server.bind_rustls(&uri, config)?.run().await
+        }
+        None => {
+            info!(uri = %uri, "Production: listening");
+            server.bind(&uri)?.run().await
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS auth.audit_event (
+  sequence bigint PRIMARY KEY,
+  event_type varchar(64) NOT NULL,
+  user_id int,
+  details text NOT NULL,
+  created_at timestamp NOT NULL,
+  previous_hash char(64) NOT NULL,
+  hash char(64) UNIQUE NOT NULL
+);
+CREATE INDEX IF NOT EXISTS audit_event_user_id ON auth.audit_event(user_id);
+CREATE INDEX IF NOT EXISTS audit_event_created_at ON auth.audit_event(created_at);
+
+CREATE OR REPLACE FUNCTION auth.reject_audit_event_change() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'auth.audit_event is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_event_append_only ON auth.audit_event;
+CREATE TRIGGER audit_event_append_only BEFORE UPDATE OR DELETE ON auth.audit_event
+  FOR EACH ROW EXECUTE PROCEDURE auth.reject_audit_event_change();
+
+DROP TRIGGER IF EXISTS audit_event_no_truncate ON auth.audit_event;
+CREATE TRIGGER audit_event_no_truncate BEFORE TRUNCATE ON auth.audit_event
+  FOR EACH STATEMENT EXECUTE PROCEDURE auth.reject_audit_event_change();
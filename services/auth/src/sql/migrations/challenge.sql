@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS auth.failed_attempt (
+  key char(64) PRIMARY KEY,
+  attempts int NOT NULL DEFAULT 1,
+  updated_at timestamp DEFAULT current_timestamp not null
+);
+
+CREATE TABLE IF NOT EXISTS auth.redeemed_challenge (
+  digest char(64) PRIMARY KEY,
+  expires_at timestamp NOT NULL
+);
+CREATE INDEX IF NOT EXISTS redeemed_challenge_expires_at ON auth.redeemed_challenge(expires_at);
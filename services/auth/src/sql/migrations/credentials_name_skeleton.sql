@@ -0,0 +1,2 @@
+ALTER TABLE auth.credentials ADD COLUMN IF NOT EXISTS name_skeleton varchar(1020) DEFAULT null;
+CREATE UNIQUE INDEX IF NOT EXISTS credentials_name_skeleton_index ON auth.credentials(name_skeleton);
@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS auth.known_device (
+  user_id int NOT NULL REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  fingerprint char(64) NOT NULL,
+  network varchar(64) NOT NULL,
+  country char(2) DEFAULT null,
+  first_seen timestamp DEFAULT current_timestamp not null,
+  last_seen timestamp DEFAULT current_timestamp not null,
+  PRIMARY KEY (user_id, fingerprint, network)
+);
+
+CREATE TABLE IF NOT EXISTS auth.device_alert (
+  id char(32) PRIMARY KEY UNIQUE NOT NULL,
+  user_id int NOT NULL REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  token char(118) UNIQUE NOT NULL,
+  fingerprint char(64) NOT NULL,
+  network varchar(64) NOT NULL,
+  created_at timestamp DEFAULT current_timestamp not null,
+  used_at timestamp DEFAULT null
+);
+
+CREATE TABLE IF NOT EXISTS auth.session_revocation (
+  user_id int PRIMARY KEY REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  revoked_at timestamp NOT NULL
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS auth.email_change (
+  id char(32) PRIMARY KEY UNIQUE NOT NULL,
+  user_id int NOT NULL REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  old_email citext NOT NULL,
+  new_email citext NOT NULL,
+  token char(118) UNIQUE NOT NULL,
+  revert_token char(118) UNIQUE NOT NULL,
+  created_at timestamp DEFAULT current_timestamp not null,
+  confirmed_at timestamp DEFAULT null,
+  reverted_at timestamp DEFAULT null
+);
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS auth.invitation (
+  id serial PRIMARY KEY,
+  token char(64) UNIQUE NOT NULL,
+  email citext NOT NULL,
+  role varchar(32) DEFAULT null,
+  created_by int NOT NULL,
+  created_at timestamp DEFAULT current_timestamp not null,
+  expires_at timestamp NOT NULL,
+  used_at timestamp DEFAULT null,
+  used_by int REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS auth.credential_role (
+  user_id int PRIMARY KEY REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  role varchar(32) NOT NULL,
+  assigned_at timestamp DEFAULT current_timestamp not null
+);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS auth.magic_link (
+  id char(32) PRIMARY KEY UNIQUE NOT NULL,
+  user_id int NOT NULL REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  token char(118) UNIQUE NOT NULL,
+  email citext NOT NULL,
+  created_at timestamp DEFAULT current_timestamp not null,
+  used_at timestamp DEFAULT null
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS auth.outbox (
+  id bigserial PRIMARY KEY,
+  event_type varchar(64) NOT NULL,
+  aggregate_id int NOT NULL,
+  payload text NOT NULL,
+  created_at timestamp DEFAULT current_timestamp NOT NULL,
+  published_at timestamp DEFAULT null
+);
+CREATE INDEX IF NOT EXISTS outbox_unpublished ON auth.outbox(id) WHERE published_at IS NULL;
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS auth.personal_access_token (
+  id char(32) PRIMARY KEY,
+  user_id int NOT NULL REFERENCES auth.credentials(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  name varchar(64) NOT NULL,
+  token char(118) NOT NULL,
+  scopes text[] NOT NULL DEFAULT '{}',
+  created_at timestamp DEFAULT current_timestamp not null,
+  expires_at timestamp DEFAULT null,
+  last_used_at timestamp DEFAULT null,
+  revoked_at timestamp DEFAULT null
+);
+
+CREATE INDEX IF NOT EXISTS personal_access_token_user_id ON auth.personal_access_token(user_id);
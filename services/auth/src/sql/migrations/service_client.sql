@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS auth.service_client (
+  id serial PRIMARY KEY,
+  client_id char(32) UNIQUE NOT NULL,
+  name varchar(64) NOT NULL,
+  secret char(118) NOT NULL,
+  previous_secret char(118) DEFAULT null,
+  previous_secret_expires_at timestamp DEFAULT null,
+  scopes text[] NOT NULL DEFAULT '{}',
+  created_by int NOT NULL,
+  created_at timestamp DEFAULT current_timestamp not null,
+  rotated_at timestamp DEFAULT null
+);
@@ -0,0 +1 @@
+ALTER TABLE auth.service_client ADD COLUMN IF NOT EXISTS audiences text[] NOT NULL DEFAULT '{}';
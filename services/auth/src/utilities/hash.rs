@@ -1,14 +1,22 @@
 extern crate argonautica;
 
-use crate::{configuration::hash, Result, error::Error};
+use crate::{configuration::settings::HashSettings, metrics, Result, error::Error};
 use argonautica::{Hasher, Verifier};
-use ring::{rand as ring_rand, rand::SecureRandom};
+use once_cell::sync::OnceCell;
+use ring::{digest as ring_digest, rand as ring_rand, rand::SecureRandom};
 use std::str;
 use rand;
 use rand::{Rng, distributions::Alphanumeric};
 
 const SALT_LENGTH: usize = 32;
 
+static DUMMY_HASH: OnceCell<String> = OnceCell::new();
+
+#[cfg(test)]
+thread_local! {
+    static DUMMY_AUTHENTICATIONS: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
 fn generate_salt() -> Result<Vec<u8>> {
     let rng = ring_rand::SystemRandom::new();
     let mut salt = [0u8; SALT_LENGTH];
@@ -23,22 +31,32 @@ pub fn token() -> String {
         .collect()
 }
 
-pub fn generate(word: &str) -> Result<String> {
+pub fn digest(content: &str) -> String {
+    ring_digest::digest(&ring_digest::SHA256, content.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn generate(settings: &HashSettings, word: &str) -> Result<String> {
+    let _timer = metrics::time_hash();
     Ok(Hasher::default()
-        .configure_lanes(hash::lanes())
-        .configure_iterations(hash::time_cost())
-        .configure_memory_size(hash::memory_usage())
+        .configure_lanes(settings.lanes)
+        .configure_iterations(settings.time_cost)
+        .configure_memory_size(settings.memory)
         .with_salt(generate_salt()?)
         .with_password(word)
-        .with_secret_key(hash::secret())
+        .with_secret_key(&settings.secret)
         .hash()?)
 }
 
-pub fn authenticate(password: &str, hash: &str) -> Result<bool> {
+pub fn authenticate(settings: &HashSettings, password: &str, hash: &str) -> Result<bool> {
+    let _timer = metrics::time_verify();
     match Verifier::default()
         .with_hash(hash)
         .with_password(password)
-        .with_secret_key(hash::secret())
+        .with_secret_key(&settings.secret)
         .verify()
     {
         Ok(result) => Ok(result),
@@ -49,18 +67,40 @@ pub fn authenticate(password: &str, hash: &str) -> Result<bool> {
     }
 }
 
+/// A hash of a random word, generated once with the cost of the first
+/// settings it is asked for.
+pub fn dummy_hash(settings: &HashSettings) -> Result<&'static str> {
+    DUMMY_HASH.get_or_try_init(|| generate(settings, &token())).map(String::as_str)
+}
+
+/// Costs as much as a failed `authenticate`, so lookups that find no account
+/// cannot be told apart from a wrong password by their response time.
+pub fn dummy_authenticate(settings: &HashSettings, password: &str) -> Result<()> {
+    #[cfg(test)]
+    DUMMY_AUTHENTICATIONS.with(|count| count.set(count.get() + 1));
+    authenticate(settings, password, dummy_hash(settings)?).map(|_| ())
+}
+
+/// How many times `dummy_authenticate` has run on this thread.
+#[cfg(test)]
+pub fn dummy_authentications() -> usize {
+    DUMMY_AUTHENTICATIONS.with(|count| count.get())
+}
+
 #[cfg(test)]
 mod hashing_and_auth_tests {
     use super::*;
+    use crate::utilities::test::fake;
 
     #[test]
     fn behaves_correctly_when_hashes_match() {
         let password = String::from("Cool!");
-        let hashed_password = match generate(&password) {
+        let settings = &fake::settings().hash;
+        let hashed_password = match generate(settings, &password) {
             Ok(hashed) => hashed,
             Err(error) => panic!("Error hashing password: {}", error),
         };
-        match authenticate(&password, &hashed_password) {
+        match authenticate(settings, &password, &hashed_password) {
             Ok(valid) => {
                 if !valid {
                     panic!("Password was not validated correctly, identical passwords responded as mismatched");
@@ -74,11 +114,12 @@ mod hashing_and_auth_tests {
     fn behaves_correctly_when_hash_does_not_match() {
         let password = String::from("Cool!");
         let invalid_password = String::from("Not cool...");
-        let hashed_password = match generate(&password) {
+        let settings = &fake::settings().hash;
+        let hashed_password = match generate(settings, &password) {
             Ok(hashed) => hashed,
             Err(_) => panic!("Error saving password"),
         };
-        match authenticate(&invalid_password, &hashed_password) {
+        match authenticate(settings, &invalid_password, &hashed_password) {
             Ok(valid) => {
                 if valid {
                     panic!(
@@ -90,4 +131,18 @@ mod hashing_and_auth_tests {
             Err(error) => panic!("Error authenticating password: {}", error),
         };
     }
+
+    #[test]
+    fn generates_the_dummy_hash_once() {
+        let settings = &fake::settings().hash;
+        let hash = dummy_hash(settings).unwrap();
+        assert_eq!(dummy_hash(settings).unwrap(), hash);
+        assert!(!authenticate(settings, "Cool!", hash).unwrap());
+        dummy_authenticate(settings, "Cool!").unwrap();
+    }
+
+    #[test]
+    fn digests_content_as_hex_sha256() {
+        assert_eq!(digest("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
 }
@@ -1,44 +1,341 @@
 use crate::{
-    configuration::jwt,
+    configuration::settings::{CookieSettings, JwtSettings, Settings},
     model,
     model::credentials::{CredentialId, Credentials},
     error::Error,
+    utilities::hash,
     Result,
 };
-use actix_web::{dev, http, web};
+use actix_web::{dev, http, http::Cookie, web, HttpMessage, HttpRequest};
 use jsonwebtoken;
-use jsonwebtoken::EncodingKey;
-use serde::{Deserialize, Serialize};
+use jsonwebtoken::{DecodingKey, EncodingKey, Validation};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const BEARER: &str = "Bearer ";
+const COOKIE_PATH: &str = "/";
+
+/// The administrator acting as the subject of an impersonation token.
+#[derive(Debug, Serialize, Deserialize)]
+struct Actor {
+    sub: CredentialId,
+}
+
+/// The registered claims (RFC 7519 section 4.1) every token carries.
+#[derive(Debug, Serialize, Deserialize)]
+struct Registered {
+    iss: String,
+    sub: String,
+    aud: Vec<String>,
+    exp: usize,
+    nbf: usize,
+    iat: usize,
+    jti: String,
+}
+
+impl Registered {
+    fn new(settings: &JwtSettings, sub: String, aud: Vec<String>, lifetime: usize) -> Result<Registered> {
+        let issued_at = now()?;
+        Ok(Registered {
+            iss: settings.issuer.clone(),
+            sub,
+            aud,
+            exp: issued_at + lifetime,
+            nbf: issued_at,
+            iat: issued_at,
+            jti: hash::token(),
+        })
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
-    id: CredentialId,
+    #[serde(flatten)]
+    registered: Registered,
     email: String,
     name: String,
-    exp: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    act: Option<Actor>,
 }
 
-pub fn generate_token(credentials: Credentials) -> Result<String> {
-    let Credentials {
-        id, name, email, ..
-    } = credentials;
+/// Claims for a service client acting as itself; `sub_type` keeps these
+/// from ever being read as a user session.
+#[derive(Debug, Serialize, Deserialize)]
+struct ServiceClaims {
+    #[serde(flatten)]
+    registered: Registered,
+    sub_type: String,
+    scope: String,
+}
+
+fn now() -> Result<usize> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as usize)
+}
+
+fn encode<T: Serialize>(settings: &JwtSettings, claims: &T) -> Result<String> {
     jsonwebtoken::encode(
         &jsonwebtoken::Header::default(),
-        &Claims {
-            id,
-            name,
-            email,
-            exp: jwt::expiration(),
-        },
-        &EncodingKey::from_secret(&jwt::secret().as_ref()),
+        claims,
+        &EncodingKey::from_secret(settings.secret.as_ref()),
     )
         .map_err(| error | Error::InternalServerError(error.to_string()))
 }
 
+/// Tokens must come from the configured issuer and, when an audience is
+/// given, name it; `exp` and `nbf` are checked allowing for clock skew.
+fn decode<T: DeserializeOwned>(settings: &JwtSettings, token: &str, audience: Option<&str>) -> Result<T> {
+    let mut validation = Validation {
+        leeway: settings.leeway,
+        validate_nbf: true,
+        iss: Some(settings.issuer.clone()),
+        ..Validation::default()
+    };
+    if let Some(audience) = audience {
+        validation.set_audience(&[audience]);
+    }
+    jsonwebtoken::decode::<T>(token, &DecodingKey::from_secret(settings.secret.as_ref()), &validation)
+        .map(| data | data.claims)
+        .map_err(| error | Error::BadRequest(error.to_string()))
+}
+
+fn issue(settings: &JwtSettings, credentials: Credentials, act: Option<Actor>, lifetime: usize) -> Result<String> {
+    let Credentials {
+        id, name, email, ..
+    } = credentials;
+    encode(settings, &Claims {
+        registered: Registered::new(settings, id.to_string(), vec![settings.audience.clone()], lifetime)?,
+        name,
+        email,
+        act,
+    })
+}
+
+pub fn generate_token(settings: &JwtSettings, credentials: Credentials) -> Result<String> {
+    issue(settings, credentials, None, settings.expiration)
+}
+
+pub fn generate_impersonation_token(
+    settings: &JwtSettings,
+    credentials: Credentials,
+    actor: CredentialId,
+    lifetime: usize,
+) -> Result<String> {
+    issue(settings, credentials, Some(Actor { sub: actor }), lifetime)
+}
+
+pub fn verify_token(settings: &JwtSettings, token: &str) -> Result<model::Session> {
+    let claims = decode::<Claims>(settings, token, Some(&settings.audience))?;
+    Ok(model::Session {
+        id: claims.registered.sub.parse().map_err(| _ | Error::BadRequest(String::from("invalid subject")))?,
+        name: claims.name,
+        email: claims.email,
+        issued_at: claims.registered.iat,
+        expires_at: claims.registered.exp,
+        actor: claims.act.map(| actor | actor.sub),
+        scopes: None,
+    })
+}
+
+/// Clients without audiences of their own get tokens for the configured
+/// audience.
+pub fn generate_service_token(
+    settings: &JwtSettings,
+    client_id: &str,
+    scopes: &[String],
+    audiences: &[String],
+    lifetime: usize,
+) -> Result<String> {
+    let audiences = if audiences.is_empty() { vec![settings.audience.clone()] } else { audiences.to_vec() };
+    encode(settings, &ServiceClaims {
+        registered: Registered::new(settings, String::from(client_id), audiences, lifetime)?,
+        sub_type: String::from(model::SERVICE_SUBJECT),
+        scope: scopes.join(" "),
+    })
+}
+
+/// The audience is reported rather than enforced, since it names the API
+/// the token is meant for rather than this service.
+pub fn verify_service_token(settings: &JwtSettings, token: &str) -> Result<model::ServiceSession> {
+    let claims = decode::<ServiceClaims>(settings, token, None)?;
+    if claims.sub_type != model::SERVICE_SUBJECT {
+        return Err(Error::BadRequest(String::from("not a service token")));
+    }
+    Ok(model::ServiceSession {
+        subject_type: claims.sub_type,
+        client_id: claims.registered.sub,
+        scopes: claims.scope.split_whitespace().map(String::from).collect(),
+        audiences: claims.registered.aud,
+        issued_at: claims.registered.iat,
+        expires_at: claims.registered.exp,
+    })
+}
+
+pub fn token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(| header | header.to_str().ok())
+        .map(| header | String::from(header.trim_start_matches(BEARER)))
+        .or_else(|| req.cookie(csrf::SESSION_COOKIE).map(| cookie | String::from(cookie.value())))
+        .filter(| token | !token.is_empty())
+}
+
+fn session_cookie(settings: &CookieSettings, token: String, max_age: i64) -> Cookie<'static> {
+    Cookie::build(csrf::SESSION_COOKIE, token)
+        .path(COOKIE_PATH)
+        .http_only(true)
+        .secure(settings.secure)
+        .same_site(settings.same_site)
+        .max_age(max_age)
+        .finish()
+}
+
 pub fn set_token(
+    settings: &Settings,
     mut response: dev::HttpResponseBuilder,
     credentials: model::Credentials,
 ) -> Result<web::HttpResponse> {
-    let token = generate_token(credentials)?;
-    Ok(response.header(http::header::AUTHORIZATION, token).finish())
+    let token = generate_token(&settings.jwt, credentials)?;
+    let transport = settings.cookies.session;
+    if transport.cookie() {
+        response.cookie(session_cookie(&settings.cookies, token.clone(), settings.jwt.expiration as i64));
+    }
+    if transport.header() {
+        response.header(http::header::AUTHORIZATION, token);
+    }
+    Ok(response.finish())
+}
+
+pub fn clear_token(settings: &CookieSettings, mut response: dev::HttpResponseBuilder) -> web::HttpResponse {
+    response.cookie(session_cookie(settings, String::new(), 0)).finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::test::fake;
+    use actix_web::{test, HttpResponse};
+
+    #[test]
+    fn verifies_generated_tokens() {
+        let settings = &fake::settings().jwt;
+        let credentials = fake::credentials();
+        let session = verify_token(settings, &generate_token(settings, credentials.clone()).unwrap()).unwrap();
+        assert_eq!(session.id, credentials.id);
+        assert_eq!(session.email, credentials.email);
+        assert!(session.issued_at <= now().unwrap());
+        assert!(session.expires_at > now().unwrap());
+    }
+
+    #[test]
+    fn impersonation_tokens_name_the_actor() {
+        let settings = &fake::settings().jwt;
+        let credentials = fake::credentials();
+        let token = generate_token(settings, credentials.clone()).unwrap();
+        assert_eq!(verify_token(settings, &token).unwrap().actor, None);
+        let token = generate_impersonation_token(settings, credentials.clone(), 42, 60).unwrap();
+        let session = verify_token(settings, &token).unwrap();
+        assert_eq!(session.id, credentials.id);
+        assert_eq!(session.actor, Some(42));
+        assert!(session.expires_at <= now().unwrap() + 60);
+    }
+
+    #[test]
+    fn service_tokens_are_never_user_sessions() {
+        let settings = &fake::settings().jwt;
+        let scopes = vec![String::from("bills:write")];
+        let token = generate_service_token(settings, "importer", &scopes, &[], 60).unwrap();
+        let session = verify_service_token(settings, &token).unwrap();
+        assert_eq!(session.client_id, "importer");
+        assert_eq!(session.subject_type, model::SERVICE_SUBJECT);
+        assert_eq!(session.scopes, scopes);
+        assert_eq!(session.audiences, vec![settings.audience.clone()]);
+        assert!(verify_token(settings, &token).is_err());
+        assert!(verify_service_token(settings, &generate_token(settings, fake::credentials()).unwrap()).is_err());
+    }
+
+    fn claims(credentials: &Credentials, lifetime: usize) -> Claims {
+        let settings = &fake::settings().jwt;
+        Claims {
+            registered: Registered::new(settings, credentials.id.to_string(), vec![settings.audience.clone()], lifetime)
+                .unwrap(),
+            email: credentials.email.clone(),
+            name: credentials.name.clone(),
+            act: None,
+        }
+    }
+
+    #[test]
+    fn tokens_carry_the_registered_claims() {
+        let settings = &fake::settings().jwt;
+        let credentials = fake::credentials();
+        let token = generate_token(settings, credentials.clone()).unwrap();
+        let registered = decode::<Claims>(settings, &token, Some(&settings.audience)).unwrap().registered;
+        assert_eq!(registered.iss, settings.issuer);
+        assert_eq!(registered.sub, credentials.id.to_string());
+        assert_eq!(registered.aud, vec![settings.audience.clone()]);
+        assert_eq!(registered.nbf, registered.iat);
+        assert_eq!(registered.exp, registered.iat + settings.expiration);
+        let other = generate_token(settings, credentials).unwrap();
+        let other = decode::<Claims>(settings, &other, None).unwrap().registered;
+        assert_ne!(registered.jti, other.jti);
+    }
+
+    #[test]
+    fn rejects_tokens_from_other_issuers_or_for_other_audiences() {
+        let settings = &fake::settings().jwt;
+        let credentials = fake::credentials();
+        let mut foreign = claims(&credentials, 60);
+        foreign.registered.iss = String::from("https://elsewhere.example");
+        assert!(verify_token(settings, &encode(settings, &foreign).unwrap()).is_err());
+        let mut foreign = claims(&credentials, 60);
+        foreign.registered.aud = vec![String::from("https://elsewhere.example")];
+        assert!(verify_token(settings, &encode(settings, &foreign).unwrap()).is_err());
+        assert!(verify_token(settings, &encode(settings, &claims(&credentials, 60)).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn allows_clock_skew_up_to_the_leeway() {
+        let settings = &fake::settings().jwt;
+        let credentials = fake::credentials();
+        let leeway = settings.leeway as usize;
+        let mut skewed = claims(&credentials, 0);
+        skewed.registered.exp -= 1;
+        assert!(verify_token(settings, &encode(settings, &skewed).unwrap()).is_ok());
+        skewed.registered.exp -= leeway;
+        assert!(verify_token(settings, &encode(settings, &skewed).unwrap()).is_err());
+        let mut early = claims(&credentials, 60);
+        early.registered.nbf += leeway / 2;
+        assert!(verify_token(settings, &encode(settings, &early).unwrap()).is_ok());
+        early.registered.nbf += leeway;
+        assert!(verify_token(settings, &encode(settings, &early).unwrap()).is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_tokens() {
+        let settings = &fake::settings().jwt;
+        let token = generate_token(settings, fake::credentials()).unwrap();
+        assert!(verify_token(settings, &format!("{}a", token)).is_err());
+    }
+
+    #[test]
+    fn reads_tokens_from_either_transport() {
+        let req = test::TestRequest::default()
+            .header(http::header::AUTHORIZATION, "Bearer header-token")
+            .to_http_request();
+        assert_eq!(token(&req), Some(String::from("header-token")));
+        let req = test::TestRequest::default()
+            .cookie(Cookie::new(csrf::SESSION_COOKIE, "cookie-token"))
+            .to_http_request();
+        assert_eq!(token(&req), Some(String::from("cookie-token")));
+        assert_eq!(token(&test::TestRequest::default().to_http_request()), None);
+    }
+
+    #[test]
+    fn clears_the_session_cookie() {
+        let response = clear_token(&fake::settings().cookies, HttpResponse::Ok());
+        let cookie = response.cookies().find(| cookie | cookie.name() == csrf::SESSION_COOKIE).unwrap();
+        assert_eq!(cookie.value(), "");
+        assert!(cookie.http_only().unwrap_or(false));
+        assert!(cookie.to_string().contains("Max-Age=0"));
+    }
 }
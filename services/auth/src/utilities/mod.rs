@@ -1,3 +1,4 @@
+pub mod name;
 pub mod password;
 pub mod hash;
 pub mod jwt;
@@ -0,0 +1,169 @@
+use caseless::default_case_fold_str;
+use serde::{Serialize, Deserialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::skeleton as confusable_skeleton;
+
+const MIN_NAME_LENGTH: usize = 3;
+const MAX_NAME_LENGTH: usize = 64;
+const EMAIL_SEPARATOR: char = '@';
+const INVALID_NAME_MESSAGE: &str = "User name is not valid";
+const TOO_SHORT: &str = "User name must be at least 3 characters long";
+const TOO_LONG: &str = "User name must be at most 64 characters long";
+const CONTAINS_EMAIL_SEPARATOR: &str = "User name may not contain an @ symbol";
+const CONTAINS_CONTROL_CHARACTERS: &str = "User name may not contain control characters";
+const RESERVED: &str = "User name contains a reserved word or title";
+
+pub enum Validity {
+    Valid,
+    Invalid(NameIssues),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct NameIssues {
+    message: String,
+    problems: Vec<String>,
+}
+
+pub fn normalize(name: &str) -> String {
+    name.nfkc().collect::<String>().trim().to_string()
+}
+
+pub fn fold(name: &str) -> String {
+    default_case_fold_str(&normalize(name)).nfkc().collect()
+}
+
+pub fn skeleton(name: &str) -> String {
+    confusable_skeleton(&fold(name)).collect()
+}
+
+fn words(name: &str) -> Vec<String> {
+    skeleton(name)
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn is_reserved(name: &str, reserved: &[String]) -> bool {
+    let name_skeleton = skeleton(name);
+    let name_words = words(name);
+    reserved.iter().any(|reserved_name| {
+        let reserved_words = words(reserved_name);
+        skeleton(reserved_name) == name_skeleton
+            || (!reserved_words.is_empty()
+                && name_words
+                    .windows(reserved_words.len())
+                    .any(|window| window == reserved_words.as_slice()))
+    })
+}
+
+pub fn validate(name: &str, reserved: &[String]) -> Validity {
+    let name = normalize(name);
+    let length = name.chars().count();
+    let mut problems: Vec<String> = vec![];
+    if length < MIN_NAME_LENGTH {
+        problems.push(String::from(TOO_SHORT));
+    }
+    if length > MAX_NAME_LENGTH {
+        problems.push(String::from(TOO_LONG));
+    }
+    if name.contains(EMAIL_SEPARATOR) {
+        problems.push(String::from(CONTAINS_EMAIL_SEPARATOR));
+    }
+    if name.chars().any(char::is_control) {
+        problems.push(String::from(CONTAINS_CONTROL_CHARACTERS));
+    }
+    if is_reserved(&name, reserved) {
+        problems.push(String::from(RESERVED));
+    }
+    if problems.is_empty() {
+        Validity::Valid
+    } else {
+        Validity::Invalid(NameIssues {
+            message: String::from(INVALID_NAME_MESSAGE),
+            problems,
+        })
+    }
+}
+
+#[cfg(test)]
+mod name_tests {
+    use super::*;
+
+    fn reserved() -> Vec<String> {
+        vec![String::from("admin"), String::from("deputy mayor")]
+    }
+
+    fn is_valid(name: &str) -> bool {
+        match validate(name, &reserved()) {
+            Validity::Valid => true,
+            Validity::Invalid(_) => false,
+        }
+    }
+
+    #[test]
+    fn normalize_applies_compatibility_composition() {
+        assert_eq!(normalize("ｆｕｌｌｗｉｄｔｈ"), "fullwidth");
+    }
+
+    #[test]
+    fn normalize_trims_surrounding_whitespace() {
+        assert_eq!(normalize("  Alice "), "Alice");
+    }
+
+    #[test]
+    fn fold_ignores_case() {
+        assert_eq!(fold("ALICE"), fold("alice"));
+    }
+
+    #[test]
+    fn skeleton_matches_confusable_names() {
+        assert_eq!(skeleton("Alice"), skeleton("\u{0410}lice"));
+    }
+
+    #[test]
+    fn skeleton_differs_for_distinct_names() {
+        assert_ne!(skeleton("Alice"), skeleton("Alicia"));
+    }
+
+    #[test]
+    fn accepts_an_ordinary_name() {
+        assert!(is_valid("Marcus Ruddick"));
+    }
+
+    #[test]
+    fn rejects_names_containing_an_email_separator() {
+        assert!(!is_valid("alice@example.com"));
+    }
+
+    #[test]
+    fn rejects_names_that_are_too_short() {
+        assert!(!is_valid("al"));
+    }
+
+    #[test]
+    fn rejects_names_that_are_too_long() {
+        assert!(!is_valid(&"a".repeat(MAX_NAME_LENGTH + 1)));
+    }
+
+    #[test]
+    fn rejects_control_characters() {
+        assert!(!is_valid("ali\u{0007}ce"));
+    }
+
+    #[test]
+    fn rejects_reserved_names_regardless_of_case_or_confusables() {
+        assert!(!is_valid("ADMIN"));
+        assert!(!is_valid("\u{0430}dmin"));
+    }
+
+    #[test]
+    fn rejects_names_containing_a_reserved_title() {
+        assert!(!is_valid("Deputy Mayor Smith"));
+    }
+
+    #[test]
+    fn accepts_names_that_only_contain_a_reserved_word_as_a_substring() {
+        assert!(is_valid("badminton"));
+    }
+}
@@ -1,6 +1,11 @@
-use super::mock::{MockCredentials, MockLoginHistory, MockPasswordReset};
-use crate::{model, utilities::hash};
+use super::mock::{
+    MockAuditLog, MockChallengeVerifier, MockChallenges, MockCredentials, MockDevices, MockEmailChanges,
+    MockHealth, MockInvitations, MockLocator, MockLoginHistory, MockMagicLinks, MockMailer, MockPasswordReset,
+    MockPersonalAccessTokens, MockServiceClients,
+};
+use crate::{configuration::settings, model, utilities::hash};
 use fake::{faker::internet::en as internet, Fake};
+use once_cell::sync::OnceCell;
 
 mod credentials;
 mod failed_login;
@@ -9,17 +14,45 @@ mod request;
 pub use credentials::*;
 pub use failed_login::*;
 pub use request::*;
-use std::time::SystemTime;
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::{Duration, SystemTime},
+};
 
 const MAX_FAKE_PASSWORD_LENGTH: usize = 20;
 const MIN_FAKE_PASSWORD_LENGTH: usize = 15;
 const WEAK_PASSWORD: &str = "password";
 
-type MockServiceState = model::ServiceState<
-    MockLoginHistory<model::DatabaseConnection>,
-    MockCredentials<model::DatabaseConnection>,
-    MockPasswordReset<model::DatabaseConnection>,
->;
+static SETTINGS: OnceCell<settings::Settings> = OnceCell::new();
+
+#[derive(Clone)]
+pub struct MockDependencies;
+
+impl model::Dependencies for MockDependencies {
+    type LoginHistory = MockLoginHistory<model::DatabaseConnection>;
+    type Credentials = MockCredentials<model::DatabaseConnection>;
+    type PasswordReset = MockPasswordReset<model::DatabaseConnection>;
+    type MagicLinks = MockMagicLinks<model::DatabaseConnection>;
+    type EmailChanges = MockEmailChanges<model::DatabaseConnection>;
+    type Health = MockHealth<model::DatabaseConnection>;
+    type AuditLog = MockAuditLog<model::DatabaseConnection>;
+    type Invitations = MockInvitations<model::DatabaseConnection>;
+    type Challenges = MockChallenges<model::DatabaseConnection>;
+    type ChallengeVerifier = MockChallengeVerifier;
+    type Devices = MockDevices<model::DatabaseConnection>;
+    type ServiceClients = MockServiceClients<model::DatabaseConnection>;
+    type PersonalAccessTokens = MockPersonalAccessTokens<model::DatabaseConnection>;
+    type Locator = MockLocator;
+    type Mailer = MockMailer;
+}
+
+pub type MockServiceState = model::ServiceState<MockDependencies>;
+
+/// Settings loaded once for every test, as the service loads them once at
+/// startup.
+pub fn settings() -> &'static settings::Settings {
+    SETTINGS.get_or_init(|| settings::Settings::load().unwrap_or_else(|error| panic!("{}", error)))
+}
 
 pub fn strong_password() -> String {
     internet::Password(MIN_FAKE_PASSWORD_LENGTH..MAX_FAKE_PASSWORD_LENGTH).fake()
@@ -63,6 +96,156 @@ pub fn password_reset_data() -> model::ResetConfirmation {
     }
 }
 
+pub fn magic_link() -> model::MagicLink {
+    model::MagicLink {
+        id: hash::token(),
+        token: hash::token(),
+        user_id: numeric_id(),
+        email: email_address(),
+        created_at: SystemTime::now(),
+        used_at: None,
+    }
+}
+
+pub fn magic_link_confirmation() -> model::MagicLinkConfirmation {
+    model::MagicLinkConfirmation::new(&hash::token(), &hash::token())
+}
+
+/// A magic link whose hashed token matches `confirmation`.
+pub fn valid_magic_link(confirmation: &model::MagicLinkConfirmation) -> model::MagicLink {
+    let mut link = magic_link();
+    link.id = confirmation.id.clone();
+    link.token = hash::generate(&settings().hash, &confirmation.token).unwrap();
+    link
+}
+
+pub fn magic_link_request() -> model::MagicLinkRequest {
+    model::MagicLinkRequest::new(&email_address())
+}
+
+pub fn email_change() -> model::EmailChange {
+    model::EmailChange {
+        id: hash::token(),
+        user_id: numeric_id(),
+        old_email: email_address(),
+        new_email: email_address(),
+        token: hash::token(),
+        revert_token: hash::token(),
+        created_at: SystemTime::now(),
+        confirmed_at: None,
+        reverted_at: None,
+    }
+}
+
+pub fn email_change_confirmation() -> model::EmailChangeConfirmation {
+    model::EmailChangeConfirmation::new(&hash::token(), &hash::token())
+}
+
+/// A pending email change whose hashed token matches `confirmation`.
+pub fn pending_email_change(confirmation: &model::EmailChangeConfirmation) -> model::EmailChange {
+    let mut change = email_change();
+    change.id = confirmation.id.clone();
+    change.token = hash::generate(&settings().hash, &confirmation.token).unwrap();
+    change
+}
+
+/// An email change whose hashed revert token matches `confirmation`.
+pub fn revertible_email_change(confirmation: &model::EmailChangeConfirmation) -> model::EmailChange {
+    let mut change = email_change();
+    change.id = confirmation.id.clone();
+    change.revert_token = hash::generate(&settings().hash, &confirmation.token).unwrap();
+    change
+}
+
+pub fn invitation() -> model::Invitation {
+    model::Invitation {
+        id: 1,
+        token: hash::token(),
+        email: email_address(),
+        role: None,
+        created_by: numeric_id(),
+        created_at: SystemTime::now(),
+        expires_at: SystemTime::now() + Duration::from_secs(settings().registration.invitation_lifetime),
+        used_at: None,
+        used_by: None,
+    }
+}
+
+pub fn sighting() -> model::Sighting {
+    model::Sighting::new(Some("Mozilla/5.0"), Some("en-NZ"), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 27))))
+}
+
+pub fn device_alert() -> model::DeviceAlert {
+    let sighting = sighting();
+    model::DeviceAlert {
+        id: hash::token(),
+        user_id: numeric_id(),
+        token: hash::token(),
+        fingerprint: sighting.fingerprint,
+        network: sighting.network,
+        created_at: SystemTime::now(),
+        used_at: None,
+    }
+}
+
+pub fn device_alert_confirmation() -> model::DeviceAlertConfirmation {
+    model::DeviceAlertConfirmation::new(&hash::token(), &hash::token())
+}
+
+/// A device alert whose hashed token matches `confirmation`.
+pub fn disownable_device_alert(confirmation: &model::DeviceAlertConfirmation) -> model::DeviceAlert {
+    let mut alert = device_alert();
+    alert.id = confirmation.id.clone();
+    alert.token = hash::generate(&settings().hash, &confirmation.token).unwrap();
+    alert
+}
+
+pub fn service_client() -> model::ServiceClient {
+    model::ServiceClient {
+        id: 1,
+        client_id: hash::token(),
+        name: String::from("Bill importer"),
+        secret: hash::token(),
+        previous_secret: None,
+        previous_secret_expires_at: None,
+        scopes: vec![String::from("bills:write")],
+        created_by: numeric_id(),
+        created_at: SystemTime::now(),
+        rotated_at: None,
+        audiences: vec![],
+    }
+}
+
+pub fn service_client_request() -> model::ServiceClientRequest {
+    model::ServiceClientRequest {
+        name: String::from("Bill importer"),
+        scopes: vec![String::from("bills:write")],
+        audiences: vec![],
+    }
+}
+
+pub fn personal_access_token() -> model::PersonalAccessToken {
+    model::PersonalAccessToken {
+        id: hash::token(),
+        user_id: numeric_id(),
+        name: String::from("Bill scraper"),
+        token: hash::token(),
+        scopes: vec![String::from("bills:read")],
+        created_at: SystemTime::now(),
+        expires_at: None,
+        last_used_at: None,
+        revoked_at: None,
+    }
+}
+
+pub fn personal_access_token_request() -> model::PersonalAccessTokenRequest {
+    model::PersonalAccessTokenRequest {
+        name: String::from("Bill scraper"),
+        scopes: vec![String::from("bills:read")],
+        expires_in: None,
+    }
+}
+
 pub fn reset_request() -> model::ResetRequest {
     model::ResetRequest {
         email: email_address(),
@@ -73,5 +256,43 @@ pub fn service_state() -> MockServiceState {
     let mock_login_history = MockLoginHistory::<model::DatabaseConnection>::new();
     let mock_credentials = MockCredentials::<model::DatabaseConnection>::new();
     let mock_password_reset = MockPasswordReset::<model::DatabaseConnection>::new();
-    model::ServiceState::new(mock_login_history, mock_credentials, mock_password_reset)
+    let mock_magic_links = MockMagicLinks::<model::DatabaseConnection>::new();
+    let mock_email_changes = MockEmailChanges::<model::DatabaseConnection>::new();
+    let mock_health = MockHealth::<model::DatabaseConnection>::new();
+    let mock_audit_log = MockAuditLog::<model::DatabaseConnection>::new();
+    let mock_invitations = MockInvitations::<model::DatabaseConnection>::new();
+    let mock_challenges = MockChallenges::<model::DatabaseConnection>::new();
+    let mock_challenge_verifier = MockChallengeVerifier::new();
+    let mock_devices = MockDevices::<model::DatabaseConnection>::new();
+    let mock_service_clients = MockServiceClients::<model::DatabaseConnection>::new();
+    let mock_personal_access_tokens = MockPersonalAccessTokens::<model::DatabaseConnection>::new();
+    let mock_locator = MockLocator::new();
+    let mock_mailer = MockMailer::new();
+    model::ServiceState::new(
+        mock_login_history,
+        mock_credentials,
+        mock_password_reset,
+        mock_magic_links,
+        mock_email_changes,
+        mock_health,
+        mock_audit_log,
+        mock_invitations,
+        mock_challenges,
+        mock_challenge_verifier,
+        mock_devices,
+        mock_service_clients,
+        mock_personal_access_tokens,
+        mock_locator,
+        mock_mailer,
+        settings(),
+    )
+}
+
+/// A service state whose settings are adjusted by `configure`.
+pub fn service_state_with<F: FnOnce(&mut settings::Settings)>(configure: F) -> MockServiceState {
+    let mut settings = settings().clone();
+    configure(&mut settings);
+    let mut state = service_state();
+    state.settings = Box::leak(Box::new(settings));
+    state
 }
@@ -6,6 +6,7 @@ pub fn full_request() -> model::FullRequest {
         name: user_name(),
         email: email_address(),
         password: strong_password(),
+        invitation: None,
     }
 }
 
@@ -37,3 +38,11 @@ pub fn update_credentials_request() -> model::UpdateCredentials {
         credentials: credentials_request(),
     }
 }
+
+pub fn login_request() -> model::LoginRequest {
+    model::LoginRequest::new(&user_name(), &strong_password())
+}
+
+pub fn email_login_request() -> model::LoginRequest {
+    model::LoginRequest::new(&email_address(), &strong_password())
+}
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use crate::{challenge, error, model, Result};
+use mocking::Method;
+
+type MockIssue = Method<model::Challenge, error::Error>;
+type MockVerify = Method<bool, error::Error>;
+
+#[derive(Clone)]
+pub struct MockChallengeVerifier {
+    pub issue: MockIssue,
+    pub verify: MockVerify,
+}
+
+impl MockChallengeVerifier {
+    pub fn new() -> MockChallengeVerifier {
+        MockChallengeVerifier {
+            issue: MockIssue::new("challenge::Verifier.issue()"),
+            verify: MockVerify::new("challenge::Verifier.verify()"),
+        }
+    }
+}
+
+#[async_trait]
+impl challenge::Verifier for MockChallengeVerifier {
+    async fn issue(&self, _failures: i64) -> Result<model::Challenge> {
+        self.issue.call()
+    }
+    async fn verify(&self, _response: &model::ChallengeResponse, _failures: i64) -> Result<bool> {
+        self.verify.call()
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{error, geoip};
+use mocking::Method;
+use std::net::IpAddr;
+
+type MockCountry = Method<Option<String>, error::Error>;
+
+#[derive(Clone)]
+pub struct MockLocator {
+    pub country: MockCountry,
+}
+
+impl MockLocator {
+    pub fn new() -> MockLocator {
+        MockLocator {
+            country: MockCountry::new("geoip::Locator.country()"),
+        }
+    }
+}
+
+impl geoip::Locator for MockLocator {
+    fn country(&self, _address: IpAddr) -> Option<String> {
+        self.country.call().unwrap_or(None)
+    }
+}
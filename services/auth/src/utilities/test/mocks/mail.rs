@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use crate::{error, mail, Result};
+use mocking::Method;
+
+type MockSend = Method<(), error::Error>;
+
+#[derive(Clone)]
+pub struct MockMailer {
+    pub send: MockSend,
+}
+
+impl MockMailer {
+    pub fn new() -> MockMailer {
+        MockMailer {
+            send: MockSend::new("mail::Mailer.send()"),
+        }
+    }
+}
+
+#[async_trait]
+impl mail::Mailer for MockMailer {
+    async fn send(&self, _message: &mail::Message) -> Result<()> {
+        self.send.call()
+    }
+}
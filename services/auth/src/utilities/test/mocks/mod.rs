@@ -1,3 +1,9 @@
+mod challenge;
+mod geoip;
+mod mail;
 mod repository;
 
+pub use challenge::*;
+pub use geoip::*;
+pub use mail::*;
 pub use repository::*;
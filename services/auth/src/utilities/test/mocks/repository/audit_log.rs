@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+
+type MockQuery = Method<Vec<model::AuditEvent>, error::Error>;
+type MockVerification = Method<model::AuditVerification, error::Error>;
+type MockRecord = Method<(), error::Error>;
+
+#[derive(Clone)]
+pub struct MockAuditLog<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub query: MockQuery,
+    pub verify: MockVerification,
+    pub record: MockRecord,
+}
+
+impl<T: model::Database> MockAuditLog<T> {
+    pub fn new() -> MockAuditLog<T> {
+        MockAuditLog {
+            phantom: PhantomData,
+            query: MockQuery::new("repository::AuditLog.query()"),
+            verify: MockVerification::new("repository::AuditLog.verify()"),
+            record: MockRecord::new("repository::AuditLog.record()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::AuditLog for MockAuditLog<T> {
+    async fn query(&self, _filter: &model::AuditFilter) -> Result<Vec<model::AuditEvent>> {
+        self.query.call()
+    }
+    async fn verify(&self) -> Result<model::AuditVerification> {
+        self.verify.call()
+    }
+    async fn record(
+        &self,
+        _event_type: model::AuditEventType,
+        _user_id: Option<model::CredentialId>,
+        _details: serde_json::Value,
+    ) -> Result<()> {
+        self.record.call()
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+use std::time::{Duration, SystemTime};
+
+type MockFailures = Method<i64, error::Error>;
+type MockRecordFailure = Method<(), error::Error>;
+type MockClear = Method<(), error::Error>;
+type MockRedeem = Method<bool, error::Error>;
+
+#[derive(Clone)]
+pub struct MockChallenges<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub failures: MockFailures,
+    pub record_failure: MockRecordFailure,
+    pub clear: MockClear,
+    pub redeem: MockRedeem,
+}
+
+impl<T: model::Database> MockChallenges<T> {
+    pub fn new() -> MockChallenges<T> {
+        MockChallenges {
+            phantom: PhantomData,
+            failures: MockFailures::new("repository::Challenges.failures()"),
+            record_failure: MockRecordFailure::new("repository::Challenges.record_failure()"),
+            clear: MockClear::new("repository::Challenges.clear()"),
+            redeem: MockRedeem::new("repository::Challenges.redeem()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::Challenges for MockChallenges<T> {
+    async fn failures(&self, _keys: &[String], _window: Duration) -> Result<i64> {
+        self.failures.call()
+    }
+    async fn record_failure(&self, _keys: &[String], _window: Duration) -> Result<()> {
+        self.record_failure.call()
+    }
+    async fn clear(&self, _keys: &[String]) -> Result<()> {
+        self.clear.call()
+    }
+    async fn redeem(&self, _digest: &str, _expires_at: SystemTime) -> Result<bool> {
+        self.redeem.call()
+    }
+}
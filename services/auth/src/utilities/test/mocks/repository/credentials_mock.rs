@@ -7,6 +7,7 @@ type MockedOptionCredentials = mocking::Method<Option<model::Credentials>, Error
 type MockedStatusResult = mocking::Method<repository::CredentialStatus, Error>;
 type MockedCountResult = mocking::Method<i32, Error>;
 type MockedCredentials = mocking::Method<model::Credentials, Error>;
+type MockedEmailChange = mocking::Method<(model::Credentials, model::EmailChange), Error>;
 
 #[derive(Clone)]
 pub struct MockCredentials<T: model::Database> {
@@ -15,8 +16,11 @@ pub struct MockCredentials<T: model::Database> {
     pub by_id: MockedOptionCredentials,
     pub get_status: MockedStatusResult,
     pub update_credentials: MockedCredentials,
+    pub update_with_email_change: MockedEmailChange,
+    pub update_with_confirmed_email_change: MockedOptionCredentials,
     pub update_password_hash: MockedCredentials,
     pub save_credentials: MockedCredentials,
+    pub save_invited_credentials: MockedOptionCredentials,
     pub mark_as_deleted_by_email: MockedCountResult,
     phantom: PhantomData<T>,
 }
@@ -31,8 +35,17 @@ impl<T: model::Database> MockCredentials<T> {
             update_credentials: MockedCredentials::new(
                 "repository::Credentials.update_credentials()",
             ),
+            update_with_email_change: MockedEmailChange::new(
+                "repository::Credentials.update_with_email_change()",
+            ),
+            update_with_confirmed_email_change: MockedOptionCredentials::new(
+                "repository::Credentials.update_with_confirmed_email_change()",
+            ),
             update_password_hash: MockedCredentials::new("repository::Credentials.update_password_hash()"),
             save_credentials: MockedCredentials::new("repository::Credentials.save_credentials()"),
+            save_invited_credentials: MockedOptionCredentials::new(
+                "repository::Credentials.save_invited_credentials()",
+            ),
             mark_as_deleted_by_email: MockedCountResult::new(
                 "repository::Credentials.mark_as_deleted_by_email()",
             ),
@@ -95,6 +108,20 @@ impl<T: model::Database> repository::Credentials for MockCredentials<T> {
     ) -> Result<model::Credentials> {
         self.update_credentials.call()
     }
+    async fn update_with_email_change(
+        &self,
+        _credentials: &model::Credentials,
+        _new_email: &str,
+    ) -> Result<(model::Credentials, model::EmailChange)> {
+        self.update_with_email_change.call()
+    }
+    async fn update_with_confirmed_email_change(
+        &self,
+        _credentials: &model::Credentials,
+        _change_id: &str,
+    ) -> CredentialResults {
+        self.update_with_confirmed_email_change.call()
+    }
     async fn update_password_hash(&self, _id: &i32, _hash: &str) -> Result<model::Credentials> {
         self.update_password_hash.call()
     }
@@ -104,6 +131,13 @@ impl<T: model::Database> repository::Credentials for MockCredentials<T> {
     ) -> Result<model::Credentials> {
         self.save_credentials.call()
     }
+    async fn save_invited_credentials(
+        &self,
+        _credentials: &model::FullRequest,
+        _invitation: &model::Invitation,
+    ) -> CredentialResults {
+        self.save_invited_credentials.call()
+    }
     async fn mark_as_deleted_by_email(&self, _email: &str) -> Result<i32> {
         self.mark_as_deleted_by_email.call()
     }
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+
+type MockObserve = Method<Vec<model::Novelty>, error::Error>;
+type MockAlert = Method<model::DeviceAlert, error::Error>;
+type MockAlertById = Method<Option<model::DeviceAlert>, error::Error>;
+type MockDisown = Method<bool, error::Error>;
+type MockRevocation = Method<Option<model::SessionRevocation>, error::Error>;
+
+#[derive(Clone)]
+pub struct MockDevices<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub observe: MockObserve,
+    pub alert: MockAlert,
+    pub alert_by_id: MockAlertById,
+    pub disown: MockDisown,
+    pub revocation: MockRevocation,
+}
+
+impl<T: model::Database> MockDevices<T> {
+    pub fn new() -> MockDevices<T> {
+        MockDevices {
+            phantom: PhantomData,
+            observe: MockObserve::new("repository::Devices.observe()"),
+            alert: MockAlert::new("repository::Devices.alert()"),
+            alert_by_id: MockAlertById::new("repository::Devices.alert_by_id()"),
+            disown: MockDisown::new("repository::Devices.disown()"),
+            revocation: MockRevocation::new("repository::Devices.revocation()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::Devices for MockDevices<T> {
+    async fn observe(&self, _user_id: model::CredentialId, _sighting: &model::Sighting) -> Result<Vec<model::Novelty>> {
+        self.observe.call()
+    }
+    async fn alert(&self, _user_id: model::CredentialId, _sighting: &model::Sighting) -> Result<model::DeviceAlert> {
+        self.alert.call()
+    }
+    async fn alert_by_id(&self, _id: &str) -> Result<Option<model::DeviceAlert>> {
+        self.alert_by_id.call()
+    }
+    async fn disown(&self, _alert: &model::DeviceAlert) -> Result<bool> {
+        self.disown.call()
+    }
+    async fn revocation(&self, _user_id: model::CredentialId) -> Result<Option<model::SessionRevocation>> {
+        self.revocation.call()
+    }
+}
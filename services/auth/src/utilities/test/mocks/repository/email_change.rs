@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+
+type MockEmailChange = Method<model::EmailChange, error::Error>;
+type MockOptionalEmailChange = Method<Option<model::EmailChange>, error::Error>;
+type MockUpdate = Method<bool, error::Error>;
+
+#[derive(Clone)]
+pub struct MockEmailChanges<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub request: MockEmailChange,
+    pub by_id: MockOptionalEmailChange,
+    pub revert: MockUpdate,
+}
+
+impl<T: model::Database> MockEmailChanges<T> {
+    pub fn new() -> MockEmailChanges<T> {
+        MockEmailChanges {
+            phantom: PhantomData,
+            request: MockEmailChange::new("repository::EmailChanges.request()"),
+            by_id: MockOptionalEmailChange::new("repository::EmailChanges.by_id()"),
+            revert: MockUpdate::new("repository::EmailChanges.revert()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::EmailChanges for MockEmailChanges<T> {
+    async fn request(&self, _credentials: &model::Credentials, _new_email: &str) -> Result<model::EmailChange> {
+        self.request.call()
+    }
+    async fn by_id(&self, _id: &str) -> Result<Option<model::EmailChange>> {
+        self.by_id.call()
+    }
+    async fn revert(&self, _id: &str) -> Result<bool> {
+        self.revert.call()
+    }
+}
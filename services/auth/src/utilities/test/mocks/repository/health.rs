@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+
+type MockCheck = Method<(), error::Error>;
+type MockPool = Method<database::PoolState, error::Error>;
+
+#[derive(Clone)]
+pub struct MockHealth<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub database: MockCheck,
+    pub pool: MockPool,
+}
+
+impl<T: model::Database> MockHealth<T> {
+    pub fn new() -> MockHealth<T> {
+        MockHealth {
+            phantom: PhantomData,
+            database: MockCheck::new("repository::Health.database()"),
+            pool: MockPool::new("repository::Health.pool()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::Health for MockHealth<T> {
+    async fn database(&self) -> Result<()> {
+        self.database.call()
+    }
+    fn pool(&self) -> database::PoolState {
+        self.pool.call().unwrap_or_default()
+    }
+}
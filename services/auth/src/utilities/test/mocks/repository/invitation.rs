@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+use std::time::Duration;
+
+type MockCreate = Method<model::Invitation, error::Error>;
+type MockByToken = Method<Option<model::Invitation>, error::Error>;
+
+#[derive(Clone)]
+pub struct MockInvitations<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub create: MockCreate,
+    pub by_token: MockByToken,
+}
+
+impl<T: model::Database> MockInvitations<T> {
+    pub fn new() -> MockInvitations<T> {
+        MockInvitations {
+            phantom: PhantomData,
+            create: MockCreate::new("repository::Invitations.create()"),
+            by_token: MockByToken::new("repository::Invitations.by_token()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::Invitations for MockInvitations<T> {
+    async fn create(
+        &self,
+        _request: &model::InvitationRequest,
+        _created_by: model::CredentialId,
+        _lifetime: Duration,
+    ) -> Result<model::Invitation> {
+        self.create.call()
+    }
+    async fn by_token(&self, _token: &str) -> Result<Option<model::Invitation>> {
+        self.by_token.call()
+    }
+}
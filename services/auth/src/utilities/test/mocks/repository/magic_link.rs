@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+
+type MockMagicLink = Method<Option<model::MagicLink>, error::Error>;
+type MockConsumption = Method<bool, error::Error>;
+
+#[derive(Clone)]
+pub struct MockMagicLinks<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub generate: MockMagicLink,
+    pub by_id: MockMagicLink,
+    pub consume: MockConsumption,
+}
+
+impl<T: model::Database> MockMagicLinks<T> {
+    pub fn new() -> MockMagicLinks<T> {
+        MockMagicLinks {
+            phantom: PhantomData,
+            generate: MockMagicLink::new("repository::MagicLinks.generate()"),
+            by_id: MockMagicLink::new("repository::MagicLinks.by_id()"),
+            consume: MockConsumption::new("repository::MagicLinks.consume()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::MagicLinks for MockMagicLinks<T> {
+    async fn generate(&self, _email: &str) -> Result<Option<model::MagicLink>> {
+        self.generate.call()
+    }
+    async fn by_id(&self, _id: &str) -> Result<Option<model::MagicLink>> {
+        self.by_id.call()
+    }
+    async fn consume(&self, _id: &str) -> Result<bool> {
+        self.consume.call()
+    }
+}
@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+
+type MockPending = Method<Vec<model::OutboxEvent>, error::Error>;
+type MockPublished = Method<u64, error::Error>;
+
+#[derive(Clone)]
+pub struct MockOutbox<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub pending: MockPending,
+    pub mark_published: MockPublished,
+}
+
+impl<T: model::Database> MockOutbox<T> {
+    pub fn new() -> MockOutbox<T> {
+        MockOutbox {
+            phantom: PhantomData,
+            pending: MockPending::new("repository::Outbox.pending()"),
+            mark_published: MockPublished::new("repository::Outbox.mark_published()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::Outbox for MockOutbox<T> {
+    async fn pending(&self, _limit: i64) -> Result<Vec<model::OutboxEvent>> {
+        self.pending.call()
+    }
+    async fn mark_published(&self, _ids: &[i64]) -> Result<u64> {
+        self.mark_published.call()
+    }
+}
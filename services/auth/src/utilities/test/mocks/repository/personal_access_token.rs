@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+use std::time::SystemTime;
+
+type MockCreate = Method<model::PersonalAccessToken, error::Error>;
+type MockById = Method<Option<model::PersonalAccessToken>, error::Error>;
+type MockForUser = Method<Vec<model::PersonalAccessToken>, error::Error>;
+type MockRevoke = Method<bool, error::Error>;
+type MockUsed = Method<(), error::Error>;
+
+#[derive(Clone)]
+pub struct MockPersonalAccessTokens<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub create: MockCreate,
+    pub by_id: MockById,
+    pub for_user: MockForUser,
+    pub revoke: MockRevoke,
+    pub used: MockUsed,
+}
+
+impl<T: model::Database> MockPersonalAccessTokens<T> {
+    pub fn new() -> MockPersonalAccessTokens<T> {
+        MockPersonalAccessTokens {
+            phantom: PhantomData,
+            create: MockCreate::new("repository::PersonalAccessTokens.create()"),
+            by_id: MockById::new("repository::PersonalAccessTokens.by_id()"),
+            for_user: MockForUser::new("repository::PersonalAccessTokens.for_user()"),
+            revoke: MockRevoke::new("repository::PersonalAccessTokens.revoke()"),
+            used: MockUsed::new("repository::PersonalAccessTokens.used()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::PersonalAccessTokens for MockPersonalAccessTokens<T> {
+    async fn create(
+        &self,
+        _user_id: model::CredentialId,
+        _request: &model::PersonalAccessTokenRequest,
+        _expires_at: Option<SystemTime>,
+    ) -> Result<model::PersonalAccessToken> {
+        self.create.call()
+    }
+    async fn by_id(&self, _id: &str) -> Result<Option<model::PersonalAccessToken>> {
+        self.by_id.call()
+    }
+    async fn for_user(&self, _user_id: model::CredentialId) -> Result<Vec<model::PersonalAccessToken>> {
+        self.for_user.call()
+    }
+    async fn revoke(&self, _user_id: model::CredentialId, _id: &str) -> Result<bool> {
+        self.revoke.call()
+    }
+    async fn used(&self, _id: &str) -> Result<()> {
+        self.used.call()
+    }
+}
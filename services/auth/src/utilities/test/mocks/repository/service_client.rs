@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use crate::{model, error, repository, Result};
+use mocking::Method;
+use serde::export::PhantomData;
+use std::time::Duration;
+
+type MockCreate = Method<model::ServiceClient, error::Error>;
+type MockByClientId = Method<Option<model::ServiceClient>, error::Error>;
+type MockRotate = Method<Option<model::ServiceClient>, error::Error>;
+
+#[derive(Clone)]
+pub struct MockServiceClients<T: model::Database> {
+    phantom: PhantomData<T>,
+    pub create: MockCreate,
+    pub by_client_id: MockByClientId,
+    pub rotate: MockRotate,
+}
+
+impl<T: model::Database> MockServiceClients<T> {
+    pub fn new() -> MockServiceClients<T> {
+        MockServiceClients {
+            phantom: PhantomData,
+            create: MockCreate::new("repository::ServiceClients.create()"),
+            by_client_id: MockByClientId::new("repository::ServiceClients.by_client_id()"),
+            rotate: MockRotate::new("repository::ServiceClients.rotate()"),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: model::Database> repository::ServiceClients for MockServiceClients<T> {
+    async fn create(
+        &self,
+        _request: &model::ServiceClientRequest,
+        _created_by: model::CredentialId,
+    ) -> Result<model::ServiceClient> {
+        self.create.call()
+    }
+    async fn by_client_id(&self, _client_id: &str) -> Result<Option<model::ServiceClient>> {
+        self.by_client_id.call()
+    }
+    async fn rotate(
+        &self,
+        _client_id: &str,
+        _rotated_by: model::CredentialId,
+        _grace: Duration,
+    ) -> Result<Option<model::ServiceClient>> {
+        self.rotate.call()
+    }
+}
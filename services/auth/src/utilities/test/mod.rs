@@ -1,4 +1,21 @@
+use crate::model;
+use actix_web::{body::Body, HttpResponse};
+use std::time::Duration;
+
 pub mod fake;
 pub mod mocks;
 
 pub use mocks as mock;
+
+pub fn error_response(response: &HttpResponse) -> model::ErrorResponse {
+    match response.body().as_ref() {
+        Some(Body::Bytes(bytes)) => serde_json::from_slice(bytes).unwrap(),
+        _ => panic!("Response did not contain a JSON body"),
+    }
+}
+
+/// Gives tasks spawned during a test the chance to run before it asserts on
+/// what they did.
+pub async fn settle() {
+    actix_rt::time::delay_for(Duration::from_millis(1)).await;
+}
@@ -0,0 +1,61 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    model,
+    repository::AuditLog,
+    routes,
+    routes::{AUDIT_ROUTE, CREDENTIALS_ROUTE},
+};
+
+#[actix_rt::test]
+async fn records_account_creation_in_the_audit_log() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let request_data = model::FullRequest::new(&name, &email, &password);
+    let req = test::TestRequest::post()
+        .uri(CREDENTIALS_ROUTE)
+        .set_json(&request_data)
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    test::call_service(&mut server, req).await;
+    let saved_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    db.delete_credentials_by_name(&name).await;
+    let filter = model::AuditFilter {
+        user_id: Some(saved_credentials.id),
+        ..model::AuditFilter::default()
+    };
+    let events = data.audit_log.query(&filter).await.unwrap();
+    assert_eq!(events[0].event_type, model::AuditEventType::AccountCreated.name());
+}
+
+#[actix_rt::test]
+async fn keeps_an_intact_hash_chain() {
+    let data = helper::init_data().await;
+    let verification = data.audit_log.verify().await.unwrap();
+    assert!(verification.valid);
+}
+
+#[actix_rt::test]
+async fn requires_an_admin_session() {
+    let data = helper::init_data().await;
+    let req = test::TestRequest::get()
+        .uri(AUDIT_ROUTE)
+        .header(http::header::AUTHORIZATION, "Bearer invalid")
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::UNAUTHORIZED);
+}
@@ -0,0 +1,28 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use btp_auth_server::{model, repository::Challenges, utilities::hash};
+use std::time::{Duration, SystemTime};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[actix_rt::test]
+async fn counts_and_clears_failures() {
+    let data = helper::init_data().await;
+    let (_, email, _) = helper::fake_credentials();
+    let keys = vec![model::account_key(&email)];
+    data.challenges.record_failure(&keys, WINDOW).await.unwrap();
+    data.challenges.record_failure(&keys, WINDOW).await.unwrap();
+    assert_eq!(data.challenges.failures(&keys, WINDOW).await.unwrap(), 2);
+    data.challenges.clear(&keys).await.unwrap();
+    assert_eq!(data.challenges.failures(&keys, WINDOW).await.unwrap(), 0);
+}
+
+#[actix_rt::test]
+async fn redeems_a_challenge_only_once() {
+    let data = helper::init_data().await;
+    let digest = hash::digest(&hash::token());
+    let expires_at = SystemTime::now() + WINDOW;
+    assert!(data.challenges.redeem(&digest, expires_at).await.unwrap());
+    assert!(!data.challenges.redeem(&digest, expires_at).await.unwrap());
+}
@@ -0,0 +1,64 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{test, App};
+use btp_auth_server::{
+    model,
+    repository::Devices,
+    routes,
+    routes::{DEVICE_DISOWN_ROUTE, DEVICE_ROUTE},
+};
+use std::net::{IpAddr, Ipv4Addr};
+
+fn sighting(agent: &str, last_octet: u8) -> model::Sighting {
+    model::Sighting::new(Some(agent), Some("en-NZ"), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, last_octet))))
+}
+
+#[actix_rt::test]
+async fn only_reports_devices_after_the_first_sighting() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    let first = data.devices.observe(user_id, &sighting("Firefox", 1)).await.unwrap();
+    let same_network = data.devices.observe(user_id, &sighting("Firefox", 200)).await.unwrap();
+    let new_device = data.devices.observe(user_id, &sighting("Chrome", 1)).await.unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert!(first.is_empty());
+    assert!(same_network.is_empty());
+    assert_eq!(new_device, vec![model::Novelty::Device]);
+}
+
+#[actix_rt::test]
+async fn disowning_a_device_revokes_sessions_and_locks_the_password() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    let alert = data.devices.alert(user_id, &sighting("Chrome", 1)).await.unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("{}{}", DEVICE_ROUTE, DEVICE_DISOWN_ROUTE))
+        .set_json(&model::DeviceAlertConfirmation::new(&alert.id, &alert.token))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    let repeated = test::TestRequest::post()
+        .uri(&format!("{}{}", DEVICE_ROUTE, DEVICE_DISOWN_ROUTE))
+        .set_json(&model::DeviceAlertConfirmation::new(&alert.id, &alert.token))
+        .to_request();
+    let repeated = test::call_service(&mut server, repeated).await;
+    let revocation = data.devices.revocation(user_id).await.unwrap();
+    let stored_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    assert_eq!(repeated.status(), status_codes::GONE);
+    assert!(revocation.is_some());
+    assert_ne!(stored_credentials.hash.trim(), password);
+}
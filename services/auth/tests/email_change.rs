@@ -0,0 +1,160 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    routes::EMAIL_CHANGE_ROUTE,
+    utilities::hash,
+    routes,
+    model,
+};
+use std::time::SystemTime;
+
+fn email_change(
+    user_id: model::CredentialId,
+    old_email: &str,
+    new_email: &str,
+    id: &str,
+    token: &str,
+    revert_token: &str,
+) -> model::EmailChange {
+    model::EmailChange {
+        id: String::from(id),
+        user_id,
+        old_email: String::from(old_email),
+        new_email: String::from(new_email),
+        token: hash::generate(&helper::settings().hash, token).unwrap(),
+        revert_token: hash::generate(&helper::settings().hash, revert_token).unwrap(),
+        created_at: SystemTime::now(),
+        confirmed_at: None,
+        reverted_at: None,
+    }
+}
+
+#[actix_rt::test]
+async fn confirming_a_change_updates_the_email() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (_name2, email2, ..) = helper::fake_credentials();
+    let (id, token, revert_token) = (hash::token(), hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    db.add_email_change(&email_change(user_id, &email, &email2, &id, &token, &revert_token))
+        .await
+        .unwrap();
+    let req = test::TestRequest::put()
+        .uri(EMAIL_CHANGE_ROUTE)
+        .set_json(&model::EmailChangeConfirmation::new(&id, &token))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let resp = test::call_service(&mut server, req).await;
+    let stored_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    assert!(resp.headers().contains_key(http::header::AUTHORIZATION));
+    assert_eq!(&stored_credentials.email, &email2);
+}
+
+#[actix_rt::test]
+async fn confirming_with_the_revert_token_is_unauthorized() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (_name2, email2, ..) = helper::fake_credentials();
+    let (id, token, revert_token) = (hash::token(), hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    db.add_email_change(&email_change(user_id, &email, &email2, &id, &token, &revert_token))
+        .await
+        .unwrap();
+    let req = test::TestRequest::put()
+        .uri(EMAIL_CHANGE_ROUTE)
+        .set_json(&model::EmailChangeConfirmation::new(&id, &revert_token))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let resp = test::call_service(&mut server, req).await;
+    let stored_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::UNAUTHORIZED);
+    assert_eq!(&stored_credentials.email, &email);
+}
+
+#[actix_rt::test]
+async fn reverting_a_pending_change_prevents_confirmation() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (_name2, email2, ..) = helper::fake_credentials();
+    let (id, token, revert_token) = (hash::token(), hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    db.add_email_change(&email_change(user_id, &email, &email2, &id, &token, &revert_token))
+        .await
+        .unwrap();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let revert = test::TestRequest::delete()
+        .uri(EMAIL_CHANGE_ROUTE)
+        .set_json(&model::EmailChangeConfirmation::new(&id, &revert_token))
+        .to_request();
+    let confirm = test::TestRequest::put()
+        .uri(EMAIL_CHANGE_ROUTE)
+        .set_json(&model::EmailChangeConfirmation::new(&id, &token))
+        .to_request();
+    let reverted = test::call_service(&mut server, revert).await;
+    let confirmed = test::call_service(&mut server, confirm).await;
+    let stored_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(reverted.status(), status_codes::OKAY);
+    assert_eq!(confirmed.status(), status_codes::GONE);
+    assert_eq!(&stored_credentials.email, &email);
+}
+
+#[actix_rt::test]
+async fn reverting_a_confirmed_change_restores_the_old_email() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (_name2, email2, ..) = helper::fake_credentials();
+    let (id, token, revert_token) = (hash::token(), hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    db.add_email_change(&email_change(user_id, &email, &email2, &id, &token, &revert_token))
+        .await
+        .unwrap();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let confirm = test::TestRequest::put()
+        .uri(EMAIL_CHANGE_ROUTE)
+        .set_json(&model::EmailChangeConfirmation::new(&id, &token))
+        .to_request();
+    let revert = test::TestRequest::delete()
+        .uri(EMAIL_CHANGE_ROUTE)
+        .set_json(&model::EmailChangeConfirmation::new(&id, &revert_token))
+        .to_request();
+    test::call_service(&mut server, confirm).await;
+    let resp = test::call_service(&mut server, revert).await;
+    let stored_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    assert_eq!(&stored_credentials.email, &email);
+}
@@ -0,0 +1,34 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{test, App};
+use btp_auth_server::{
+    routes::{HEALTH_ROUTE, LIVE_ROUTE, READY_ROUTE},
+    routes,
+};
+
+#[actix_rt::test]
+async fn reports_the_service_as_live() {
+    let req = test::TestRequest::get()
+        .uri(&format!("{}{}", HEALTH_ROUTE, LIVE_ROUTE))
+        .to_request();
+    let mut server = test::init_service(App::new().configure(routes::configuration)).await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+}
+
+#[actix_rt::test]
+async fn reports_the_service_as_ready_when_the_database_responds() {
+    let data = helper::init_data().await;
+    let req = test::TestRequest::get()
+        .uri(&format!("{}{}", HEALTH_ROUTE, READY_ROUTE))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+}
@@ -2,16 +2,18 @@ extern crate btp_auth_server;
 
 use actix_web::web;
 use btp_auth_server::{
-    configuration::database::TEST_DATABASE_CONFIG,
+    configuration::Settings,
     model::{
         credentials::query::SUSPEND,
         CredentialId,
     },
+    repository,
     Result,
     model,
 };
 use fake::faker::{internet::en as internet, name::en as name};
 use fake::Fake;
+use once_cell::sync::OnceCell;
 
 const DATABASE_INITIALIZATION_FAILURE: &str = "Failed to initialize database";
 const CREATE_OR_UPDATE_FAILED_LOGIN: &str = "INSERT INTO auth.failed_login(user_id, attempts, updated_at) VALUES ($1, $2, CURRENT_TIMESTAMP);";
@@ -19,6 +21,10 @@ const GET_FAILED_LOGIN_HISTORY: &str =
     "SELECT user_id, attempts, created_at, updated_at FROM auth.failed_login WHERE user_id = $1;";
 const CREATE_FAILED_LOGIN: &str = "INSERT INTO auth.failed_login(user_id, created_at, updated_at, attempts) VALUES ($1, $2, $3, $4);";
 const GET_RESET_REQUEST_BY_USER_ID: &str = "SELECT id, user_id, reset_token, name, email, created_at FROM auth.password_reset WHERE user_id = $1";
+const GET_MAGIC_LINK_BY_USER_ID: &str = "SELECT id, user_id, token, email, created_at, used_at FROM auth.magic_link WHERE user_id = $1";
+const CREATE_MAGIC_LINK: &str = "INSERT INTO auth.magic_link(id, user_id, token, email, created_at) VALUES($1, $2, $3, $4, $5) RETURNING id, user_id, token, email, created_at, used_at";
+const GET_EMAIL_CHANGES_BY_USER_ID: &str = "SELECT id, user_id, old_email, new_email, token, revert_token, created_at, confirmed_at, reverted_at FROM auth.email_change WHERE user_id = $1";
+const CREATE_EMAIL_CHANGE: &str = "INSERT INTO auth.email_change(id, user_id, old_email, new_email, token, revert_token, created_at, confirmed_at) VALUES($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id, user_id, old_email, new_email, token, revert_token, created_at, confirmed_at, reverted_at";
 const CREATE_RESET_REQUEST: &str = "INSERT INTO auth.password_reset(id, user_id, reset_token, name, email, created_at) VALUES($1, $2, $3, $4, $5, $6) RETURNING id, user_id, reset_token, name, email, created_at";
 
 const MAX_FAKE_PASSWORD_LENGTH: usize = 20;
@@ -26,11 +32,17 @@ const MIN_FAKE_PASSWORD_LENGTH: usize = 15;
 
 pub const WEAK_PASSWORD: &str = "password";
 
+static SETTINGS: OnceCell<Settings> = OnceCell::new();
+
+pub fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(|| Settings::load().unwrap_or_else(|error| panic!("{}", error)))
+}
+
 pub async fn init_data() -> web::Data<model::AppServiceState> {
-    let db = model::DatabaseConnection::new(TEST_DATABASE_CONFIG)
+    let db = model::DatabaseConnection::new(settings().database.configuration())
         .await
         .expect(DATABASE_INITIALIZATION_FAILURE);
-    web::Data::new(model::initialize_state(&db))
+    web::Data::new(model::initialize_state(&db, settings()))
 }
 
 pub fn fake_credentials() -> (String, String, String) {
@@ -47,10 +59,10 @@ pub struct Helper {
 
 impl Helper {
     pub async fn new() -> Result<Helper> {
-        let db = model::DatabaseConnection::new(TEST_DATABASE_CONFIG).await?;
+        let db = model::DatabaseConnection::new(settings().database.configuration()).await?;
         Ok(Helper {
             db: db.clone(),
-            state: model::initialize_state(&db),
+            state: model::initialize_state(&db, settings()),
         })
     }
     pub async fn get_credentials_by_name(
@@ -64,6 +76,7 @@ impl Helper {
             name,
             email,
             password,
+            ..
         }: &model::FullRequest = request;
         let query =
             String::from("INSERT INTO auth.credentials(name, hash, email) VALUES ($1, $2, $3)");
@@ -75,6 +88,9 @@ impl Helper {
             .await
             .unwrap();
     }
+    pub async fn backfill_name_skeletons(&self) -> Vec<CredentialId> {
+        repository::backfill_name_skeletons(&self.db).await.unwrap()
+    }
     pub async fn delete_credentials_by_name(&self, name: &str) {
         let db = &self.db;
         db.client()
@@ -160,4 +176,63 @@ impl Helper {
             .unwrap()
             .clone())
     }
+    pub async fn get_magic_links(
+        &self,
+        user_id: &CredentialId,
+    ) -> Result<Vec<model::MagicLink>> {
+        let db = &self.db;
+        let client = &db.client().await?;
+        let stmt = client.prepare(GET_MAGIC_LINK_BY_USER_ID).await?;
+        Ok(client.query::<model::MagicLink>(&stmt, &[&user_id]).await?)
+    }
+    pub async fn add_magic_link(
+        &self,
+        link: &model::MagicLink,
+    ) -> Result<model::MagicLink> {
+        let db = &self.db;
+        let client = &db.client().await?;
+        let stmt = client.prepare(CREATE_MAGIC_LINK).await?;
+        Ok(client.query::<model::MagicLink>(
+            &stmt,
+            &[
+                &link.id,
+                &link.user_id,
+                &link.token,
+                &link.email,
+                &link.created_at,
+            ],
+        ).await?
+            .remove(0))
+    }
+    pub async fn get_email_changes(
+        &self,
+        user_id: &CredentialId,
+    ) -> Result<Vec<model::EmailChange>> {
+        let db = &self.db;
+        let client = &db.client().await?;
+        let stmt = client.prepare(GET_EMAIL_CHANGES_BY_USER_ID).await?;
+        Ok(client.query::<model::EmailChange>(&stmt, &[&user_id]).await?)
+    }
+    pub async fn add_email_change(
+        &self,
+        change: &model::EmailChange,
+    ) -> Result<model::EmailChange> {
+        let db = &self.db;
+        let client = &db.client().await?;
+        let stmt = client.prepare(CREATE_EMAIL_CHANGE).await?;
+        Ok(client.query::<model::EmailChange>(
+            &stmt,
+            &[
+                &change.id,
+                &change.user_id,
+                &change.old_email,
+                &change.new_email,
+                &change.token,
+                &change.revert_token,
+                &change.created_at,
+                &change.confirmed_at,
+            ],
+        ).await?
+            .remove(0))
+    }
 }
@@ -0,0 +1,84 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    model,
+    repository::AuditLog,
+    routes,
+    routes::{CREDENTIALS_ROUTE, IMPERSONATION_ROUTE},
+    utilities::{hash, jwt},
+};
+use serde_json::json;
+
+#[actix_rt::test]
+async fn impersonation_tokens_cannot_delete_credentials() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let hashed_password = hash::generate(&helper::settings().hash, &password).unwrap();
+    db.add_credentials(&model::FullRequest::new(&name, &email, &hashed_password)).await;
+    let stored = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    let token =
+        jwt::generate_impersonation_token(&helper::settings().jwt, stored.clone(), stored.id + 1, 60).unwrap();
+    let req = test::TestRequest::delete()
+        .uri(CREDENTIALS_ROUTE)
+        .header(http::header::AUTHORIZATION, token)
+        .set_json(&model::EmailRequest::new(&email, &password))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    let remaining = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::FORBIDDEN);
+    assert!(remaining.deleted_at.is_none());
+}
+
+#[actix_rt::test]
+async fn requires_an_admin_session_to_impersonate() {
+    let data = helper::init_data().await;
+    let req = test::TestRequest::post()
+        .uri(IMPERSONATION_ROUTE)
+        .header(http::header::AUTHORIZATION, "Bearer invalid")
+        .set_json(&model::ImpersonationRequest { user_id: 1 })
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn records_issued_impersonations_in_the_audit_log() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    data.audit_log
+        .record(model::AuditEventType::ImpersonationIssued, Some(user_id), json!({ "actor": 1 }))
+        .await
+        .unwrap();
+    let events = data
+        .audit_log
+        .query(&model::AuditFilter {
+            user_id: Some(user_id),
+            event_type: Some(model::AuditEventType::ImpersonationIssued),
+            ..model::AuditFilter::default()
+        })
+        .await
+        .unwrap();
+    let verification = data.audit_log.verify().await.unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(events.len(), 1);
+    assert!(verification.valid);
+}
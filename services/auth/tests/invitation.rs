@@ -0,0 +1,68 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    model,
+    repository::{Credentials, Invitations},
+    routes,
+    routes::INVITATION_ROUTE,
+};
+use std::time::Duration;
+
+const INVITATION_LIFETIME: Duration = Duration::from_secs(60);
+
+#[actix_rt::test]
+async fn invitations_can_only_be_claimed_once() {
+    let data = helper::init_data().await;
+    let (_, email, _) = helper::fake_credentials();
+    let request = model::InvitationRequest {
+        email: email.clone(),
+        role: Some(String::from("council_member")),
+        expires_in: None,
+    };
+    let created = data.invitations.create(&request, 1, INVITATION_LIFETIME).await.unwrap();
+    let found = data.invitations.by_token(&created.token).await.unwrap().unwrap();
+    assert_eq!(found.id, created.id);
+    assert!(found.for_email(&email));
+    let db = helper::Helper::new().await.unwrap();
+    let (name, _, password) = helper::fake_credentials();
+    let (other_name, _, _) = helper::fake_credentials();
+    let first = model::FullRequest::new(&name, &email, &password);
+    let second = model::FullRequest::new(&other_name, &email, &password);
+    let saved = data.credentials.save_invited_credentials(&first, &found).await.unwrap();
+    let reused = data.credentials.save_invited_credentials(&second, &found).await.unwrap();
+    let orphan = db.get_credentials_by_name(&other_name).await.unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(saved.unwrap().name, name);
+    assert!(reused.is_none());
+    assert!(orphan.is_none());
+}
+
+#[actix_rt::test]
+async fn does_not_find_unknown_tokens() {
+    let data = helper::init_data().await;
+    assert!(data.invitations.by_token("unknown").await.unwrap().is_none());
+}
+
+#[actix_rt::test]
+async fn requires_an_admin_session_to_invite() {
+    let data = helper::init_data().await;
+    let req = test::TestRequest::post()
+        .uri(INVITATION_ROUTE)
+        .header(http::header::AUTHORIZATION, "Bearer invalid")
+        .set_json(&model::InvitationRequest {
+            email: String::from("invitee@example.org"),
+            role: None,
+            expires_in: None,
+        })
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::UNAUTHORIZED);
+}
@@ -0,0 +1,178 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    configuration::MAGIC_LINK_TIME_PERIOD,
+    routes::MAGIC_LINK_ROUTE,
+    utilities::hash,
+    routes,
+    model,
+};
+use std::{
+    ops::Sub,
+    time::{Duration, SystemTime},
+};
+
+fn magic_link(user_id: model::CredentialId, email: &str, id: &str, token: &str) -> model::MagicLink {
+    model::MagicLink {
+        id: String::from(id),
+        user_id,
+        token: hash::generate(&helper::settings().hash, token).unwrap(),
+        email: String::from(email),
+        created_at: SystemTime::now(),
+        used_at: None,
+    }
+}
+
+#[actix_rt::test]
+async fn returns_accepted_and_creates_a_link_when_credentials_exist() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let credentials = model::FullRequest::new(&name, &email, &password);
+    let request_data = model::MagicLinkRequest::new(&email);
+    db.add_credentials(&credentials).await;
+    let req = test::TestRequest::post()
+        .uri(MAGIC_LINK_ROUTE)
+        .set_json(&request_data)
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let resp = test::call_service(&mut server, req).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    let links = db.get_magic_links(&user_id).await.unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::ACCEPTED);
+    assert_eq!(links.len(), 1);
+}
+
+#[actix_rt::test]
+async fn returns_accepted_if_no_matching_credentials_exist() {
+    let data = helper::init_data().await;
+    let (_name, email, _password) = helper::fake_credentials();
+    let request_data = model::MagicLinkRequest::new(&email);
+    let req = test::TestRequest::post()
+        .uri(MAGIC_LINK_ROUTE)
+        .set_json(&request_data)
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::ACCEPTED);
+}
+
+#[actix_rt::test]
+async fn signs_in_with_a_valid_link() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (id, token) = (hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    db.add_magic_link(&magic_link(user_id, &email, &id, &token)).await.unwrap();
+    let req = test::TestRequest::put()
+        .uri(MAGIC_LINK_ROUTE)
+        .set_json(&model::MagicLinkConfirmation::new(&id, &token))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let resp = test::call_service(&mut server, req).await;
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    assert!(resp.headers().contains_key(http::header::AUTHORIZATION));
+}
+
+#[actix_rt::test]
+async fn returns_gone_when_a_link_is_reused() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (id, token) = (hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    db.add_magic_link(&magic_link(user_id, &email, &id, &token)).await.unwrap();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let confirmation = model::MagicLinkConfirmation::new(&id, &token);
+    let first = test::TestRequest::put()
+        .uri(MAGIC_LINK_ROUTE)
+        .set_json(&confirmation)
+        .to_request();
+    let second = test::TestRequest::put()
+        .uri(MAGIC_LINK_ROUTE)
+        .set_json(&confirmation)
+        .to_request();
+    test::call_service(&mut server, first).await;
+    let resp = test::call_service(&mut server, second).await;
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::GONE);
+    assert!(!resp.headers().contains_key(http::header::AUTHORIZATION));
+}
+
+#[actix_rt::test]
+async fn returns_gone_when_a_link_has_expired() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (id, token) = (hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    let mut link = magic_link(user_id, &email, &id, &token);
+    link.created_at = SystemTime::now().sub(Duration::from_secs(MAGIC_LINK_TIME_PERIOD + 1));
+    db.add_magic_link(&link).await.unwrap();
+    let req = test::TestRequest::put()
+        .uri(MAGIC_LINK_ROUTE)
+        .set_json(&model::MagicLinkConfirmation::new(&id, &token))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let resp = test::call_service(&mut server, req).await;
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::GONE);
+}
+
+#[actix_rt::test]
+async fn returns_unauthorized_when_the_account_is_suspended() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let (id, token) = (hash::token(), hash::token());
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    db.suspend_user(&user_id).await;
+    db.add_magic_link(&magic_link(user_id, &email, &id, &token)).await.unwrap();
+    let req = test::TestRequest::put()
+        .uri(MAGIC_LINK_ROUTE)
+        .set_json(&model::MagicLinkConfirmation::new(&id, &token))
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+        .await;
+    let resp = test::call_service(&mut server, req).await;
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::UNAUTHORIZED);
+}
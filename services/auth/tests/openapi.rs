@@ -0,0 +1,105 @@
+extern crate btp_auth_server;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    openapi,
+    routes,
+    routes::{
+        AUDIT_ROUTE, AUDIT_VERIFICATION_ROUTE, CREDENTIALS_ROUTE, DEVICE_DISOWN_ROUTE, DEVICE_ROUTE,
+        EMAIL_CHANGE_ROUTE, HEALTH_ROUTE, IMPERSONATION_ROUTE, INVITATION_ROUTE, LIVE_ROUTE, MAGIC_LINK_ROUTE,
+        METRICS_ROUTE, OPENAPI_ROUTE, PASSWORD_RESET_ROUTE, PERSONAL_ACCESS_TOKEN_ROUTE, READY_ROUTE,
+        SERVICE_CLIENT_ROTATE_ROUTE, SERVICE_CLIENT_ROUTE, TOKEN_ROUTE, VERIFICATION_ROUTE,
+    },
+};
+use serde_json::json;
+
+fn http_method(method: openapi::Method) -> http::Method {
+    match method {
+        openapi::Method::Get => http::Method::GET,
+        openapi::Method::Post => http::Method::POST,
+        openapi::Method::Put => http::Method::PUT,
+        openapi::Method::Delete => http::Method::DELETE,
+        openapi::Method::Patch => http::Method::PATCH,
+    }
+}
+
+// The specification itself and the Prometheus exposition are not JSON APIs.
+const UNDOCUMENTED: &[&str] = &[OPENAPI_ROUTE, METRICS_ROUTE];
+
+fn served_paths() -> Vec<String> {
+    vec![
+        String::from(VERIFICATION_ROUTE),
+        String::from(CREDENTIALS_ROUTE),
+        String::from(PASSWORD_RESET_ROUTE),
+        String::from(MAGIC_LINK_ROUTE),
+        String::from(EMAIL_CHANGE_ROUTE),
+        format!("{}{}", HEALTH_ROUTE, LIVE_ROUTE),
+        format!("{}{}", HEALTH_ROUTE, READY_ROUTE),
+        String::from(AUDIT_ROUTE),
+        format!("{}{}", AUDIT_ROUTE, AUDIT_VERIFICATION_ROUTE),
+        String::from(INVITATION_ROUTE),
+        format!("{}{}", DEVICE_ROUTE, DEVICE_DISOWN_ROUTE),
+        String::from(IMPERSONATION_ROUTE),
+        String::from(SERVICE_CLIENT_ROUTE),
+        format!("{}{}", SERVICE_CLIENT_ROUTE, SERVICE_CLIENT_ROTATE_ROUTE),
+        String::from(TOKEN_ROUTE),
+        String::from(PERSONAL_ACCESS_TOKEN_ROUTE),
+        String::from(OPENAPI_ROUTE),
+        String::from(METRICS_ROUTE),
+    ]
+}
+
+fn documented(path: &str, method: openapi::Method) -> bool {
+    openapi::OPERATIONS
+        .iter()
+        .any(|operation| operation.path == path && operation.method == method)
+}
+
+#[actix_rt::test]
+async fn serves_the_specification() {
+    let req = test::TestRequest::get().uri(OPENAPI_ROUTE).to_request();
+    let mut server = test::init_service(App::new().configure(routes::configuration)).await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body, openapi::specification());
+}
+
+#[actix_rt::test]
+async fn documents_exactly_the_routes_that_are_served() {
+    let mut server = test::init_service(App::new().configure(routes::configuration)).await;
+    let served_paths = served_paths();
+    let mut paths: Vec<&str> = served_paths
+        .iter()
+        .map(String::as_str)
+        .filter(|path| !UNDOCUMENTED.contains(path))
+        .collect();
+    for operation in openapi::OPERATIONS {
+        if !paths.contains(&operation.path) {
+            paths.push(operation.path);
+        }
+    }
+    for path in paths {
+        let mut any_served = false;
+        for method in openapi::Method::ALL.iter() {
+            let req = test::TestRequest::default()
+                .method(http_method(*method))
+                .uri(path)
+                .set_json(&json!({}))
+                .to_request();
+            let status = test::call_service(&mut server, req).await.status();
+            let served = status != status_codes::NOT_FOUND
+                && status != http::StatusCode::METHOD_NOT_ALLOWED;
+            any_served |= served;
+            assert_eq!(
+                served,
+                documented(path, *method),
+                "{} {} is {} but the specification disagrees",
+                method.name(),
+                path,
+                if served { "served" } else { "not served" },
+            );
+        }
+        assert!(any_served, "{} is not served", path);
+    }
+}
@@ -0,0 +1,68 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    model,
+    repository::PersonalAccessTokens,
+    routes,
+    routes::VERIFICATION_ROUTE,
+};
+
+fn token_request() -> model::PersonalAccessTokenRequest {
+    model::PersonalAccessTokenRequest {
+        name: String::from("Bill scraper"),
+        scopes: vec![String::from("bills:read")],
+        expires_in: None,
+    }
+}
+
+#[actix_rt::test]
+async fn verifies_tokens_until_they_are_revoked() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    let created = data.personal_access_tokens.create(user_id, &token_request(), None).await.unwrap();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let verify = || {
+        test::TestRequest::get()
+            .uri(VERIFICATION_ROUTE)
+            .header(http::header::AUTHORIZATION, format!("Bearer {}", created.token))
+            .to_request()
+    };
+    let resp = test::call_service(&mut server, verify()).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    let session: model::Session = test::read_body_json(resp).await;
+    let listed = data.personal_access_tokens.for_user(user_id).await.unwrap();
+    assert!(data.personal_access_tokens.revoke(user_id, &created.id).await.unwrap());
+    let revoked = test::call_service(&mut server, verify()).await;
+    let remaining = data.personal_access_tokens.for_user(user_id).await.unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(session.id, user_id);
+    assert_eq!(session.scopes, Some(vec![String::from("bills:read")]));
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].last_used_at.is_some());
+    assert_ne!(listed[0].token, created.token);
+    assert_eq!(revoked.status(), status_codes::UNAUTHORIZED);
+    assert!(remaining.is_empty());
+}
+
+#[actix_rt::test]
+async fn only_the_owner_can_revoke_a_token() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    db.add_credentials(&model::FullRequest::new(&name, &email, &password)).await;
+    let user_id = db.get_credentials_by_name(&name).await.unwrap().unwrap().id;
+    let created = data.personal_access_tokens.create(user_id, &token_request(), None).await.unwrap();
+    let revoked_by_someone_else = data.personal_access_tokens.revoke(user_id + 1, &created.id).await.unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert!(!revoked_by_someone_else);
+}
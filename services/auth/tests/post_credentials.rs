@@ -140,6 +140,32 @@ async fn returns_conflict_if_name_exists() {
     assert_eq!(resp.status(), status_codes::CONFLICT);
 }
 
+#[actix_rt::test]
+async fn returns_conflict_for_a_confusable_copy_of_a_name_that_predates_skeletons() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let existing = model::FullRequest::new(&name, &email, &password);
+    db.add_credentials(&existing).await;
+    db.backfill_name_skeletons().await;
+    let (_, other_email, _) = helper::fake_credentials();
+    let request_data = model::FullRequest::new(&name.to_uppercase(), &other_email, &password);
+    let req = test::TestRequest::post()
+        .uri(CREDENTIALS_ROUTE)
+        .set_json(&request_data)
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    db.delete_credentials_by_name(&name).await;
+    db.delete_credentials_by_name(&request_data.name).await;
+    assert_eq!(resp.status(), status_codes::CONFLICT);
+}
+
 #[actix_rt::test]
 async fn does_not_set_auth_token_if_name_exists() {
     let data = helper::init_data().await;
@@ -200,3 +226,23 @@ async fn does_not_set_auth_token_if_password_is_too_weak() {
     let resp = test::call_service(&mut server, req).await;
     assert!(!resp.headers().contains_key(http::header::AUTHORIZATION));
 }
+
+#[actix_rt::test]
+async fn returns_the_error_envelope_for_malformed_json() {
+    let data = helper::init_data().await;
+    let req = test::TestRequest::post()
+        .uri(CREDENTIALS_ROUTE)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .set_payload("{\"name\": ")
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::UNPROCESSABLE_ENTITY);
+    let body: model::ErrorResponse = test::read_body_json(resp).await;
+    assert_eq!(body.code, model::ErrorCode::InvalidRequest);
+}
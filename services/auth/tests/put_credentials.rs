@@ -14,12 +14,12 @@ use std::{
 };
 
 #[actix_rt::test]
-async fn returns_okay_if_the_update_was_successful() {
+async fn returns_accepted_if_the_update_includes_a_new_email() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
     let (name2, email2, password2) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, &password);
     let updated_credentials = model::CredentialsRequest::new(
         &Some(name2.clone()),
@@ -41,7 +41,7 @@ async fn returns_okay_if_the_update_was_successful() {
     .await;
     let resp = test::call_service(&mut server, req).await;
     db.delete_credentials_by_name(&name2).await;
-    assert_eq!(resp.status(), status_codes::OKAY);
+    assert_eq!(resp.status(), status_codes::ACCEPTED);
 }
 
 #[actix_rt::test]
@@ -50,7 +50,7 @@ async fn sets_updated_auth_token_on_successful_response() {
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
     let (name2, email2, password2) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, &password);
     let updated_credentials = model::CredentialsRequest::new(
         &Some(name2.clone()),
@@ -81,7 +81,7 @@ async fn updates_a_users_name() {
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
     let (name2, ..) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, &password);
     let updated_credentials = model::CredentialsRequest::new(&Some(name2.clone()), &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -111,7 +111,7 @@ async fn updates_a_users_password() {
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
     let (name2, _email2, password2) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, &password);
     let updated_credentials =
         model::CredentialsRequest::new(&None, &None, &Some(password2.clone()));
@@ -133,16 +133,16 @@ async fn updates_a_users_password() {
     db.delete_credentials_by_name(&name2).await;
     assert_eq!(&stored_credentials.email, &email);
     assert_eq!(&stored_credentials.name, &name);
-    assert!(utilities::hash::authenticate(&password2, &stored_credentials.hash).unwrap());
+    assert!(utilities::hash::authenticate(&helper::settings().hash, &password2, &stored_credentials.hash).unwrap());
 }
 
 #[actix_rt::test]
-async fn updates_a_users_email() {
+async fn holds_a_new_email_as_pending_until_confirmed() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
     let (_name2, email2, ..) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, &password);
     let updated_credentials = model::CredentialsRequest::new(&None, &Some(email2.clone()), &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -160,8 +160,11 @@ async fn updates_a_users_email() {
     .await;
     test::call_service(&mut server, req).await;
     let stored_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    let email_changes = db.get_email_changes(&stored_credentials.id).await.unwrap();
     db.delete_credentials_by_name(&name).await;
-    assert_eq!(&stored_credentials.email, &email2);
+    assert_eq!(&stored_credentials.email, &email);
+    assert_eq!(email_changes.len(), 1);
+    assert_eq!(&email_changes[0].new_email, &email2);
     assert_eq!(&stored_credentials.hash, &hashed_password);
     assert_eq!(&stored_credentials.name, &name);
 }
@@ -171,7 +174,7 @@ async fn returns_unauthorized_if_auth_credentials_are_invalid() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, "Invalid Password");
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -197,7 +200,7 @@ async fn does_not_set_auth_token_if_unauthorized() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, "Invalid Password");
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -265,7 +268,7 @@ async fn returns_unauthorized_if_a_user_has_been_suspended() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, &password);
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -293,7 +296,7 @@ async fn suspends_a_user_if_they_have_exceeded_the_allowed_failed_update_attempt
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, "Invalid password");
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -323,7 +326,7 @@ async fn deletes_the_login_history_once_a_user_has_been_suspended() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, "Invalid password");
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -353,7 +356,7 @@ async fn deletes_login_history_if_previous_update_failures_are_expired() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, "Invalid password");
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -390,7 +393,7 @@ async fn does_not_suspend_user_if_previous_update_failures_are_expired() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, "Invalid password");
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
@@ -427,7 +430,7 @@ async fn creates_a_log_of_failed_login_attempts() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let auth_credentials = model::EmailRequest::new(&email, "Invalid password");
     let updated_credentials = model::CredentialsRequest::new(&None, &None, &None);
     let request_data = model::UpdateCredentials::new(&auth_credentials, &updated_credentials);
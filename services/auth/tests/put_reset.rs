@@ -19,7 +19,7 @@ async fn returns_accepted_when_password_reset_is_successful() {
     let (name, email, password) = helper::fake_credentials();
     let id = hash::token();
     let reset_token = hash::token();
-    let hashed_token = hash::generate(&reset_token).unwrap();
+    let hashed_token = hash::generate(&helper::settings().hash, &reset_token).unwrap();
     let request_data = model::ResetConfirmation::new(&id, &reset_token, &password);
     let credentials = model::FullRequest::new(&name, &email, &password);
     let req = test::TestRequest::put()
@@ -77,7 +77,7 @@ async fn returns_accepted_when_an_invalid_token_is_received() {
     let id = hash::token();
     let invalid_token = hash::token();
     let reset_token = hash::token();
-    let hashed_token = hash::generate(&reset_token).unwrap();
+    let hashed_token = hash::generate(&helper::settings().hash, &reset_token).unwrap();
     let request_data = model::ResetConfirmation::new(&id, &invalid_token, &password);
     let credentials = model::FullRequest::new(&name, &email, &password);
     let req = test::TestRequest::put()
@@ -114,7 +114,7 @@ async fn returns_gone_if_the_reset_request_has_expired() {
     let id = hash::token();
     let invalid_token = hash::token();
     let reset_token = hash::token();
-    let hashed_token = hash::generate(&reset_token).unwrap();
+    let hashed_token = hash::generate(&helper::settings().hash, &reset_token).unwrap();
     let request_data = model::ResetConfirmation::new(&id, &invalid_token, &password);
     let credentials = model::FullRequest::new(&name, &email, &password);
     let req = test::TestRequest::put()
@@ -151,7 +151,7 @@ async fn returns_forbidden_when_a_password_is_too_weak() {
     let id = hash::token();
     let invalid_token = hash::token();
     let reset_token = hash::token();
-    let hashed_token = hash::generate(&reset_token).unwrap();
+    let hashed_token = hash::generate(&helper::settings().hash, &reset_token).unwrap();
     let request_data = model::ResetConfirmation::new(&id, &invalid_token, &helper::WEAK_PASSWORD);
     let credentials = model::FullRequest::new(&name, &email, &password);
     let req = test::TestRequest::put()
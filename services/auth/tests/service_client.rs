@@ -0,0 +1,115 @@
+extern crate btp_auth_server;
+pub mod helper;
+use actix_rt;
+use actix_web::{http, test, App};
+use btp_auth_server::{
+    model,
+    repository::ServiceClients,
+    routes,
+    routes::{TOKEN_ROUTE, VERIFICATION_ROUTE},
+};
+use std::time::Duration;
+
+const ADMIN_ID: model::CredentialId = 1;
+const GRACE: Duration = Duration::from_secs(60);
+
+fn client_request() -> model::ServiceClientRequest {
+    model::ServiceClientRequest {
+        name: String::from("Bill importer"),
+        scopes: vec![String::from("bills:read"), String::from("bills:write")],
+        audiences: vec![],
+    }
+}
+
+fn token_request(client_id: &str, client_secret: &str, scope: &str) -> test::TestRequest {
+    test::TestRequest::post()
+        .uri(TOKEN_ROUTE)
+        .header(http::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .set_payload(format!(
+            "grant_type=client_credentials&client_id={}&client_secret={}&scope={}",
+            client_id, client_secret, scope
+        ))
+}
+
+#[actix_rt::test]
+async fn issues_scoped_tokens_that_verify_as_the_client() {
+    let data = helper::init_data().await;
+    let client = data.service_clients.create(&client_request(), ADMIN_ID).await.unwrap();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, token_request(&client.client_id, &client.secret, "bills:read").to_request()).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    let issued: model::TokenResponse = test::read_body_json(resp).await;
+    assert_eq!(issued.scope, "bills:read");
+    let req = test::TestRequest::get()
+        .uri(VERIFICATION_ROUTE)
+        .header(http::header::AUTHORIZATION, format!("Bearer {}", issued.access_token))
+        .to_request();
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    let session: model::ServiceSession = test::read_body_json(resp).await;
+    assert_eq!(session.subject_type, model::SERVICE_SUBJECT);
+    assert_eq!(session.client_id, client.client_id);
+    assert_eq!(session.scopes, vec![String::from("bills:read")]);
+}
+
+#[actix_rt::test]
+async fn rejects_wrong_secrets_and_unheld_scopes() {
+    let data = helper::init_data().await;
+    let client = data.service_clients.create(&client_request(), ADMIN_ID).await.unwrap();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, token_request(&client.client_id, "wrong", "").to_request()).await;
+    assert_eq!(resp.status(), status_codes::UNAUTHORIZED);
+    let resp = test::call_service(&mut server, token_request(&client.client_id, &client.secret, "users:write").to_request()).await;
+    assert_eq!(resp.status(), status_codes::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_rt::test]
+async fn returns_the_error_envelope_for_a_malformed_form() {
+    let data = helper::init_data().await;
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri(TOKEN_ROUTE)
+        .header(http::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .set_payload("client_id=only")
+        .to_request();
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::UNPROCESSABLE_ENTITY);
+    let body: model::ErrorResponse = test::read_body_json(resp).await;
+    assert_eq!(body.code, model::ErrorCode::InvalidRequest);
+}
+
+#[actix_rt::test]
+async fn accepts_the_previous_secret_during_the_rotation_grace_period() {
+    let data = helper::init_data().await;
+    let client = data.service_clients.create(&client_request(), ADMIN_ID).await.unwrap();
+    let rotated = data.service_clients.rotate(&client.client_id, ADMIN_ID, GRACE).await.unwrap().unwrap();
+    let stored = data.service_clients.by_client_id(&client.client_id).await.unwrap().unwrap();
+    assert_ne!(rotated.secret, client.secret);
+    assert!(stored.authenticate(&helper::settings().hash, &rotated.secret).unwrap());
+    assert!(stored.authenticate(&helper::settings().hash, &client.secret).unwrap());
+    let rotated_again = data.service_clients.rotate(&client.client_id, ADMIN_ID, GRACE).await.unwrap().unwrap();
+    let stored = data.service_clients.by_client_id(&client.client_id).await.unwrap().unwrap();
+    assert!(stored.authenticate(&helper::settings().hash, &rotated_again.secret).unwrap());
+    assert!(!stored.authenticate(&helper::settings().hash, &client.secret).unwrap());
+}
+
+#[actix_rt::test]
+async fn does_not_rotate_unknown_clients() {
+    let data = helper::init_data().await;
+    assert!(data.service_clients.rotate("unknown", ADMIN_ID, GRACE).await.unwrap().is_none());
+}
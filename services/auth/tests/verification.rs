@@ -18,7 +18,7 @@ async fn authenticate_credentials_success_status() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let request_data = model::NameRequest::new(&name, &password);
     db.add_credentials(&model::FullRequest::new(&name, &email, &hashed_password))
         .await;
@@ -42,7 +42,7 @@ async fn authenticate_credentials_sets_auth_header() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     let request_data = model::NameRequest::new(&name, &password);
     db.add_credentials(&model::FullRequest::new(&name, &email, &hashed_password))
         .await;
@@ -61,6 +61,57 @@ async fn authenticate_credentials_sets_auth_header() {
     assert!(resp.headers().contains_key(http::header::AUTHORIZATION));
 }
 
+#[actix_rt::test]
+async fn authenticates_credentials_by_email() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
+    let request_data = model::EmailRequest::new(&email, &password);
+    db.add_credentials(&model::FullRequest::new(&name, &email, &hashed_password))
+        .await;
+    let req = test::TestRequest::post()
+        .uri(VERIFICATION_ROUTE)
+        .set_json(&request_data)
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(resp.status(), status_codes::OKAY);
+    assert!(resp.headers().contains_key(http::header::AUTHORIZATION));
+}
+
+#[actix_rt::test]
+async fn creates_a_log_of_failed_login_attempts_by_email() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
+    db.add_credentials(&model::FullRequest::new(&name, &email, &hashed_password))
+        .await;
+    let request_data = model::LoginRequest::new(&email, "Bad Password");
+    let stored_credentials = db.get_credentials_by_name(&name).await.unwrap().unwrap();
+    let req = test::TestRequest::post()
+        .uri(VERIFICATION_ROUTE)
+        .set_json(&request_data)
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    test::call_service(&mut server, req).await;
+    let login_history = db.get_login_history(&stored_credentials.id).await.unwrap();
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(login_history.len(), 1);
+}
+
 #[actix_rt::test]
 async fn errors_with_unauthorized_if_no_record_exists() {
     let data = helper::init_data().await;
@@ -85,7 +136,7 @@ async fn errors_with_unauthorized_if_passwords_do_not_match() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     db.add_credentials(&model::FullRequest::new(&name, &hashed_password, &email))
         .await;
     let request_data = model::NameRequest::new(&name, "invalid password");
@@ -109,7 +160,7 @@ async fn returns_unauthorized_if_a_user_has_been_suspended() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     db.add_credentials(&model::FullRequest::new(&name, &hashed_password, &email))
         .await;
     let request_data = model::NameRequest::new(&name, "invalid password");
@@ -135,7 +186,7 @@ async fn suspends_a_user_if_they_have_exceeded_the_allowed_failed_login_attempts
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     db.add_credentials(&model::FullRequest::new(&name, &hashed_password, &email))
         .await;
     let request_data = model::NameRequest::new(&name, "invalid password");
@@ -163,7 +214,7 @@ async fn deletes_the_login_history_once_a_user_has_been_suspended() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     db.add_credentials(&model::FullRequest::new(&name, &hashed_password, &email))
         .await;
     let request_data = model::NameRequest::new(&name, "invalid password");
@@ -191,7 +242,7 @@ async fn deletes_login_history_if_previous_login_failures_are_expired() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     db.add_credentials(&model::FullRequest::new(&name, &hashed_password, &email))
         .await;
     let request_data = model::NameRequest::new(&name, "invalid password");
@@ -226,7 +277,7 @@ async fn does_not_suspend_user_if_previous_login_failures_are_expired() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     db.add_credentials(&model::FullRequest::new(&name, &hashed_password, &email))
         .await;
     let request_data = model::NameRequest::new(&name, "invalid password");
@@ -261,7 +312,7 @@ async fn creates_a_log_of_failed_login_attempts() {
     let data = helper::init_data().await;
     let db = helper::Helper::new().await.unwrap();
     let (name, email, password) = helper::fake_credentials();
-    let hashed_password = utilities::hash::generate(&password).unwrap();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
     db.add_credentials(&model::FullRequest::new(&name, &hashed_password, &email))
         .await;
     let request_data = model::NameRequest::new(&name, "invalid password");
@@ -281,3 +332,50 @@ async fn creates_a_log_of_failed_login_attempts() {
     db.delete_credentials_by_name(&name).await;
     assert_eq!(login_history.len(), 1);
 }
+
+#[actix_rt::test]
+async fn verifies_the_issued_token() {
+    let data = helper::init_data().await;
+    let db = helper::Helper::new().await.unwrap();
+    let (name, email, password) = helper::fake_credentials();
+    let hashed_password = utilities::hash::generate(&helper::settings().hash, &password).unwrap();
+    let request_data = model::NameRequest::new(&name, &password);
+    db.add_credentials(&model::FullRequest::new(&name, &email, &hashed_password))
+        .await;
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri(VERIFICATION_ROUTE)
+        .set_json(&request_data)
+        .to_request();
+    let resp = test::call_service(&mut server, req).await;
+    let token = resp.headers().get(http::header::AUTHORIZATION).unwrap().clone();
+    let req = test::TestRequest::get()
+        .uri(VERIFICATION_ROUTE)
+        .header(http::header::AUTHORIZATION, token)
+        .to_request();
+    let session: model::Session = test::read_response_json(&mut server, req).await;
+    db.delete_credentials_by_name(&name).await;
+    assert_eq!(session.name, name);
+}
+
+#[actix_rt::test]
+async fn rejects_an_invalid_token() {
+    let data = helper::init_data().await;
+    let req = test::TestRequest::get()
+        .uri(VERIFICATION_ROUTE)
+        .header(http::header::AUTHORIZATION, "Bearer invalid")
+        .to_request();
+    let mut server = test::init_service(
+        App::new()
+            .app_data(data.clone())
+            .configure(routes::configuration),
+    )
+    .await;
+    let resp = test::call_service(&mut server, req).await;
+    assert_eq!(resp.status(), status_codes::UNAUTHORIZED);
+}
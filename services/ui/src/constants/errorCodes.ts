@@ -2,4 +2,25 @@ export const SERVER_ERROR = 500;
 export const OKAY = 200;
 export const UNAUTHORIZED = 401;
 export const INVALID_REQUEST = 422;
-export const NOT_FOUND = 404;
\ No newline at end of file
+export const NOT_FOUND = 404;
+export enum ErrorCode {
+  INVALID_CREDENTIALS = 'INVALID_CREDENTIALS',
+  SUSPENDED = 'SUSPENDED',
+  INVALID_TOKEN = 'INVALID_TOKEN',
+  EXPIRED = 'EXPIRED',
+  CONFLICT = 'CONFLICT',
+  WEAK_PASSWORD = 'WEAK_PASSWORD',
+  INVALID_NAME = 'INVALID_NAME',
+  INVALID_CSRF_TOKEN = 'INVALID_CSRF_TOKEN',
+  FORBIDDEN = 'FORBIDDEN',
+  INVITATION_REQUIRED = 'INVITATION_REQUIRED',
+  INVALID_REQUEST = 'INVALID_REQUEST',
+  CHALLENGE_REQUIRED = 'CHALLENGE_REQUIRED',
+  INTERNAL_ERROR = 'INTERNAL_ERROR',
+}
+
+export interface ErrorResponse {
+  code: ErrorCode;
+  message: string;
+  details?: unknown;
+}